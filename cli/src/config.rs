@@ -0,0 +1,66 @@
+use std::{env, process};
+
+use clap::Subcommand;
+use color_eyre::eyre::{Result, eyre};
+use nohead_rs_config::{Environment, redacted_config};
+use nohead_rs_web::{middlewares::cookie_keys::generate_secret_key, state::AppState};
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Load and validate the configuration and print it with secrets redacted
+    ///
+    /// Checks every environment unless `--env` is given.
+    Check,
+    /// Print a new random key for `[security] secret_key`
+    GenerateKey,
+}
+
+pub fn run(command: ConfigCommand, env: Option<Environment>) -> Result<()> {
+    match command {
+        ConfigCommand::Check => match env {
+            Some(env) => check(&env),
+            None => check_all(),
+        },
+        ConfigCommand::GenerateKey => {
+            println!("{}", generate_secret_key());
+            Ok(())
+        }
+    }
+}
+
+fn check(env: &Environment) -> Result<()> {
+    AppState::load_config(env)?;
+
+    println!("# {env}");
+    println!("{}", redacted_config(env)?);
+
+    Ok(())
+}
+
+/// Checks each environment in a process of its own, so that the `.env` file loaded for one
+/// environment doesn't leak into the next.
+fn check_all() -> Result<()> {
+    let exe = env::current_exe()?;
+    let mut failed = vec![];
+
+    for env in [
+        Environment::Development,
+        Environment::Test,
+        Environment::Staging,
+        Environment::Production,
+    ] {
+        let status = process::Command::new(&exe)
+            .args(["--env", &env.to_string(), "config", "check"])
+            .status()?;
+
+        if !status.success() {
+            failed.push(env.to_string());
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(eyre!("invalid configuration for {}", failed.join(", ")))
+    }
+}
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use color_eyre::eyre::{Context as _, Result};
+use nohead_rs_config::Environment;
+use nohead_rs_db::migrate;
+
+#[derive(Subcommand)]
+pub enum DbCommand {
+    /// Run the seed files against the database
+    Seed {
+        /// The directory of `.sql` seed files, which are run in file name order
+        #[arg(long, default_value = "db/seeds")]
+        dir: PathBuf,
+    },
+}
+
+pub async fn run(command: DbCommand, env: &Environment) -> Result<()> {
+    match command {
+        DbCommand::Seed { dir } => seed(env, dir).await,
+    }
+}
+
+async fn seed(env: &Environment, dir: PathBuf) -> Result<()> {
+    let db_pool = crate::connect(env).await?;
+
+    let files = migrate::seed(&db_pool, &dir)
+        .await
+        .wrap_err_with(|| format!("failed to seed the database from {}", dir.display()))?;
+
+    for file in files {
+        println!("seeded {}", file.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use std::{
+    env, fs,
+    io::Write as _,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use clap::Subcommand;
+use color_eyre::eyre::{Result, WrapErr as _, eyre};
+use similar::TextDiff;
+
+mod scaffold;
+
+#[derive(Subcommand)]
+pub enum GenerateCommand {
+    /// Generate a migration, entity, controller, view and templates for a resource
+    ///
+    /// Fields are given as `name:type`, where type is one of string, text, integer,
+    /// float or boolean, e.g. `nohead-rs generate scaffold post title:string body:text`
+    Scaffold(scaffold::ScaffoldArgs),
+}
+
+pub fn run(command: GenerateCommand) -> Result<()> {
+    match command {
+        GenerateCommand::Scaffold(args) => scaffold::run(args),
+    }
+}
+
+/// A file that a generator creates or changes.
+pub struct FileChange {
+    /// The path of the file, relative to the workspace root.
+    pub path: PathBuf,
+    /// The contents of the file before the change, `None` for new files.
+    pub before: Option<String>,
+    pub after: String,
+}
+
+/// The set of file changes made by a generator, which are either written or printed as a diff.
+pub struct Changes {
+    root: PathBuf,
+    changes: Vec<FileChange>,
+}
+
+impl Changes {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            changes: vec![],
+        }
+    }
+
+    /// Reads a file of the workspace, relative to its root.
+    pub fn read(&self, path: impl AsRef<Path>) -> Result<String> {
+        let path = self.root.join(path);
+        fs::read_to_string(&path).wrap_err_with(|| format!("failed to read {}", path.display()))
+    }
+
+    /// Adds a new file, failing if it already exists.
+    pub fn create(&mut self, path: impl Into<PathBuf>, contents: String) -> Result<()> {
+        let path = path.into();
+        if self.root.join(&path).exists() {
+            return Err(eyre!("{} already exists", path.display()));
+        }
+
+        self.changes.push(FileChange {
+            after: format_if_rust(&path, contents),
+            path,
+            before: None,
+        });
+        Ok(())
+    }
+
+    /// Changes an existing file with `edit`, which receives its current contents.
+    pub fn update(
+        &mut self,
+        path: impl Into<PathBuf>,
+        edit: impl FnOnce(&str) -> Result<String>,
+    ) -> Result<()> {
+        let path = path.into();
+        let before = self.read(&path)?;
+        let after =
+            edit(&before).wrap_err_with(|| format!("failed to update {}", path.display()))?;
+
+        self.changes.push(FileChange {
+            after: format_if_rust(&path, after),
+            path,
+            before: Some(before),
+        });
+        Ok(())
+    }
+
+    /// Prints the changes as a unified diff without touching any files.
+    pub fn print_diff(&self) {
+        for change in &self.changes {
+            let path = change.path.display().to_string();
+            let before = change.before.as_deref().unwrap_or_default();
+            let diff = TextDiff::from_lines(before, &change.after);
+            let old_path = if change.before.is_some() {
+                path.as_str()
+            } else {
+                "/dev/null"
+            };
+
+            print!(
+                "{}",
+                diff.unified_diff()
+                    .context_radius(3)
+                    .header(old_path, &path)
+            );
+        }
+    }
+
+    /// Writes the changes to disk.
+    pub fn write(&self) -> Result<()> {
+        for change in &self.changes {
+            let path = self.root.join(&change.path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, &change.after)
+                .wrap_err_with(|| format!("failed to write {}", path.display()))?;
+
+            let action = if change.before.is_some() {
+                "update"
+            } else {
+                "create"
+            };
+            println!("{action:>8} {}", change.path.display());
+        }
+        Ok(())
+    }
+}
+
+/// Finds the root of the workspace by walking up from the current directory.
+pub fn workspace_root() -> Result<PathBuf> {
+    let cwd = env::current_dir()?;
+
+    cwd.ancestors()
+        .find(|dir| {
+            fs::read_to_string(dir.join("Cargo.toml"))
+                .is_ok_and(|manifest| manifest.contains("[workspace]"))
+        })
+        .map(Path::to_path_buf)
+        .ok_or_else(|| eyre!("could not find the workspace root from {}", cwd.display()))
+}
+
+/// Runs generated Rust code through rustfmt so that it matches the rest of the workspace,
+/// leaving it as is when rustfmt isn't available.
+fn format_if_rust(path: &Path, contents: String) -> String {
+    if path.extension().is_none_or(|extension| extension != "rs") {
+        return contents;
+    }
+
+    let rustfmt = Command::new("rustfmt")
+        .args(["--edition", "2024", "--emit", "stdout", "--quiet"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut rustfmt) = rustfmt else {
+        return contents;
+    };
+
+    // stdin is dropped once written so rustfmt sees the end of the input
+    if rustfmt
+        .stdin
+        .take()
+        .is_some_and(|mut stdin| stdin.write_all(contents.as_bytes()).is_err())
+    {
+        return contents;
+    }
+
+    match rustfmt.wait_with_output() {
+        Ok(output) if output.status.success() => {
+            String::from_utf8(output.stdout).unwrap_or(contents)
+        }
+        _ => contents,
+    }
+}
@@ -0,0 +1,502 @@
+use std::{fmt, str::FromStr};
+
+use clap::Args;
+use color_eyre::eyre::{Result, bail, eyre};
+use heck::{ToSnakeCase as _, ToTitleCase as _, ToUpperCamelCase as _};
+
+use super::{Changes, workspace_root};
+
+#[derive(Args)]
+pub struct ScaffoldArgs {
+    /// The singular name of the resource, e.g. `post`
+    name: String,
+    /// The fields of the resource as `name:type`
+    #[arg(required = true)]
+    fields: Vec<Field>,
+    /// The plural name of the resource, used for the table, routes and templates
+    #[arg(long)]
+    plural: Option<String>,
+    /// Print the changes as a diff instead of writing them
+    #[arg(long)]
+    dry_run: bool,
+}
+
+pub fn run(args: ScaffoldArgs) -> Result<()> {
+    let names = Names::new(&args.name, args.plural.as_deref())?;
+    let mut changes = Changes::new(workspace_root()?);
+
+    scaffold(&mut changes, &names, &args.fields)?;
+
+    if args.dry_run {
+        changes.print_diff();
+        return Ok(());
+    }
+
+    changes.write()?;
+    println!(
+        "\nApply the new migration before building, as the {} queries are checked against the database, e.g.\n\n    nohead-rs migrate up",
+        names.pascal
+    );
+
+    Ok(())
+}
+
+/// Adds every file of the scaffold to the changes.
+fn scaffold(changes: &mut Changes, names: &Names, fields: &[Field]) -> Result<()> {
+    let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
+
+    changes.create(
+        format!(
+            "db/migrations/{timestamp}_create_{}_table.sql",
+            names.plural
+        ),
+        names.render(
+            include_str!("../../templates/scaffold/migration.sql"),
+            fields,
+        ),
+    )?;
+    changes.create(
+        format!("db/src/entities/{}.rs", names.snake),
+        names.render(include_str!("../../templates/scaffold/entity.rs"), fields),
+    )?;
+    changes.update("db/src/entities/mod.rs", |src| add_mod(src, &names.snake))?;
+    changes.create(
+        format!("web/src/controllers/{}.rs", names.plural),
+        names.render(
+            include_str!("../../templates/scaffold/controller.rs"),
+            fields,
+        ),
+    )?;
+    changes.update("web/src/controllers/mod.rs", |src| {
+        add_mod(src, &names.plural)
+    })?;
+    changes.create(
+        format!("web/src/views/{}.rs", names.plural),
+        names.render(include_str!("../../templates/scaffold/view.rs"), fields),
+    )?;
+    changes.update("web/src/views/mod.rs", |src| add_mod(src, &names.plural))?;
+    for template in ["index", "new", "form", "show", "update"] {
+        let contents = match template {
+            "index" => include_str!("../../templates/scaffold/index.html"),
+            "new" => include_str!("../../templates/scaffold/new.html"),
+            "form" => include_str!("../../templates/scaffold/form.html"),
+            "show" => include_str!("../../templates/scaffold/show.html"),
+            _ => include_str!("../../templates/scaffold/update.html"),
+        };
+        changes.create(
+            format!("web/templates/{}/{template}.html", names.plural),
+            names.render(contents, fields),
+        )?;
+    }
+    changes.update("web/src/router.rs", |src| register_controller(src, names))?;
+
+    Ok(())
+}
+
+/// The column types a scaffold field can have.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FieldType {
+    String,
+    Text,
+    Integer,
+    Float,
+    Boolean,
+}
+
+impl FieldType {
+    fn sql(&self) -> &'static str {
+        match self {
+            FieldType::String | FieldType::Text => "TEXT",
+            FieldType::Integer => "INTEGER",
+            FieldType::Float => "REAL",
+            FieldType::Boolean => "BOOLEAN",
+        }
+    }
+
+    fn rust(&self) -> &'static str {
+        match self {
+            FieldType::String | FieldType::Text => "String",
+            FieldType::Integer => "i64",
+            FieldType::Float => "f64",
+            FieldType::Boolean => "bool",
+        }
+    }
+}
+
+/// A field of a scaffolded resource, parsed from `name:type`.
+#[derive(Clone, Debug, PartialEq)]
+struct Field {
+    name: String,
+    ty: FieldType,
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, ty) = s
+            .split_once(':')
+            .ok_or_else(|| format!("expected `name:type` but got `{s}`"))?;
+
+        let ty = match ty {
+            "string" => FieldType::String,
+            "text" => FieldType::Text,
+            "integer" | "int" => FieldType::Integer,
+            "float" | "real" => FieldType::Float,
+            "boolean" | "bool" => FieldType::Boolean,
+            _ => {
+                return Err(format!(
+                    "unknown type `{ty}`, expected one of string, text, integer, float or boolean"
+                ));
+            }
+        };
+
+        if name.is_empty() || name.to_snake_case() != name {
+            return Err(format!("field names must be snake_case but got `{name}`"));
+        }
+        if ["id", "user_id"].contains(&name) {
+            return Err(format!("`{name}` is added to every scaffold already"));
+        }
+
+        Ok(Field {
+            name: name.to_string(),
+            ty,
+        })
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Field {
+    fn title(&self) -> String {
+        self.name.to_title_case()
+    }
+
+    /// The input of the field in a form, filled in with the form's values, see
+    /// `nohead_rs_web::forms`.
+    fn input(&self) -> String {
+        let name = &self.name;
+        let input = match self.ty {
+            FieldType::String => {
+                format!(r#"<input type="text" name="{name}" value="{{{{ form.{name} }}}}" />"#)
+            }
+            FieldType::Text => {
+                format!(r#"<textarea name="{name}">{{{{ form.{name} }}}}</textarea>"#)
+            }
+            FieldType::Integer => {
+                format!(r#"<input type="number" name="{name}" value="{{{{ form.{name} }}}}" />"#)
+            }
+            FieldType::Float => format!(
+                r#"<input type="number" step="any" name="{name}" value="{{{{ form.{name} }}}}" />"#
+            ),
+            FieldType::Boolean => format!(
+                r#"<input type="checkbox" name="{name}" value="true"{{% if form.{name} %}} checked{{% endif %}} />"#
+            ),
+        };
+        let errors = format!(
+            "<span id=\"{name}-errors\">\n    {{% for error in errors.{name} %}}<p>{{{{ error }}}}</p>{{% endfor %}}\n</span>\n"
+        );
+
+        format!(
+            "<label>\n    {}:\n    {input}\n</label>\n{errors}",
+            self.title()
+        )
+    }
+}
+
+/// The names of a resource in the cases used across the generated files.
+struct Names {
+    /// e.g. `blog_post`
+    snake: String,
+    /// e.g. `BlogPost`
+    pascal: String,
+    /// e.g. `blog_posts`
+    plural: String,
+}
+
+impl Names {
+    fn new(name: &str, plural: Option<&str>) -> Result<Self> {
+        let snake = name.to_snake_case();
+        if snake.is_empty() {
+            bail!("the resource needs a name");
+        }
+        let plural = plural
+            .map(|plural| plural.to_snake_case())
+            .unwrap_or_else(|| pluralize(&snake));
+        if plural == snake {
+            return Err(eyre!(
+                "the plural of `{snake}` is the same as the singular, pass a different one with --plural"
+            ));
+        }
+
+        Ok(Self {
+            pascal: snake.to_upper_camel_case(),
+            snake,
+            plural,
+        })
+    }
+
+    /// Fills in the placeholders of a scaffold template.
+    fn render(&self, template: &str, fields: &[Field]) -> String {
+        let title = self.snake.replace('_', " ");
+        let plural_title = self.plural.replace('_', " ");
+        let display = fields
+            .iter()
+            .find(|field| field.ty == FieldType::String)
+            .or_else(|| fields.iter().find(|field| field.ty == FieldType::Text))
+            .map(|field| field.name.as_str())
+            .unwrap_or("id");
+
+        template
+            .replace("__columns__", &columns(fields))
+            .replace("__entity_args__", &entity_args(fields))
+            .replace("__record_fields__", &record_fields(fields))
+            .replace("__changeset_fields__", &changeset_fields(fields))
+            .replace("__inputs__", &indent(&inputs(fields), 4))
+            .replace("__display__", display)
+            .replace("__Pascal__", &self.pascal)
+            .replace("__snake__", &self.snake)
+            .replace("__plural__", &self.plural)
+            .replace("__Plural_title__", &capitalize(&plural_title))
+            .replace("__plural_title__", &plural_title)
+            .replace("__Title__", &capitalize(&title))
+            .replace("__title__", &title)
+    }
+}
+
+fn columns(fields: &[Field]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("{} {} NOT NULL,\n", field.name, field.ty.sql()))
+        .collect()
+}
+
+fn entity_args(fields: &[Field]) -> String {
+    let sortable = std::iter::once("id".to_string())
+        .chain(fields.iter().map(ToString::to_string))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let filterable = fields
+        .iter()
+        .filter(|field| matches!(field.ty, FieldType::String | FieldType::Text))
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+
+    if filterable.is_empty() {
+        format!("    sortable({sortable})\n")
+    } else {
+        format!(
+            "    sortable({sortable}),\n    filterable({})\n",
+            filterable.join(", ")
+        )
+    }
+}
+
+fn record_fields(fields: &[Field]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            format!(
+                "    /// The {}.\n    pub {}: {},\n",
+                field.title().to_lowercase(),
+                field.name,
+                field.ty.rust()
+            )
+        })
+        .collect()
+}
+
+fn changeset_fields(fields: &[Field]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            let attrs = match field.ty {
+                FieldType::String => format!(
+                    "    #[validate(length(min = 1, message = \"{} must be at least 1 character long\"))]\n",
+                    field.title()
+                ),
+                // Unchecked checkboxes are left out of the form
+                FieldType::Boolean => "    #[serde(default)]\n".to_string(),
+                _ => String::new(),
+            };
+            format!("{attrs}    pub {}: {},\n", field.name, field.ty.rust())
+        })
+        .collect()
+}
+
+fn inputs(fields: &[Field]) -> String {
+    fields.iter().map(Field::input).collect()
+}
+
+fn indent(text: &str, spaces: usize) -> String {
+    text.lines()
+        .map(|line| format!("{}{line}\n", " ".repeat(spaces)))
+        .collect()
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// A naive English plural, pass `--plural` for anything irregular.
+fn pluralize(word: &str) -> String {
+    if let Some(stem) = word
+        .strip_suffix('y')
+        .filter(|stem| !stem.ends_with(['a', 'e', 'i', 'o', 'u']))
+    {
+        return format!("{stem}ies");
+    }
+    if word.ends_with(['s', 'x', 'z']) || word.ends_with("ch") || word.ends_with("sh") {
+        return format!("{word}es");
+    }
+    format!("{word}s")
+}
+
+/// Adds a `pub mod` declaration in alphabetical order to a module's list of submodules.
+fn add_mod(src: &str, module: &str) -> Result<String> {
+    let declaration = format!("pub mod {module};");
+    let mut lines: Vec<&str> = src.lines().collect();
+
+    if lines.contains(&declaration.as_str()) {
+        bail!("the `{module}` module already exists");
+    }
+
+    let mods: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.starts_with("pub mod ") && line.ends_with(';'))
+        .map(|(i, _)| i)
+        .collect();
+    let position = mods
+        .iter()
+        .find(|&&i| lines[i] > declaration.as_str())
+        .copied()
+        .or_else(|| mods.last().map(|i| i + 1))
+        .unwrap_or(lines.len());
+
+    lines.insert(position, &declaration);
+
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Imports the scaffold's controller in the router and merges it into the routes that
+/// require a logged in user.
+fn register_controller(src: &str, names: &Names) -> Result<String> {
+    let import = format!("{}::{}Controller,", names.plural, names.pascal);
+    let merge = format!(
+        ".merge(table.add({0}Controller::ROUTES, {0}Controller::router()))",
+        names.pascal
+    );
+
+    // The controllers are imported in a nested `controllers::{...}` group
+    let group = src
+        .find("controllers::{")
+        .ok_or_else(|| eyre!("could not find the controller imports"))?;
+    let group_end = src[group..]
+        .find("\n    },")
+        .map(|end| group + end)
+        .ok_or_else(|| eyre!("could not find the end of the controller imports"))?;
+
+    // The protected routes are the ones merged before the auth route layers
+    let route_layer = src
+        .find(".route_layer(")
+        .ok_or_else(|| eyre!("could not find the protected routes"))?;
+    let line_start = src[..route_layer].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let indent = &src[line_start..route_layer];
+
+    let mut out = String::with_capacity(src.len() + import.len() + merge.len() + 32);
+    out.push_str(&src[..group_end]);
+    out.push_str(&format!("\n        {import}"));
+    out.push_str(&src[group_end..line_start]);
+    out.push_str(&format!("{indent}{merge}\n"));
+    out.push_str(&src[line_start..]);
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fields() {
+        assert_eq!(
+            "title:string".parse::<Field>(),
+            Ok(Field {
+                name: "title".into(),
+                ty: FieldType::String,
+            })
+        );
+        assert!("title".parse::<Field>().is_err());
+        assert!("title:uuid".parse::<Field>().is_err());
+        assert!("Title:string".parse::<Field>().is_err());
+        assert!("user_id:integer".parse::<Field>().is_err());
+    }
+
+    #[test]
+    fn pluralizes_names() {
+        assert_eq!(pluralize("post"), "posts");
+        assert_eq!(pluralize("category"), "categories");
+        assert_eq!(pluralize("day"), "days");
+        assert_eq!(pluralize("box"), "boxes");
+        assert_eq!(pluralize("blog_post"), "blog_posts");
+    }
+
+    #[test]
+    fn adds_mods_in_order() {
+        let src = "use foo;\n\npub mod auth;\npub mod todos;\n\npub trait Foo {}\n";
+
+        assert_eq!(
+            add_mod(src, "posts").unwrap(),
+            "use foo;\n\npub mod auth;\npub mod posts;\npub mod todos;\n\npub trait Foo {}\n"
+        );
+        assert_eq!(
+            add_mod(src, "widgets").unwrap(),
+            "use foo;\n\npub mod auth;\npub mod todos;\npub mod widgets;\n\npub trait Foo {}\n"
+        );
+        assert!(add_mod(src, "todos").is_err());
+    }
+
+    #[test]
+    fn registers_controller_in_router() {
+        let src = r#"use crate::{
+    controllers::{
+        home::HomeController,
+        todos::TodoController,
+    },
+};
+
+    let router = Router::new()
+        .merge(table.add(TodoController::ROUTES, TodoController::router()))
+        .route_layer(login_required!(AuthBackend, login_url = "/auth/login"))
+        .merge(table.add(HomeController::ROUTES, HomeController::router()));
+"#;
+        let names = Names::new("post", None).unwrap();
+
+        assert_eq!(
+            register_controller(src, &names).unwrap(),
+            r#"use crate::{
+    controllers::{
+        home::HomeController,
+        todos::TodoController,
+        posts::PostController,
+    },
+};
+
+    let router = Router::new()
+        .merge(table.add(TodoController::ROUTES, TodoController::router()))
+        .merge(table.add(PostController::ROUTES, PostController::router()))
+        .route_layer(login_required!(AuthBackend, login_url = "/auth/login"))
+        .merge(table.add(HomeController::ROUTES, HomeController::router()));
+"#
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use chrono::DateTime;
+use clap::Subcommand;
+use color_eyre::eyre::{Result, eyre};
+use nohead_rs_config::Environment;
+use nohead_rs_db::{
+    Error,
+    entities::job::{Job, JobStatus},
+};
+
+#[derive(Subcommand)]
+pub enum JobsCommand {
+    /// List the most recently scheduled jobs
+    List {
+        /// Only list jobs with this status, e.g. failed
+        #[arg(long)]
+        status: Option<JobStatus>,
+        /// How many jobs to list
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+    },
+    /// Queue a job to run again with a fresh set of attempts
+    Retry {
+        /// The id of the job to retry
+        #[arg(required_unless_present = "failed", conflicts_with = "failed")]
+        id: Option<String>,
+        /// Retry every job that failed or was killed
+        #[arg(long)]
+        failed: bool,
+    },
+    /// Delete jobs with a given status
+    Purge {
+        /// The status of the jobs to delete, may be repeated
+        #[arg(long = "status", default_value = "done")]
+        statuses: Vec<JobStatus>,
+    },
+}
+
+pub async fn run(command: JobsCommand, env: &Environment) -> Result<()> {
+    let db_pool = crate::connect(env).await?;
+
+    match command {
+        JobsCommand::List { status, limit } => {
+            let jobs = Job::load_recent(status, limit, &db_pool).await?;
+            if jobs.is_empty() {
+                println!("no jobs found");
+            }
+            for job in jobs {
+                print_job(&job);
+            }
+        }
+        JobsCommand::Retry { id: Some(id), .. } => {
+            let job = Job::retry(&id, &db_pool).await.map_err(|e| match e {
+                Error::NoRecordFound => eyre!("no job with id {id}"),
+                e => e.into(),
+            })?;
+            println!("queued job {} to run again", job.id);
+        }
+        JobsCommand::Retry { id: None, .. } => {
+            let count = Job::retry_failed(&db_pool).await?;
+            println!("queued {count} failed jobs to run again");
+        }
+        JobsCommand::Purge { statuses } => {
+            for status in statuses {
+                let count = Job::purge(status, &db_pool).await?;
+                println!("deleted {count} {status} jobs");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_job(job: &Job) {
+    let run_at = DateTime::from_timestamp(job.run_at, 0)
+        .map(|run_at| run_at.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default();
+
+    println!(
+        "{}  {:<9} {:<20} {}/{} attempts  run at {run_at}",
+        job.id, job.status, job.job_type, job.attempts, job.max_attempts
+    );
+    if let Some(error) = &job.last_error {
+        println!("    last error: {error}");
+    }
+}
@@ -0,0 +1,89 @@
+use clap::{Parser, Subcommand};
+use color_eyre::eyre::{Context as _, Result};
+use nohead_rs_config::{Config, Environment, get_env, parse_env};
+use nohead_rs_db::{DbPool, connect_pool};
+use nohead_rs_web::state::AppState;
+
+mod config;
+mod db;
+mod generate;
+mod jobs;
+mod migrate;
+mod routes;
+mod user;
+
+/// Tooling for working on a nohead-rs app.
+#[derive(Parser)]
+#[command(name = "nohead-rs", version, about)]
+struct Cli {
+    /// The environment whose configuration is used, defaults to `APP_ENVIRONMENT` or development
+    #[arg(long, global = true, value_parser = |env: &str| parse_env(env).map_err(|e| e.to_string()))]
+    env: Option<Environment>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate code for the app
+    #[command(subcommand)]
+    Generate(generate::GenerateCommand),
+    /// Apply, revert or inspect database migrations
+    #[command(subcommand)]
+    Migrate(migrate::MigrateCommand),
+    /// Manage the data in the database
+    #[command(subcommand)]
+    Db(db::DbCommand),
+    /// Manage users
+    #[command(subcommand)]
+    User(user::UserCommand),
+    /// Inspect and manage background jobs
+    #[command(subcommand)]
+    Jobs(jobs::JobsCommand),
+    /// Print the routes of the app router
+    Routes,
+    /// Check the configuration or generate a secret key
+    #[command(subcommand)]
+    Config(config::ConfigCommand),
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    color_eyre::install()?;
+
+    let cli = Cli::parse();
+    let env = cli.env;
+
+    match cli.command {
+        Command::Generate(command) => generate::run(command),
+        Command::Config(command) => config::run(command, env),
+        Command::Migrate(command) => migrate::run(command, &resolve_env(env)?).await,
+        Command::Db(command) => db::run(command, &resolve_env(env)?).await,
+        Command::User(command) => user::run(command, &resolve_env(env)?).await,
+        Command::Jobs(command) => jobs::run(command, &resolve_env(env)?).await,
+        Command::Routes => routes::run(resolve_env(env)?).await,
+    }
+}
+
+/// Uses the environment given with `--env`, falling back to `APP_ENVIRONMENT`.
+fn resolve_env(env: Option<Environment>) -> Result<Environment> {
+    match env {
+        Some(env) => Ok(env),
+        None => get_env().wrap_err("cannot get environment"),
+    }
+}
+
+/// Loads and validates the configuration of an environment.
+fn config(env: &Environment) -> Result<Config> {
+    AppState::load_config(env).wrap_err_with(|| format!("failed to load the {env} configuration"))
+}
+
+/// Connects to the database of an environment.
+async fn connect(env: &Environment) -> Result<DbPool> {
+    let config = config(env)?;
+
+    connect_pool(&config.database)
+        .await
+        .wrap_err_with(|| format!("failed to connect to {}", config.database.url))
+}
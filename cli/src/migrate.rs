@@ -0,0 +1,95 @@
+use clap::Subcommand;
+use color_eyre::eyre::{Context as _, Result};
+use nohead_rs_config::Environment;
+use nohead_rs_db::{connect_pool, migrate};
+
+#[derive(Subcommand)]
+pub enum MigrateCommand {
+    /// Create the database if it doesn't exist yet and apply every pending migration
+    Up,
+    /// Revert the most recently applied migrations
+    Down {
+        /// How many migrations to revert
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
+    /// List every migration and whether it has been applied
+    Status,
+}
+
+pub async fn run(command: MigrateCommand, env: &Environment) -> Result<()> {
+    match command {
+        MigrateCommand::Up => up(env).await,
+        MigrateCommand::Down { steps } => down(env, steps).await,
+        MigrateCommand::Status => status(env).await,
+    }
+}
+
+async fn up(env: &Environment) -> Result<()> {
+    let config = crate::config(env)?;
+
+    if migrate::create_database_if_missing(&config.database).await? {
+        println!("created database {}", config.database.url);
+    }
+
+    let db_pool = connect_pool(&config.database).await?;
+    let pending: Vec<_> = migrate::status(&db_pool)
+        .await?
+        .into_iter()
+        .filter(|migration| !migration.applied)
+        .collect();
+
+    migrate::run(&db_pool)
+        .await
+        .wrap_err("failed to apply migrations")?;
+
+    if pending.is_empty() {
+        println!("database is up to date");
+    }
+    for migration in pending {
+        println!("applied {} {}", migration.version, migration.description);
+    }
+
+    Ok(())
+}
+
+async fn down(env: &Environment, steps: usize) -> Result<()> {
+    let db_pool = crate::connect(env).await?;
+
+    let reverted = migrate::undo(&db_pool, steps)
+        .await
+        .wrap_err("failed to revert migrations")?;
+
+    if reverted.is_empty() {
+        println!("no migrations to revert");
+    }
+    for version in reverted {
+        println!("reverted {version}");
+    }
+
+    Ok(())
+}
+
+async fn status(env: &Environment) -> Result<()> {
+    let db_pool = crate::connect(env).await?;
+
+    for migration in migrate::status(&db_pool).await? {
+        let state = match (migration.applied, migration.checksum_mismatch) {
+            (true, true) => "changed",
+            (true, false) => "applied",
+            (false, _) => "pending",
+        };
+        let reversible = if migration.reversible {
+            ""
+        } else {
+            " (irreversible)"
+        };
+
+        println!(
+            "{:<8} {} {}{reversible}",
+            state, migration.version, migration.description
+        );
+    }
+
+    Ok(())
+}
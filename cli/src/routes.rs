@@ -0,0 +1,16 @@
+use color_eyre::eyre::{Context as _, Result};
+use nohead_rs_config::Environment;
+use nohead_rs_web::{app::App, state::AppState};
+
+pub async fn run(env: Environment) -> Result<()> {
+    let app_state = AppState::build(env)
+        .await
+        .wrap_err("failed to build app state")?;
+    let app = App::build(app_state).wrap_err("failed to build app")?;
+
+    for route in app.routes.routes() {
+        println!("{:<20} {}", route.methods.join(","), route.path);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use std::io::{self, BufRead as _, Write as _};
+
+use clap::{Args, Subcommand};
+use color_eyre::eyre::{Result, eyre};
+use nohead_rs_config::Environment;
+use nohead_rs_db::{
+    Error,
+    entities::{
+        role::Role,
+        user::{RegisterUser, User, UserStatus},
+    },
+    transaction,
+};
+
+#[derive(Subcommand)]
+pub enum UserCommand {
+    /// Create a user, e.g. the first admin of a new deployment
+    Create(CreateArgs),
+}
+
+#[derive(Args)]
+pub struct CreateArgs {
+    /// The email address the user logs in with
+    email: String,
+    /// The user's password, read from stdin when not given
+    #[arg(long)]
+    password: Option<String>,
+    /// Mark the email address as confirmed so that the user can log in straight away
+    #[arg(long)]
+    confirmed: bool,
+    /// A role to assign to the user on top of the default `user` role, may be repeated
+    #[arg(long = "role")]
+    roles: Vec<String>,
+}
+
+pub async fn run(command: UserCommand, env: &Environment) -> Result<()> {
+    match command {
+        UserCommand::Create(args) => create(env, args).await,
+    }
+}
+
+async fn create(env: &Environment, args: CreateArgs) -> Result<()> {
+    let password = match args.password {
+        Some(password) => password,
+        None => read_password()?,
+    };
+    let db_pool = crate::connect(env).await?;
+
+    let mut tx = transaction(&db_pool).await?;
+    let mut user = User::create(
+        RegisterUser {
+            email: args.email,
+            password: password.clone(),
+            confirm_password: password,
+        },
+        &mut *tx,
+    )
+    .await
+    .map_err(|e| match e {
+        Error::ValidationError(errors) => eyre!("invalid user: {errors}"),
+        Error::UniqueConstraint(_) => eyre!("a user with this email already exists"),
+        e => e.into(),
+    })?;
+
+    if args.confirmed {
+        user = User::update_status(user.id, UserStatus::Confirmed, &mut *tx).await?;
+    }
+
+    for role in &args.roles {
+        Role::assign(user.id, role, &mut *tx).await?;
+    }
+    let roles = Role::load_for_user(user.id, &mut *tx).await?;
+    if let Some(role) = args
+        .roles
+        .iter()
+        .find(|role| !roles.iter().any(|assigned| assigned.name == **role))
+    {
+        return Err(eyre!("unknown role: {role}"));
+    }
+
+    tx.commit().await?;
+
+    let roles: Vec<_> = roles.into_iter().map(|role| role.name).collect();
+    println!(
+        "created user {} <{}> ({:?}) with roles: {}",
+        user.id,
+        user.email,
+        user.status,
+        roles.join(", ")
+    );
+
+    Ok(())
+}
+
+fn read_password() -> Result<String> {
+    print!("password: ");
+    io::stdout().flush()?;
+
+    let mut password = String::new();
+    io::stdin().lock().read_line(&mut password)?;
+
+    Ok(password.trim_end_matches(['\r', '\n']).to_string())
+}
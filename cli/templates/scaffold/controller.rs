@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use axum::{
+    Form, Router,
+    extract::{Path, Query, State},
+    http::Method,
+    middleware::from_fn_with_state,
+    response::Redirect,
+    routing::{delete, get, post, put},
+};
+use nohead_rs_db::{
+    entities::{
+        OwnedEntity as _,
+        __snake__::{__Pascal__, __Pascal__Changeset},
+    },
+    pagination::{ListParams, Page},
+};
+
+use crate::{
+    error::Error,
+    forms::{ValidForm, form_page},
+    initializers::view_engine::engine::{View, ViewEngine},
+    middlewares::{
+        auth::CurrentUser,
+        flash::{Flash, IncomingFlashes},
+    },
+    negotiate::{Negotiated, ResponseFormat},
+    router::Routes,
+    state::AppState,
+    views::__plural__::__Pascal__View,
+};
+
+use super::{Action, Controller};
+
+pub struct __Pascal__Controller;
+
+#[async_trait]
+impl Controller for __Pascal__Controller {
+    type Id = i64;
+
+    type Entity = __Pascal__;
+
+    type View = __Pascal__View;
+
+    type EntityChangeset = __Pascal__Changeset;
+
+    type Error = Error;
+
+    const ROUTES: Routes = &[
+        ("/__plural__", &[Method::GET, Method::POST]),
+        ("/__plural__/batch", &[Method::POST]),
+        ("/__plural__/{id}", &[Method::GET, Method::PUT, Method::DELETE]),
+    ];
+
+    fn router() -> Router<AppState> {
+        Router::new()
+            .route(
+                "/__plural__",
+                Self::authorize(Action::ReadAll, get(Self::read_all))
+                    .merge(Self::authorize(
+                        Action::Create,
+                        post(Self::create).route_layer(from_fn_with_state(
+                            __Pascal__View::CREATE_FORM,
+                            form_page,
+                        )),
+                    )),
+            )
+            .route(
+                "/__plural__/batch",
+                Self::authorize(Action::CreateBatch, post(Self::create_batch)),
+            )
+            .route(
+                "/__plural__/{id}",
+                Self::authorize(Action::ReadOne, get(Self::read_one))
+                    .merge(Self::authorize(
+                        Action::Update,
+                        put(Self::update).route_layer(from_fn_with_state(
+                            __Pascal__View::UPDATE_FORM,
+                            form_page,
+                        )),
+                    ))
+                    .merge(Self::authorize(Action::Delete, delete(Self::delete))),
+            )
+    }
+
+    fn permission(action: Action) -> Option<&'static str> {
+        match action {
+            Action::ReadAll => Some("__plural__.read_all"),
+            Action::Create | Action::CreateBatch => Some("__plural__.create"),
+            Action::ReadOne => Some("__plural__.read_one"),
+            Action::Update => Some("__plural__.update"),
+            Action::Delete => Some("__plural__.delete"),
+        }
+    }
+
+    async fn read_all(
+        v: ViewEngine<View>,
+        format: ResponseFormat,
+        flashes: IncomingFlashes,
+        CurrentUser(user): CurrentUser,
+        Query(params): Query<ListParams>,
+        State(app_state): State<AppState>,
+    ) -> Result<(IncomingFlashes, Negotiated<Self::View, Page<Self::Entity>>), Self::Error> {
+        let __plural__ = __Pascal__::load_page(&params, user.id, &app_state.db_read_pool).await?;
+
+        Ok((
+            flashes.clone(),
+            Negotiated::new(format, __Pascal__View::Index(v, __plural__.clone(), flashes), __plural__),
+        ))
+    }
+
+    async fn create(
+        flash: Flash,
+        format: ResponseFormat,
+        CurrentUser(user): CurrentUser,
+        State(app_state): State<AppState>,
+        ValidForm(record): ValidForm<Self::EntityChangeset>,
+    ) -> Result<(Flash, Negotiated<Redirect, Self::Entity>), Self::Error> {
+        let __snake__ = __Pascal__::create(record, user.id, &app_state.db_pool).await?;
+        let location = format!("/__plural__/{}", __snake__.id);
+
+        Ok((
+            flash.success("✅ created new __title__"),
+            Negotiated::new(format, Redirect::to(&location), __snake__).created(&location),
+        ))
+    }
+
+    async fn create_batch(
+        flash: Flash,
+        format: ResponseFormat,
+        CurrentUser(user): CurrentUser,
+        State(app_state): State<AppState>,
+        Form(records): Form<Vec<Self::EntityChangeset>>,
+    ) -> Result<(Flash, Negotiated<Redirect, Vec<Self::Entity>>), Self::Error> {
+        let __plural__ = __Pascal__::create_batch(records, user.id, &app_state.db_pool).await?;
+
+        Ok((
+            flash.success("✅ created __plural_title__"),
+            Negotiated::new(format, Redirect::to("/__plural__"), __plural__).created("/__plural__"),
+        ))
+    }
+
+    async fn read_one(
+        v: ViewEngine<View>,
+        format: ResponseFormat,
+        flashes: IncomingFlashes,
+        CurrentUser(user): CurrentUser,
+        Path(id): Path<Self::Id>,
+        State(app_state): State<AppState>,
+    ) -> Result<(IncomingFlashes, Negotiated<Self::View, Self::Entity>), Self::Error> {
+        let __snake__ = __Pascal__::load(id, user.id, &app_state.db_read_pool).await?;
+
+        Ok((
+            flashes.clone(),
+            Negotiated::new(format, __Pascal__View::Show(v, __snake__.clone(), flashes), __snake__),
+        ))
+    }
+
+    async fn update(
+        flash: Flash,
+        format: ResponseFormat,
+        CurrentUser(user): CurrentUser,
+        Path(id): Path<Self::Id>,
+        State(app_state): State<AppState>,
+        ValidForm(form): ValidForm<Self::EntityChangeset>,
+    ) -> Result<(Flash, Negotiated<Redirect, Self::Entity>), Self::Error> {
+        let __snake__ = __Pascal__::update(id, form, user.id, &app_state.db_pool).await?;
+
+        Ok((
+            flash.success("✅ updated __title__"),
+            Negotiated::new(format, Redirect::to(&format!("/__plural__/{}", __snake__.id)), __snake__),
+        ))
+    }
+
+    async fn delete(
+        flash: Flash,
+        format: ResponseFormat,
+        CurrentUser(user): CurrentUser,
+        Path(id): Path<Self::Id>,
+        State(app_state): State<AppState>,
+    ) -> Result<(Flash, Negotiated<Redirect, ()>), Self::Error> {
+        let ___snake__ = __Pascal__::delete(id, user.id, &app_state.db_pool).await?;
+
+        Ok((
+            flash.info("deleted __title__"),
+            Negotiated::new(format, Redirect::to("/__plural__"), ()).no_content(),
+        ))
+    }
+}
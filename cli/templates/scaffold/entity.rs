@@ -0,0 +1,37 @@
+#[cfg(feature = "test-helpers")]
+use fake::Dummy;
+
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use validator::Validate;
+
+use super::Entity;
+use crate::schema::Schema;
+
+/// A __title__.
+#[derive(Serialize, Clone, Debug, Deserialize, FromRow, Entity, Schema)]
+#[entity(
+    table = "__plural__",
+    changeset = __Pascal__Changeset,
+    owner = user_id,
+__entity_args__)]
+pub struct __Pascal__ {
+    /// The id of the record.
+    pub id: i64,
+__record_fields__    /// The id of the user that owns the __title__.
+    pub user_id: i64,
+}
+
+/// A changeset representing the data that is intended to be used to either create a new __title__ or update an existing __title__.
+///
+/// Changesets are validatated in the [`create`] and [`update`] functions which return an [Result::Err] if validation fails.
+///
+/// Changesets can also be used to generate fake data for tests when the `test-helpers` feature is enabled:
+///
+/// ```
+/// let __snake___changeset: __Pascal__Changeset = Faker.fake();
+/// ```
+#[derive(Deserialize, Validate, Clone, Schema)]
+#[cfg_attr(feature = "test-helpers", derive(Serialize, Dummy))]
+pub struct __Pascal__Changeset {
+__changeset_fields__}
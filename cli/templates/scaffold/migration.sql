@@ -0,0 +1,20 @@
+-- Create __plural__ table
+CREATE TABLE __plural__ (
+id INTEGER PRIMARY KEY NOT NULL,
+__columns__user_id INTEGER NOT NULL,
+FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+) ;
+
+CREATE INDEX __plural___user_id_idx ON __plural__ (user_id) ;
+
+-- Every role can manage __plural__
+INSERT INTO permissions (name) VALUES
+('__plural__.read_all'),
+('__plural__.read_one'),
+('__plural__.create'),
+('__plural__.update'),
+('__plural__.delete') ;
+
+INSERT INTO roles_permissions (role_id, permission_id)
+SELECT roles.id, permissions.id FROM roles, permissions
+WHERE permissions.name LIKE '__plural__.%' ;
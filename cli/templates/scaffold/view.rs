@@ -0,0 +1,54 @@
+use axum::response::{IntoResponse, Response};
+use nohead_rs_db::{entities::__snake__::__Pascal__, pagination::Page};
+use serde_json::json;
+
+use crate::{
+    format,
+    forms::FormPage,
+    initializers::view_engine::engine::{View, ViewEngine},
+    middlewares::flash::IncomingFlashes,
+};
+
+pub enum __Pascal__View {
+    Index(ViewEngine<View>, Page<__Pascal__>, IncomingFlashes),
+    Show(ViewEngine<View>, __Pascal__, IncomingFlashes),
+}
+
+impl __Pascal__View {
+    pub const CREATE_FORM: FormPage = FormPage {
+        page: "__plural__/new.html",
+        form: "__plural__/form.html",
+        form_id: "__snake__-form",
+    };
+
+    pub const UPDATE_FORM: FormPage = FormPage {
+        page: "__plural__/show.html",
+        form: "__plural__/update.html",
+        form_id: "update-__snake__-form",
+    };
+}
+
+impl IntoResponse for __Pascal__View {
+    fn into_response(self) -> Response {
+        match self {
+            __Pascal__View::Index(ViewEngine(v), __plural__, IncomingFlashes { flashes, .. }) => {
+                format::render()
+                    .view(
+                        &v,
+                        "__plural__/index.html",
+                        json!({ "__plural__": __plural__, "flashes": flashes }),
+                    )
+                    .into_response()
+            }
+            __Pascal__View::Show(ViewEngine(v), __snake__, IncomingFlashes { flashes, .. }) => {
+                format::render()
+                    .view(
+                        &v,
+                        "__plural__/show.html",
+                        json!({ "__snake__": __snake__, "flashes": flashes }),
+                    )
+                    .into_response()
+            }
+        }
+    }
+}
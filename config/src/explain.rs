@@ -0,0 +1,141 @@
+//! Turns configuration errors into messages that point at the file and line to fix.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::eyre::{Error, eyre};
+use figment::error::Kind;
+
+/// Explains every error in `error`, looking up the keys involved in the config `files`.
+pub fn explain(error: figment::Error, files: &[String]) -> Error {
+    let messages: Vec<String> = error
+        .into_iter()
+        .map(|error| match &error.kind {
+            Kind::UnknownField(key, expected) => {
+                // The path of an unknown key ends with the key itself.
+                let section = error
+                    .path
+                    .strip_suffix(std::slice::from_ref(key))
+                    .unwrap_or(&error.path);
+                unknown_key(section, key, expected, files)
+            }
+            Kind::MissingField(key) => missing_key(&error.path, key, files),
+            _ => error.to_string(),
+        })
+        .collect();
+
+    eyre!(messages.join("\n"))
+}
+
+fn unknown_key(section: &[String], key: &str, expected: &[&str], files: &[String]) -> String {
+    let expected = expected
+        .iter()
+        .map(|key| format!("`{key}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let problem = format!(
+        "unknown key `{key}` in {}, expected one of {expected}",
+        section_name(section)
+    );
+
+    let locations: Vec<String> = files
+        .iter()
+        .filter_map(|file| {
+            let line = find_line(&find_file(file)?, section, Some(key))?;
+            Some(format!("{file}:{line}: {problem}"))
+        })
+        .collect();
+
+    if locations.is_empty() {
+        // Not in any file, so it must come from the environment.
+        format!("{problem} (set by the {} env var)", env_var(section, key))
+    } else {
+        locations.join("\n")
+    }
+}
+
+fn missing_key(section: &[String], key: &str, files: &[String]) -> String {
+    if section.is_empty() {
+        return format!(
+            "missing section [{key}], add it to one of {}",
+            files.join(", ")
+        );
+    }
+
+    let problem = format!("missing key `{key}` in {}", section_name(section));
+    let env_var = env_var(section, key);
+
+    // Point at the most specific file that already has the section.
+    let location = files.iter().rev().find_map(|file| {
+        let line = find_line(&find_file(file)?, section, None)?;
+        Some(format!("{file}:{line}"))
+    });
+
+    match (location, files.last()) {
+        (Some(location), _) => {
+            format!("{location}: {problem}, add it to this section or set {env_var}")
+        }
+        (None, Some(file)) => format!(
+            "{file}: {problem}, add it to a {} section or set {env_var}",
+            section_name(section)
+        ),
+        (None, None) => format!("{problem}, set {env_var}"),
+    }
+}
+
+fn section_name(section: &[String]) -> String {
+    if section.is_empty() {
+        "the top level".to_string()
+    } else {
+        format!("[{}]", section.join("."))
+    }
+}
+
+fn env_var(section: &[String], key: &str) -> String {
+    let path: Vec<&str> = section.iter().map(String::as_str).chain([key]).collect();
+
+    format!("APP_{}", path.join("__").to_uppercase())
+}
+
+/// Finds a config file the same way `Toml::file` does, by looking in the current directory
+/// and then its parents.
+fn find_file(file: &str) -> Option<PathBuf> {
+    let cwd = env::current_dir().ok()?;
+
+    cwd.ancestors()
+        .map(|dir| dir.join(file))
+        .find(|path| path.is_file())
+}
+
+/// Finds the line of `key` in `section` of a TOML file, or of the section's header when no key
+/// is given.
+fn find_line(path: &Path, section: &[String], key: Option<&str>) -> Option<usize> {
+    let contents = fs::read_to_string(path).ok()?;
+    let section = section.join(".");
+    let mut current = String::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if let Some(header) = line.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+            current = header.trim().to_string();
+            if key.is_none() && current == section {
+                return Some(index + 1);
+            }
+            continue;
+        }
+
+        if current == section
+            && key.is_some_and(|key| {
+                line.split_once('=')
+                    .is_some_and(|(name, _)| name.trim() == key)
+            })
+        {
+            return Some(index + 1);
+        }
+    }
+
+    None
+}
@@ -1,7 +1,9 @@
 use std::{
+    collections::BTreeMap,
     env,
     fmt::{Display, Formatter},
     net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::Path,
 };
 
 use color_eyre::eyre::{Context as _, Error, eyre};
@@ -9,16 +11,20 @@ use dotenvy::dotenv;
 use figment::{
     Figment,
     providers::{Env, Format as _, Serialized, Toml},
+    value::Value,
 };
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
+mod explain;
+
 /// The application configuration.
 ///
 /// This struct is the central point for the entire application configuration. It holds the [`ServerConfig`] [`DatabaseConfig`] [`TracingConfig`] as well as [`StaticAssetsConfig`] and can be extended with any application-specific configuration settings that will be read from the main `app.toml` and the environment-specific configuration files.
 ///
 /// For any setting that appears in both the `app.toml` and the environment-specific file, the latter will override the former so that default settings can be kept in `app.toml` that are overridden per environment if necessary.
 #[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     pub app: AppConfig,
     pub server: ServerConfig,
@@ -28,9 +34,107 @@ pub struct Config {
     pub templates: TemplatesConfig,
     pub components: ComponentsConfig,
     pub mailer: MailerConfig,
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub security: SecurityConfig,
+}
+
+impl Config {
+    /// Checks the values that deserializing alone can't, e.g. that the templates exist.
+    ///
+    /// Relative paths are resolved against `root`, the directory of the web crate.
+    pub fn validate(&self, root: &Path) -> Result<(), Error> {
+        let mut problems = vec![];
+
+        if !is_sqlite_url(&self.database.url) {
+            problems.push(format!(
+                "[database] url: `{}` is not a sqlite URL, e.g. \"sqlite://db/app.db\"",
+                self.database.url
+            ));
+        }
+
+        if !is_email(&self.mailer.sender) {
+            problems.push(format!(
+                "[mailer] sender: `{}` is not an email address",
+                self.mailer.sender
+            ));
+        }
+
+        if let Some(intercept_to) = self
+            .mailer
+            .intercept_to
+            .as_ref()
+            .filter(|intercept_to| !is_email(intercept_to))
+        {
+            problems.push(format!(
+                "[mailer] intercept_to: `{intercept_to}` is not an email address"
+            ));
+        }
+
+        match &self.mailer.transport {
+            TransportConfig::Smtp { host, .. } if host.is_empty() => {
+                problems.push("[mailer.transport] host: must not be empty".to_string());
+            }
+            TransportConfig::Smtp {
+                username: Some(_),
+                password: None,
+                ..
+            } => {
+                problems.push(
+                    "[mailer.transport] password: must be set along with the username".to_string(),
+                );
+            }
+            TransportConfig::File { path } if path.is_empty() => {
+                problems.push("[mailer.transport] path: must not be empty".to_string());
+            }
+            _ => {}
+        }
+
+        for (section, path) in [
+            ("templates", &self.templates.path),
+            ("static_assets", &self.static_assets.path),
+        ] {
+            let dir = root.join(path);
+            if !dir.is_dir() {
+                problems.push(format!(
+                    "[{section}] path: `{}` does not exist",
+                    dir.display()
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("Invalid configuration:\n{}", problems.join("\n")))
+        }
+    }
+}
+
+fn is_sqlite_url(url: &str) -> bool {
+    let Some(path) = url.strip_prefix("sqlite:") else {
+        return false;
+    };
+    let path = path.strip_prefix("//").unwrap_or(path);
+    let path = path.split('?').next().unwrap_or_default();
+
+    !path.is_empty()
+}
+
+fn is_email(email: &str) -> bool {
+    let Some((name, domain)) = email.split_once('@') else {
+        return false;
+    };
+
+    !name.is_empty()
+        && !domain.contains('@')
+        && domain.split('.').count() > 1
+        && domain.split('.').all(|part| !part.is_empty())
+        && !email.contains(char::is_whitespace)
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct AppConfig {
     /// The name of the app which can be presented in the UI
     pub name: String,
@@ -38,6 +142,7 @@ pub struct AppConfig {
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 #[cfg_attr(test, derive(PartialEq))]
+#[serde(deny_unknown_fields)]
 pub struct ServerConfig {
     /// The port to bind to, e.g. 3000
     pub port: u16,
@@ -76,13 +181,126 @@ impl ServerConfig {
 
 #[derive(Deserialize, Clone, Debug)]
 #[cfg_attr(test, derive(PartialEq))]
+#[serde(deny_unknown_fields)]
 pub struct DatabaseConfig {
     /// The URL to use to connect to the database, e.g. "sqlite://database.db"
     pub url: String,
+
+    /// Whether to apply pending migrations when the app starts, skipped on LiteFS replicas
+    #[serde(default)]
+    pub auto_migrate: bool,
+
+    /// The journal mode of the database, WAL lets reads carry on while a write is in progress
+    #[serde(default = "DatabaseConfig::default_journal_mode")]
+    pub journal_mode: JournalMode,
+
+    /// How careful SQLite is to flush writes to disk, `normal` is safe in WAL mode
+    #[serde(default = "DatabaseConfig::default_synchronous")]
+    pub synchronous: Synchronous,
+
+    /// How long to wait for another connection's lock before failing with `SQLITE_BUSY`, in milliseconds
+    #[serde(default = "DatabaseConfig::default_busy_timeout")]
+    pub busy_timeout: u64,
+
+    /// Whether to enforce foreign key constraints
+    #[serde(default = "DatabaseConfig::default_foreign_keys")]
+    pub foreign_keys: bool,
+
+    /// The most connections the pool opens
+    #[serde(default = "DatabaseConfig::default_max_connections")]
+    pub max_connections: u32,
+
+    /// The connections the pool keeps open even when idle
+    #[serde(default)]
+    pub min_connections: u32,
+
+    /// Any other pragmas to set on each connection, e.g. `cache_size = "-20000"`
+    #[serde(default)]
+    pub pragmas: BTreeMap<String, String>,
+
+    /// A separate pool of read only connections. When set, the main pool is limited to a
+    /// single connection so that writes queue up in the app rather than fight over the lock.
+    #[serde(default)]
+    pub read_pool: Option<ReadPoolConfig>,
+}
+
+impl DatabaseConfig {
+    fn default_journal_mode() -> JournalMode {
+        JournalMode::Wal
+    }
+
+    fn default_synchronous() -> Synchronous {
+        Synchronous::Normal
+    }
+
+    fn default_busy_timeout() -> u64 {
+        5000
+    }
+
+    fn default_foreign_keys() -> bool {
+        true
+    }
+
+    fn default_max_connections() -> u32 {
+        10
+    }
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: "sqlite://db/nohead_rs.db".to_string(),
+            auto_migrate: false,
+            journal_mode: Self::default_journal_mode(),
+            synchronous: Self::default_synchronous(),
+            busy_timeout: Self::default_busy_timeout(),
+            foreign_keys: Self::default_foreign_keys(),
+            max_connections: Self::default_max_connections(),
+            min_connections: 0,
+            pragmas: BTreeMap::new(),
+            read_pool: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+#[serde(deny_unknown_fields)]
+pub struct ReadPoolConfig {
+    /// The most read only connections the pool opens
+    #[serde(default = "DatabaseConfig::default_max_connections")]
+    pub max_connections: u32,
+
+    /// The read only connections the pool keeps open even when idle
+    #[serde(default)]
+    pub min_connections: u32,
+}
+
+/// The SQLite journal mode, see <https://www.sqlite.org/pragma.html#pragma_journal_mode>.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalMode {
+    Delete,
+    Truncate,
+    Persist,
+    Memory,
+    Wal,
+    Off,
+}
+
+/// The SQLite synchronous setting, see <https://www.sqlite.org/pragma.html#pragma_synchronous>.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Synchronous {
+    Off,
+    Normal,
+    Full,
+    Extra,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[cfg_attr(test, derive(PartialEq))]
+#[serde(deny_unknown_fields)]
 pub struct StaticAssetsConfig {
     pub precompressed: bool,
     pub path: String,
@@ -90,12 +308,14 @@ pub struct StaticAssetsConfig {
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[cfg_attr(test, derive(PartialEq))]
+#[serde(deny_unknown_fields)]
 pub struct TemplatesConfig {
     pub path: String,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[cfg_attr(test, derive(PartialEq))]
+#[serde(deny_unknown_fields)]
 pub struct ComponentsConfig {
     pub path: String,
     pub wasm: String,
@@ -103,16 +323,183 @@ pub struct ComponentsConfig {
 
 #[derive(Debug, Deserialize, Clone)]
 #[cfg_attr(test, derive(PartialEq))]
+#[serde(deny_unknown_fields)]
 pub struct TracingConfig {
     pub enable: bool,
     pub env_filter: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct MailerConfig {
-    pub base_url: String,
+    /// The address emails are sent from
     pub sender: String,
+    /// How long to wait for the transport to accept an email, in milliseconds
     pub timeout: u64,
+    /// How emails are delivered
+    pub transport: TransportConfig,
+    /// Sends emails to this address instead of their recipients, e.g. in staging so that
+    /// real users don't get emails from it
+    #[serde(default)]
+    pub intercept_to: Option<String>,
+    /// The domains whose recipients still get their emails when `intercept_to` is set, e.g.
+    /// ["example.com"] for the team to test with
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    /// Put in front of the subject of every email, e.g. "[staging]"
+    #[serde(default)]
+    pub subject_prefix: Option<String>,
+}
+
+/// How emails are delivered, selected with `kind` in the `[mailer.transport]` section.
+#[derive(Clone, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum TransportConfig {
+    /// Sends emails with the HTTP API of Resend.
+    Resend {
+        /// The URL of the API, e.g. "https://api.resend.com"
+        base_url: String,
+        /// The API key, read from the `RESEND_API_KEY` env var when it isn't set here
+        #[serde(default)]
+        api_key: Option<String>,
+    },
+    /// Sends emails to an SMTP server.
+    Smtp {
+        host: String,
+        /// The port to connect to, e.g. 587 for STARTTLS or 465 for TLS
+        port: u16,
+        #[serde(default)]
+        tls: SmtpTls,
+        /// The user to log in as, emails are sent without logging in if this isn't set
+        #[serde(default)]
+        username: Option<String>,
+        /// The password of the user, e.g. set with APP_MAILER__TRANSPORT__PASSWORD
+        #[serde(default)]
+        password: Option<String>,
+    },
+    /// Writes each email to a `.eml` file in a directory, which mail clients can open.
+    File {
+        /// The directory to write to, relative to the working directory
+        path: String,
+    },
+    /// Logs each email rather than sending it.
+    Log,
+    /// Keeps the emails in memory, e.g. for tests to check what was sent.
+    Memory,
+}
+
+/// Keeps the API key and password out of logs.
+impl std::fmt::Debug for TransportConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let redacted = |secret: &Option<String>| secret.as_ref().map(|_| "[redacted]");
+
+        match self {
+            TransportConfig::Resend { base_url, api_key } => f
+                .debug_struct("Resend")
+                .field("base_url", base_url)
+                .field("api_key", &redacted(api_key))
+                .finish(),
+            TransportConfig::Smtp {
+                host,
+                port,
+                tls,
+                username,
+                password,
+            } => f
+                .debug_struct("Smtp")
+                .field("host", host)
+                .field("port", port)
+                .field("tls", tls)
+                .field("username", username)
+                .field("password", &redacted(password))
+                .finish(),
+            TransportConfig::File { path } => f.debug_struct("File").field("path", path).finish(),
+            TransportConfig::Log => f.write_str("Log"),
+            TransportConfig::Memory => f.write_str("Memory"),
+        }
+    }
+}
+
+/// How the connection to an SMTP server is secured.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpTls {
+    /// Connect in plain text and upgrade the connection with `STARTTLS`.
+    #[default]
+    Starttls,
+    /// Connect with TLS right away.
+    Tls,
+    /// Don't encrypt the connection, e.g. for a local server such as Mailpit.
+    None,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+#[serde(deny_unknown_fields)]
+pub struct AuthConfig {
+    /// How to treat users that have not confirmed their email address yet when they log in
+    pub pending_users: PendingUserPolicy,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+#[serde(deny_unknown_fields)]
+pub struct SecurityConfig {
+    /// The key that signs the session and flash cookies, as base64 of at least 64 bytes, e.g. from
+    /// `nohead-rs config generate-key`. Required in production, generated on every boot otherwise
+    #[serde(default)]
+    pub secret_key: Option<String>,
+
+    /// Keys that signed cookies before `secret_key`, still accepted so that rotating the key
+    /// doesn't log everybody out. Drop them once the cookies they signed have expired, a day
+    /// for sessions
+    #[serde(default)]
+    pub old_secret_keys: Vec<String>,
+
+    /// Whether form posts, puts and deletes must send the CSRF token of their session
+    #[serde(default = "SecurityConfig::default_csrf")]
+    pub csrf: bool,
+}
+
+impl SecurityConfig {
+    fn default_csrf() -> bool {
+        true
+    }
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            secret_key: None,
+            old_secret_keys: vec![],
+            csrf: Self::default_csrf(),
+        }
+    }
+}
+
+/// Keeps the keys out of logs.
+impl std::fmt::Debug for SecurityConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecurityConfig")
+            .field(
+                "secret_key",
+                &self.secret_key.as_ref().map(|_| "[redacted]"),
+            )
+            .field("old_secret_keys", &self.old_secret_keys.len())
+            .field("csrf", &self.csrf)
+            .finish()
+    }
+}
+
+/// The login policy for users whose status is still pending.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PendingUserPolicy {
+    /// Refuse to log in pending users until they have confirmed their email address.
+    Reject,
+    /// Log in pending users but keep them out of routes that require a confirmed account.
+    Limited,
 }
 
 /// Loads the application configuration for a particular environment.
@@ -135,6 +522,30 @@ pub fn load_config<'a, T>(env: &Environment) -> Result<T, Error>
 where
     T: Deserialize<'a>,
 {
+    load_dotenv(env);
+
+    let config: T = figment(env)
+        .extract()
+        .map_err(|error| explain::explain(error, &config_files(env)))
+        .wrap_err("Could not read configuration!")?;
+
+    Ok(config)
+}
+
+/// Loads the merged configuration of an environment as TOML with secrets such as keys and
+/// passwords redacted, so that it can be printed, e.g. by `nohead-rs config check`.
+pub fn redacted_config(env: &Environment) -> Result<String, Error> {
+    load_dotenv(env);
+
+    let mut config: Value = figment(env)
+        .extract()
+        .wrap_err("Could not read configuration!")?;
+    redact(&mut config);
+
+    toml::to_string(&config).wrap_err("Could not print configuration!")
+}
+
+fn load_dotenv(env: &Environment) {
     let dotenv_config_dir = env::var("APP_DOTENV_CONFIG_DIR")
         .ok()
         .map(std::path::PathBuf::from);
@@ -156,35 +567,50 @@ where
         }
         _ => { /* don't use any .env file for production */ }
     }
+}
 
-    let env_config_file = match env {
-        Environment::Development => "development.toml",
-        Environment::Staging => "staging.toml",
-        Environment::Production => "production.toml",
-        Environment::Test => "test.toml",
-    };
+/// The config files of an environment, in the order they are merged.
+fn config_files(env: &Environment) -> [String; 2] {
+    [
+        "config/app.toml".to_string(),
+        format!("config/environments/{}.toml", env),
+    ]
+}
+
+fn figment(env: &Environment) -> Figment {
+    let [app_file, env_file] = config_files(env);
 
-    let config: T = Figment::new()
+    Figment::new()
+        .merge(Serialized::defaults(ServerConfig::default()).key("server"))
+        .merge(Toml::file(app_file))
+        .merge(Toml::file(env_file))
         .merge(
-            Serialized::defaults(ServerConfig::default())
-                .key("server")
-                .key("database")
-                .key("tracing")
-                .key("static_assets")
-                .key("templates")
-                .key("components")
-                .key("mailer"),
+            Env::prefixed("APP_")
+                // These select the environment and .env files rather than configure the app.
+                .ignore(&["ENVIRONMENT", "DOTENV_CONFIG_DIR"])
+                .split("__"),
         )
-        .merge(Toml::file("config/app.toml"))
-        .merge(Toml::file(format!(
-            "config/environments/{}",
-            env_config_file
-        )))
-        .merge(Env::prefixed("APP_").split("__"))
-        .extract()
-        .wrap_err("Could not read configuration!")?;
+}
 
-    Ok(config)
+/// Replaces the values of keys that look like they hold secrets, e.g. `api_key`, `password`
+/// or `old_secret_keys`.
+fn redact(value: &mut Value) {
+    if let Value::Dict(_, dict) = value {
+        for (key, value) in dict.iter_mut() {
+            let key = key.to_lowercase();
+            let mut words = key.split('_');
+            let secret = words
+                .clone()
+                .any(|word| matches!(word, "secret" | "secrets" | "password" | "token"))
+                || words.next_back() == Some("key");
+
+            if secret {
+                *value = Value::from("[redacted]");
+            } else {
+                redact(value);
+            }
+        }
+    }
 }
 
 /// The environment the application runs in.
@@ -245,3 +671,149 @@ pub fn parse_env(env: &str) -> Result<Environment, Error> {
         unknown => Err(eyre!(r#"Unknown environment: "{}"!"#, unknown)),
     }
 }
+
+#[cfg(test)]
+// `Jail` closures return a `figment::Error`, which is large.
+#[allow(clippy::result_large_err)]
+mod tests {
+    use figment::Jail;
+
+    use super::*;
+
+    const STAGING: &str = r#"
+[app]
+name = "nohead-rs"
+
+[server]
+port = 8080
+ip = "0.0.0.0"
+host = "http://localhost"
+
+[database]
+url = "sqlite://app.db"
+
+[tracing]
+enable = true
+env_filter = "info"
+
+[static_assets]
+precompressed = false
+path = "static"
+
+[templates]
+path = "templates"
+
+[components]
+path = "components"
+wasm = "enhance-ssr.wasm"
+
+[mailer]
+sender = "dev@notebar.io"
+timeout = 2000
+
+[mailer.transport]
+kind = "resend"
+base_url = "https://api.resend.com"
+
+[auth]
+pending_users = "reject"
+"#;
+
+    fn load(jail: &mut Jail, staging: &str) -> Result<Config, Error> {
+        jail.create_dir("config/environments")?;
+        jail.create_file("config/environments/staging.toml", staging)?;
+
+        load_config(&Environment::Staging)
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected_with_their_line() {
+        Jail::expect_with(|jail| {
+            let staging = STAGING.replace("url = ", "uri = ");
+
+            let error = load(jail, &staging).unwrap_err();
+
+            assert!(
+                format!("{error:?}").contains(
+                    "config/environments/staging.toml:11: unknown key `uri` in [database]"
+                ),
+                "{error:?}"
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn missing_keys_point_at_their_section() {
+        Jail::expect_with(|jail| {
+            let staging = STAGING.replace("sender = \"dev@notebar.io\"\n", "");
+
+            let error = load(jail, &staging).unwrap_err();
+
+            assert!(
+                format!("{error:?}").contains(
+                    "config/environments/staging.toml:28: missing key `sender` in [mailer]"
+                ),
+                "{error:?}"
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn env_vars_override_files() {
+        Jail::expect_with(|jail| {
+            jail.set_env("APP_ENVIRONMENT", "staging");
+            jail.set_env("APP_DATABASE__URL", "sqlite://override.db");
+
+            let config = load(jail, STAGING).unwrap();
+
+            assert_eq!(config.database.url, "sqlite://override.db");
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn invalid_values_are_reported_together() {
+        Jail::expect_with(|jail| {
+            let staging = STAGING
+                .replace("sqlite://app.db", "postgres://app")
+                .replace("dev@notebar.io", "dev")
+                .replace(
+                    "timeout = 2000",
+                    "timeout = 2000\nintercept_to = \"nobody\"",
+                );
+            let config = load(jail, &staging).unwrap();
+
+            let error = config.validate(jail.directory()).unwrap_err().to_string();
+
+            assert!(error.contains("[database] url"), "{error}");
+            assert!(error.contains("[mailer] sender"), "{error}");
+            assert!(error.contains("[mailer] intercept_to"), "{error}");
+            assert!(error.contains("[templates] path"), "{error}");
+
+            jail.create_dir("templates")?;
+            jail.create_dir("static")?;
+            let config = load(jail, STAGING).unwrap();
+            assert!(config.validate(jail.directory()).is_ok());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn redacted_config_hides_secrets() {
+        Jail::expect_with(|jail| {
+            jail.create_dir("config/environments")?;
+            jail.create_file(
+                "config/environments/staging.toml",
+                &format!("{STAGING}\n[security]\nsecret_key = \"hunter2\"\n"),
+            )?;
+
+            let config = redacted_config(&Environment::Staging).unwrap();
+
+            assert!(config.contains("secret_key = \"[redacted]\""), "{config}");
+            assert!(!config.contains("hunter2"));
+            Ok(())
+        });
+    }
+}
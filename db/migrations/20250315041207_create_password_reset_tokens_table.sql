@@ -0,0 +1,7 @@
+-- Create password reset token table
+CREATE TABLE password_reset_tokens (
+reset_token TEXT PRIMARY KEY NOT NULL,
+user_id INTEGER NOT NULL,
+expires_at TIMESTAMP DEFAULT (DATETIME (CURRENT_TIMESTAMP, '+1 hours')),
+FOREIGN KEY (user_id) REFERENCES users (id)
+) ;
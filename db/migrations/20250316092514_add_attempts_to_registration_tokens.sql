@@ -0,0 +1,2 @@
+-- Track failed guesses so a register token can't be brute-forced
+ALTER TABLE registration_tokens ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0 ;
@@ -0,0 +1,2 @@
+-- Statuses used to be written with their variant name, e.g. 'Confirmed'
+UPDATE users SET status = LOWER(status) ;
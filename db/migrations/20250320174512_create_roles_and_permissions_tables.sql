@@ -0,0 +1,57 @@
+-- Create roles table
+CREATE TABLE roles (
+id INTEGER PRIMARY KEY NOT NULL,
+name TEXT UNIQUE NOT NULL
+) ;
+
+-- Create permissions table
+CREATE TABLE permissions (
+id INTEGER PRIMARY KEY NOT NULL,
+name TEXT UNIQUE NOT NULL
+) ;
+
+-- Create users_roles join table
+CREATE TABLE users_roles (
+user_id INTEGER NOT NULL,
+role_id INTEGER NOT NULL,
+PRIMARY KEY (user_id, role_id),
+FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE
+) ;
+
+-- Create roles_permissions join table
+CREATE TABLE roles_permissions (
+role_id INTEGER NOT NULL,
+permission_id INTEGER NOT NULL,
+PRIMARY KEY (role_id, permission_id),
+FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE,
+FOREIGN KEY (permission_id) REFERENCES permissions (id) ON DELETE CASCADE
+) ;
+
+-- Seed the default roles and permissions
+INSERT INTO roles (name) VALUES ('admin'), ('staff'), ('user') ;
+
+INSERT INTO permissions (name) VALUES
+('todos.read_all'),
+('todos.read_one'),
+('todos.create'),
+('todos.update'),
+('todos.delete') ;
+
+-- Every role can manage todos
+INSERT INTO roles_permissions (role_id, permission_id)
+SELECT roles.id, permissions.id FROM roles, permissions
+WHERE permissions.name LIKE 'todos.%' ;
+
+-- Assign the default role to new users
+CREATE TRIGGER assign_default_role_to_new_user
+AFTER INSERT ON users
+BEGIN
+INSERT INTO users_roles (user_id, role_id)
+SELECT NEW.id, id FROM roles WHERE name = 'user' ;
+END ;
+
+-- Existing users get the default role as well
+INSERT INTO users_roles (user_id, role_id)
+SELECT users.id, roles.id FROM users, roles
+WHERE roles.name = 'user' ;
@@ -0,0 +1,5 @@
+-- Tie todos to the user that owns them. Todos created before this have no owner
+-- and are not visible to anyone.
+ALTER TABLE todos ADD COLUMN user_id INTEGER REFERENCES users (id) ON DELETE CASCADE ;
+
+CREATE INDEX todos_user_id_idx ON todos (user_id) ;
@@ -0,0 +1,12 @@
+-- Personal access tokens for the JSON API. Only the SHA-256 hash of a token is stored, the
+-- token itself is shown once when it is created.
+CREATE TABLE api_tokens (
+id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+name TEXT NOT NULL,
+token_hash TEXT NOT NULL UNIQUE,
+created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+last_used_at TEXT
+) ;
+
+CREATE INDEX api_tokens_user_id_idx ON api_tokens (user_id) ;
@@ -0,0 +1,2 @@
+-- Count how often a register token was replaced with a new one, so resends can be throttled
+ALTER TABLE registration_tokens ADD COLUMN resends INTEGER NOT NULL DEFAULT 0 ;
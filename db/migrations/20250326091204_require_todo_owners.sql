@@ -0,0 +1,37 @@
+-- Todos created before they had owners go to the first admin rather than being hidden from
+-- everyone, or to the first user when there is no admin yet, as the roles migration only gave
+-- existing users the `user` role.
+UPDATE todos SET user_id = COALESCE(
+(
+SELECT MIN(users_roles.user_id) FROM users_roles
+JOIN roles ON roles.id = users_roles.role_id
+WHERE roles.name = 'admin'
+),
+(SELECT MIN(id) FROM users)
+) WHERE user_id IS NULL ;
+
+-- SQLite can't make an existing column NOT NULL, so the table is rebuilt
+CREATE TABLE todos_with_owners (
+id INTEGER PRIMARY KEY NOT NULL,
+description TEXT NOT NULL,
+user_id INTEGER NOT NULL REFERENCES users (id) ON DELETE CASCADE
+) ;
+
+-- Without any user there is nobody to give them to, which fails the migration rather than
+-- deleting them
+CREATE TEMP TRIGGER todos_need_an_owner BEFORE INSERT ON todos_with_owners
+WHEN NEW.user_id IS NULL
+BEGIN
+SELECT RAISE(ABORT, 'todos without owners need a user to be given to, create one first') ;
+END ;
+
+INSERT INTO todos_with_owners (id, description, user_id)
+SELECT id, description, user_id FROM todos ;
+
+DROP TRIGGER todos_need_an_owner ;
+
+DROP TABLE todos ;
+
+ALTER TABLE todos_with_owners RENAME TO todos ;
+
+CREATE INDEX todos_user_id_idx ON todos (user_id) ;
@@ -0,0 +1,16 @@
+-- passwords are all hashed versions of 'testing12345'
+INSERT OR IGNORE INTO users (email, password_hash, status) VALUES (
+    'admin@example.com',
+    '$argon2id$v=19$m=19456,t=2,p=1$MYlgvlS1auvOAHtS5qnpVA$o4NMH0IGELKfiakQGCC+I8DRBBR+AAfhTC+65dBcbf8',
+    'confirmed'
+);
+
+INSERT OR IGNORE INTO users (email, password_hash, status) VALUES (
+    'user@example.com',
+    '$argon2id$v=19$m=19456,t=2,p=1$MYlgvlS1auvOAHtS5qnpVA$o4NMH0IGELKfiakQGCC+I8DRBBR+AAfhTC+65dBcbf8',
+    'confirmed'
+);
+
+INSERT OR IGNORE INTO users_roles (user_id, role_id)
+SELECT users.id, roles.id FROM users, roles
+WHERE users.email = 'admin@example.com' AND roles.name = 'admin';
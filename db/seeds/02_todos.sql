@@ -0,0 +1,11 @@
+INSERT INTO todos (description, user_id)
+SELECT seed.description, users.id
+FROM (
+    SELECT 'buy milk' AS description
+    UNION ALL SELECT 'walk the dog'
+    UNION ALL SELECT 'water the plants'
+) AS seed
+JOIN users ON users.email = 'user@example.com'
+WHERE NOT EXISTS (
+    SELECT 1 FROM todos WHERE todos.description = seed.description AND todos.user_id = users.id
+);
@@ -0,0 +1,156 @@
+use rand::Rng as _;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Sqlite, prelude::FromRow};
+use validator::Validate;
+
+use crate::{Error, schema::Schema};
+
+/// Tokens start with this, so that they are easy to recognize, e.g. by secret scanners.
+const TOKEN_PREFIX: &str = "nh_";
+
+/// A personal access token, which lets API clients act as the user it was issued to.
+///
+/// Only the hash of the token is stored, see [`ApiToken::create`].
+#[derive(Clone, Debug, FromRow, Serialize, Schema)]
+pub struct ApiToken {
+    pub id: i64,
+    #[serde(skip)]
+    pub user_id: i64,
+    /// What the token is for, e.g. the device it is used on.
+    pub name: String,
+    pub created_at: String,
+    /// When the token was last used to authenticate a request, if ever.
+    pub last_used_at: Option<String>,
+}
+
+/// A token that was just created, the only time the token itself is known.
+#[derive(Clone, Debug, Serialize, Schema)]
+pub struct IssuedApiToken {
+    #[serde(flatten)]
+    pub api_token: ApiToken,
+    /// The token to send as `Authorization: Bearer <token>`.
+    pub token: String,
+}
+
+/// NewApiToken is a changeset for issuing a token to a user, who proves who they are with
+/// their credentials.
+#[derive(Deserialize, Validate, Clone, Debug, Schema)]
+#[cfg_attr(feature = "test-helpers", derive(serde::Serialize))]
+pub struct NewApiToken {
+    #[validate(email(message = "Must be a valid email address"))]
+    pub email: String,
+    #[validate(length(min = 8, message = "password must be at least 8 characters"))]
+    pub password: String,
+    #[validate(length(min = 1, max = 100, message = "name must be 1 to 100 characters long"))]
+    pub name: String,
+}
+
+impl ApiToken {
+    /// Creates a token for a user, returning it along with the token itself, which is not
+    /// stored and can't be shown again.
+    pub async fn create(
+        name: &str,
+        user_id: i64,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<IssuedApiToken, Error> {
+        let token = generate_token();
+        let token_hash = hash_token(&token);
+
+        let api_token = sqlx::query_as!(
+            ApiToken,
+            r#"INSERT INTO api_tokens (user_id, name, token_hash) VALUES (?, ?, ?)
+            RETURNING id, user_id, name, created_at, last_used_at
+
+"#,
+            user_id,
+            name,
+            token_hash
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(IssuedApiToken { api_token, token })
+    }
+
+    /// Loads the tokens of a user, newest first.
+    pub async fn load_all(
+        user_id: i64,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<Vec<ApiToken>, Error> {
+        let api_tokens = sqlx::query_as!(
+            ApiToken,
+            r#"SELECT id, user_id, name, created_at, last_used_at FROM api_tokens
+            WHERE user_id = ? ORDER BY id DESC
+
+"#,
+            user_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(api_tokens)
+    }
+
+    /// Deletes a token of a user, after which it no longer authenticates anyone.
+    ///
+    /// Fails with [`Error::NoRecordFound`] when the user has no such token.
+    pub async fn revoke(
+        id: i64,
+        user_id: i64,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<ApiToken, Error> {
+        sqlx::query_as!(
+            ApiToken,
+            r#"DELETE FROM api_tokens WHERE id = ? AND user_id = ?
+            RETURNING id, user_id, name, created_at, last_used_at
+
+"#,
+            id,
+            user_id
+        )
+        .fetch_optional(executor)
+        .await?
+        .ok_or(Error::NoRecordFound)
+    }
+
+    /// Returns the id of the user a token was issued to, marking the token as used.
+    pub async fn try_get_user_id_by_token(
+        token: &str,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<Option<i64>, Error> {
+        let token_hash = hash_token(token);
+
+        let maybe_user_id = sqlx::query!(
+            r#"UPDATE api_tokens SET last_used_at = CURRENT_TIMESTAMP WHERE token_hash = ?
+            RETURNING user_id
+
+"#,
+            token_hash
+        )
+        .fetch_optional(executor)
+        .await?
+        .map(|row| row.user_id);
+
+        Ok(maybe_user_id)
+    }
+}
+
+fn generate_token() -> String {
+    let mut rng = rand::rng();
+    let random: String = std::iter::repeat_with(|| rng.sample(rand::distr::Alphanumeric))
+        .map(char::from)
+        .take(40)
+        .collect();
+
+    format!("{TOKEN_PREFIX}{random}")
+}
+
+/// Tokens are long and random, so a fast hash is enough to keep them safe at rest and lets
+/// them be looked up by their hash.
+fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
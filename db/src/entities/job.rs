@@ -0,0 +1,152 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, Type, prelude::FromRow};
+
+use crate::Error;
+
+/// A background job in the `Jobs` table that apalis uses as its queue.
+#[derive(Clone, Debug, FromRow, Serialize)]
+pub struct Job {
+    pub id: String,
+    pub job_type: String,
+    pub status: JobStatus,
+    pub attempts: i64,
+    pub max_attempts: i64,
+    /// When the job is due to run, as a unix timestamp.
+    pub run_at: i64,
+    pub last_error: Option<String>,
+    /// When the job finished, as a unix timestamp.
+    pub done_at: Option<i64>,
+}
+
+/// The status apalis stores for a job.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, Type)]
+#[sqlx(type_name = "TEXT")]
+pub enum JobStatus {
+    Pending,
+    Scheduled,
+    Running,
+    Done,
+    Failed,
+    Killed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Pending => "Pending",
+            JobStatus::Scheduled => "Scheduled",
+            JobStatus::Running => "Running",
+            JobStatus::Done => "Done",
+            JobStatus::Failed => "Failed",
+            JobStatus::Killed => "Killed",
+        }
+    }
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pending" => Ok(JobStatus::Pending),
+            "scheduled" => Ok(JobStatus::Scheduled),
+            "running" => Ok(JobStatus::Running),
+            "done" => Ok(JobStatus::Done),
+            "failed" => Ok(JobStatus::Failed),
+            "killed" => Ok(JobStatus::Killed),
+            unknown => Err(format!("unknown job status: {unknown}")),
+        }
+    }
+}
+
+impl Job {
+    /// Loads the most recently scheduled jobs, optionally only those with a given status.
+    pub async fn load_recent(
+        status: Option<JobStatus>,
+        limit: i64,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<Vec<Job>, Error> {
+        let jobs = sqlx::query_as!(
+            Job,
+            r#"SELECT id, job_type, status as "status: JobStatus", attempts, max_attempts, run_at, last_error, done_at
+            FROM Jobs
+            WHERE ?1 IS NULL OR status = ?1
+            ORDER BY run_at DESC
+            LIMIT ?2
+
+"#,
+            status,
+            limit
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(jobs)
+    }
+
+    /// Puts a job back in the queue to run again straight away with a fresh set of attempts.
+    pub async fn retry(
+        id: &str,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<Job, Error> {
+        let job = sqlx::query_as!(
+            Job,
+            r#"UPDATE Jobs
+            SET status = 'Pending', attempts = 0, run_at = strftime('%s', 'now'),
+                lock_by = NULL, lock_at = NULL, done_at = NULL, last_error = NULL
+            WHERE id = ?
+            RETURNING id, job_type, status as "status: JobStatus", attempts, max_attempts, run_at, last_error, done_at
+
+"#,
+            id
+        )
+        .fetch_optional(executor)
+        .await?
+        .ok_or(Error::NoRecordFound)?;
+
+        Ok(job)
+    }
+
+    /// Retries every job that failed or was killed, returning how many were queued again.
+    pub async fn retry_failed(
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            r#"UPDATE Jobs
+            SET status = 'Pending', attempts = 0, run_at = strftime('%s', 'now'),
+                lock_by = NULL, lock_at = NULL, done_at = NULL, last_error = NULL
+            WHERE status IN ('Failed', 'Killed')
+
+"#
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes every job with the given status, returning how many were deleted.
+    pub async fn purge(
+        status: JobStatus,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            r#"DELETE FROM Jobs WHERE status = ?
+
+"#,
+            status
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
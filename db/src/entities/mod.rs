@@ -3,9 +3,19 @@ use serde::de::DeserializeOwned;
 use sqlx::{Sqlite, SqlitePool, prelude::FromRow, sqlite::SqliteRow as DbRow};
 use validator::Validate;
 
-use crate::Error;
+use crate::{
+    Error,
+    pagination::{ListParams, Page},
+};
 
+pub use nohead_rs_macros::Entity;
+
+pub mod api_token;
+pub mod job;
+pub mod password_reset_token;
+pub mod permission;
 pub mod register_token;
+pub mod role;
 pub mod session;
 pub mod todo;
 pub mod user;
@@ -17,6 +27,9 @@ pub mod user;
 /// Implement the Model trait on a specific model to get a full set
 /// of common CRUD functions: list, show, create, update, delete
 ///
+/// Rather than writing the queries by hand, most entities can derive the
+/// implementation with `#[derive(Entity)]`, see [`nohead_rs_macros::Entity`].
+///
 /// # Example
 ///
 /// ```rust
@@ -44,34 +57,127 @@ pub trait Entity {
         executor: impl sqlx::Executor<'_, Database = Sqlite>,
     ) -> Result<Vec<Self::Record<'a>>, Error>;
 
+    /// Loads a page of records, sorted and filtered by the params.
+    async fn load_page<'a>(
+        params: &ListParams,
+        db_pool: &SqlitePool,
+    ) -> Result<Page<Self::Record<'a>>, Error>;
+
+    async fn load<'a>(
+        id: Self::Id,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<Self::Record<'a>, Error>;
+
+    async fn create<'a>(
+        record: Self::Changeset,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<Self::Record<'a>, Error>;
+
+    async fn create_batch(
+        records: Vec<Self::Changeset>,
+        db_pool: &SqlitePool,
+    ) -> Result<Vec<Self::Record<'_>>, Error>;
+
+    async fn update<'a>(
+        id: Self::Id,
+        record: Self::Changeset,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<Self::Record<'a>, Error>;
+
+    async fn delete<'a>(
+        id: Self::Id,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<Self::Record<'a>, Error>;
+
+    async fn delete_batch(
+        keys: Vec<Self::Id>,
+        db_pool: &SqlitePool,
+    ) -> Result<Vec<Self::Record<'_>>, Error>;
+}
+
+/// ------------------------------------------------------------------------
+/// # An OwnedEntity trait for tables whose records belong to a user
+/// ------------------------------------------------------------------------
+///
+/// Works like [`Entity`] but every method is scoped to the owner of the records, so
+/// a user can never load, change or delete another user's records. Loading a record
+/// owned by someone else fails with [`Error::NoRecordFound`], just like a missing one.
+///
+/// Prefer this over [`Entity`] for anything users create for themselves. It is
+/// derived by `#[derive(Entity)]` when the `owner` column is set.
+///
+/// # Example
+///
+/// ```rust
+/// #[async_trait]
+/// impl OwnedEntity for Note {
+///     type Id = i64;
+///     type Record<'a> = Note;
+///     type Changeset = NoteChangeset;
+///
+///     async fn load_all<'a>(
+///         owner_id: i64,
+///         executor: impl sqlx::Executor<'_, Database = Sqlite>,
+///     ) -> Result<Vec<Self::Record<'a>>, Error> {
+///         // select only the notes where user_id = owner_id
+///         Ok(vec![])
+///         }
+///     // ...other methods
+/// ```
+///
+/// ------------------------------------------------------------------------
+#[async_trait]
+pub trait OwnedEntity {
+    type Id: PartialOrd;
+    type Record<'a>: FromRow<'a, DbRow>;
+    type Changeset: Validate + DeserializeOwned;
+
+    async fn load_all<'a>(
+        owner_id: i64,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<Vec<Self::Record<'a>>, Error>;
+
+    /// Loads a page of the owner's records, sorted and filtered by the params.
+    async fn load_page<'a>(
+        params: &ListParams,
+        owner_id: i64,
+        db_pool: &SqlitePool,
+    ) -> Result<Page<Self::Record<'a>>, Error>;
+
     async fn load<'a>(
         id: Self::Id,
+        owner_id: i64,
         executor: impl sqlx::Executor<'_, Database = Sqlite>,
     ) -> Result<Self::Record<'a>, Error>;
 
     async fn create<'a>(
         record: Self::Changeset,
+        owner_id: i64,
         executor: impl sqlx::Executor<'_, Database = Sqlite>,
     ) -> Result<Self::Record<'a>, Error>;
 
     async fn create_batch(
         records: Vec<Self::Changeset>,
+        owner_id: i64,
         db_pool: &SqlitePool,
     ) -> Result<Vec<Self::Record<'_>>, Error>;
 
     async fn update<'a>(
         id: Self::Id,
         record: Self::Changeset,
+        owner_id: i64,
         executor: impl sqlx::Executor<'_, Database = Sqlite>,
     ) -> Result<Self::Record<'a>, Error>;
 
     async fn delete<'a>(
         id: Self::Id,
+        owner_id: i64,
         executor: impl sqlx::Executor<'_, Database = Sqlite>,
     ) -> Result<Self::Record<'a>, Error>;
 
     async fn delete_batch(
         keys: Vec<Self::Id>,
+        owner_id: i64,
         db_pool: &SqlitePool,
     ) -> Result<Vec<Self::Record<'_>>, Error>;
 }
@@ -0,0 +1,104 @@
+use rand::Rng as _;
+use serde::Deserialize;
+use sqlx::{Sqlite, prelude::FromRow, types::time::OffsetDateTime};
+use validator::Validate;
+
+use crate::Error;
+
+#[derive(Clone, FromRow)]
+pub struct PasswordResetToken {
+    pub reset_token: String,
+    pub user_id: i64,
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+/// ForgotPassword is a changeset for requesting a password reset email.
+#[derive(Deserialize, Validate, Clone, Debug)]
+#[cfg_attr(feature = "test-helpers", derive(serde::Serialize))]
+pub struct ForgotPassword {
+    #[validate(email(message = "Must be a valid email address"))]
+    pub email: String,
+}
+
+/// ResetPassword is a changeset for choosing a new password with an emailed reset token.
+#[derive(Deserialize, Validate, Clone, Debug)]
+#[cfg_attr(feature = "test-helpers", derive(serde::Serialize))]
+pub struct ResetPassword {
+    /// The reset token must be exactly 32 characters long.
+    #[validate(length(min = 32, max = 32, message = "token must be 32 characters long"))]
+    pub reset_token: String,
+    #[validate(length(min = 8, message = "password must be at least 8 characters"))]
+    pub password: String,
+    #[validate(must_match(other = "password", message = "passwords do not match"))]
+    pub confirm_password: String,
+}
+
+impl PasswordResetToken {
+    /// Returns the id of the user the reset token was issued to, as long as the token has not
+    /// expired yet.
+    pub async fn try_get_user_id_by_reset_token(
+        reset_password: &ResetPassword,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<Option<i64>, Error> {
+        reset_password.validate()?;
+        let maybe_user_id = sqlx::query!(
+            r#"SELECT user_id FROM password_reset_tokens
+            WHERE reset_token = ? AND expires_at > CURRENT_TIMESTAMP
+
+"#,
+            reset_password.reset_token
+        )
+        .fetch_optional(executor)
+        .await?
+        .map(|row| row.user_id);
+
+        Ok(maybe_user_id)
+    }
+
+    pub async fn create(
+        user_id: i64,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<PasswordResetToken, Error> {
+        let rand_token = generate_reset_token();
+        let reset_token = sqlx::query_as!(
+            PasswordResetToken,
+            r#"INSERT INTO password_reset_tokens (reset_token, user_id) VALUES (
+                $1, $2
+            ) RETURNING *
+
+            "#,
+            rand_token,
+            user_id
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(reset_token)
+    }
+
+    /// Deletes every reset token issued to a user, so that a token can only be used once and
+    /// requesting a new one invalidates the previous ones.
+    pub async fn delete_for_user(
+        user_id: i64,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"DELETE FROM password_reset_tokens WHERE user_id = ?
+
+"#,
+            user_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+}
+
+fn generate_reset_token() -> String {
+    let mut rng = rand::rng();
+    std::iter::repeat_with(|| rng.sample(rand::distr::Alphanumeric))
+        .map(char::from)
+        .take(32)
+        .collect()
+}
@@ -0,0 +1,43 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, prelude::FromRow};
+
+use crate::Error;
+
+/// A named permission granted to users through their roles, e.g. `todos.delete`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, FromRow, Deserialize, Serialize)]
+pub struct Permission {
+    pub name: String,
+}
+
+impl From<&str> for Permission {
+    fn from(name: &str) -> Self {
+        Permission {
+            name: name.to_string(),
+        }
+    }
+}
+
+impl Permission {
+    /// Loads every permission granted to a user through the roles assigned to them.
+    pub async fn load_for_user(
+        user_id: i64,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<HashSet<Permission>, Error> {
+        let permissions = sqlx::query_as!(
+            Permission,
+            r#"SELECT DISTINCT permissions.name FROM users_roles
+            JOIN roles_permissions ON roles_permissions.role_id = users_roles.role_id
+            JOIN permissions ON permissions.id = roles_permissions.permission_id
+            WHERE users_roles.user_id = ?
+
+"#,
+            user_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(permissions.into_iter().collect())
+    }
+}
@@ -1,46 +1,129 @@
 #[cfg(feature = "test-helpers")]
-use fake::Dummy;
+use fake::{Dummy, faker::internet::en::SafeEmail};
 
 use rand::Rng as _;
 use serde::Deserialize;
-use sqlx::{Sqlite, prelude::FromRow, types::time::OffsetDateTime};
+use sqlx::{Sqlite, SqliteConnection, prelude::FromRow, types::time::OffsetDateTime};
 use validator::Validate;
 
 use crate::Error;
 
+/// The number of wrong guesses allowed for a user's register token before it is invalidated
+/// and a new one has to be requested.
+pub const MAX_REGISTER_TOKEN_ATTEMPTS: i64 = 5;
+
+/// The number of times a user's register token can be replaced with a new one before it
+/// expires, after which no new one is sent until it has.
+pub const MAX_REGISTER_TOKEN_RESENDS: i64 = 3;
+
 #[derive(Clone, FromRow)]
 pub struct RegisterToken {
     pub register_token: String,
     pub user_id: i64,
     pub expires_at: Option<OffsetDateTime>,
+    pub attempts: i64,
+    pub resends: i64,
 }
 
 #[derive(Deserialize, Validate, Clone)]
 #[cfg_attr(feature = "test-helpers", derive(serde::Serialize, Dummy))]
 pub struct RegisterTokenValidate {
+    /// The email address the register token was sent to.
+    #[cfg_attr(feature = "test-helpers", dummy(faker = "SafeEmail()"))]
+    #[validate(email(message = "Must be a valid email address"))]
+    pub email: String,
     /// The register token must be exactly 6 characters long.
     #[cfg_attr(feature = "test-helpers", dummy(expr = "generate_register_token()"))]
     #[validate(length(min = 6, max = 6, message = "token must be 6 characters long"))]
     pub register_token: String,
 }
 
+/// RegisterTokenResend is a changeset for requesting a new register token.
+#[derive(Deserialize, Validate, Clone, Debug)]
+#[cfg_attr(feature = "test-helpers", derive(serde::Serialize))]
+pub struct RegisterTokenResend {
+    #[validate(email(message = "Must be a valid email address"))]
+    pub email: String,
+}
+
+/// The outcome of checking a register token guess with [`RegisterToken::verify`].
+#[derive(Debug, PartialEq)]
+pub enum RegisterTokenVerification {
+    /// The token matched, holds the id of the user it was issued to.
+    Verified(i64),
+    /// No token matched the email and token combination.
+    Invalid,
+    /// The token matched but it has expired.
+    Expired,
+    /// The maximum number of guesses has been reached and the token has been invalidated.
+    TooManyAttempts,
+}
+
 impl RegisterToken {
-    pub async fn try_get_user_id_by_register_token(
+    /// Checks a register token guess for the user with the given email.
+    ///
+    /// Every guess is counted against the user's token and once [`MAX_REGISTER_TOKEN_ATTEMPTS`]
+    /// is reached the token is deleted. A correct guess consumes the token so it can't be used
+    /// again.
+    ///
+    /// The counted attempts are only persisted when the caller commits, so the connection should
+    /// be committed regardless of the outcome.
+    pub async fn verify(
         register_token: RegisterTokenValidate,
-        executor: impl sqlx::Executor<'_, Database = Sqlite>,
-    ) -> Result<Option<i64>, Error> {
+        conn: &mut SqliteConnection,
+    ) -> Result<RegisterTokenVerification, Error> {
         register_token.validate()?;
-        let maybe_user_id = sqlx::query!(
-            r#"SELECT user_id FROM registration_tokens WHERE register_token = ?
+
+        // Count the attempt before anything else so that a transaction takes the write lock
+        // straight away, rather than failing to upgrade from a read lock under contention.
+        let Some(token) = Self::record_attempt(&register_token.email, &mut *conn).await? else {
+            return Ok(RegisterTokenVerification::Invalid);
+        };
+
+        if token.attempts > MAX_REGISTER_TOKEN_ATTEMPTS {
+            Self::delete_for_user(token.user_id, &mut *conn).await?;
+            return Ok(RegisterTokenVerification::TooManyAttempts);
+        }
+
+        if token.register_token != register_token.register_token {
+            if token.attempts >= MAX_REGISTER_TOKEN_ATTEMPTS {
+                Self::delete_for_user(token.user_id, &mut *conn).await?;
+                return Ok(RegisterTokenVerification::TooManyAttempts);
+            }
+            return Ok(RegisterTokenVerification::Invalid);
+        }
+
+        if token.is_expired() {
+            return Ok(RegisterTokenVerification::Expired);
+        }
+
+        Self::delete_for_user(token.user_id, &mut *conn).await?;
+
+        Ok(RegisterTokenVerification::Verified(token.user_id))
+    }
+
+    /// Whether the token is past its expiry date.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= OffsetDateTime::now_utc())
+    }
+
+    pub async fn try_get_by_email(
+        email: &str,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<Option<RegisterToken>, Error> {
+        let register_token = sqlx::query_as!(
+            RegisterToken,
+            r#"SELECT * FROM registration_tokens
+            WHERE user_id = (SELECT id FROM users WHERE email = ?)
 
 "#,
-            register_token.register_token
+            email
         )
         .fetch_optional(executor)
-        .await?
-        .map(|row| row.user_id);
+        .await?;
 
-        Ok(maybe_user_id)
+        Ok(register_token)
     }
 
     pub async fn create<'a>(
@@ -63,6 +146,89 @@ impl RegisterToken {
 
         Ok(register_token)
     }
+
+    /// Replaces the register token of a user with a new one, carrying over how often it was
+    /// replaced while the previous one was still valid.
+    ///
+    /// Returns `None` without replacing the token once it was replaced
+    /// [`MAX_REGISTER_TOKEN_RESENDS`] times, until it expires. Like [`RegisterToken::verify`],
+    /// the connection should be committed regardless of the outcome.
+    pub async fn reissue(
+        user_id: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<RegisterToken>, Error> {
+        // Count the resend before anything else so that a transaction takes the write lock
+        // straight away, rather than failing to upgrade from a read lock under contention.
+        let resends = sqlx::query_scalar!(
+            r#"UPDATE registration_tokens SET resends = resends + 1
+            WHERE user_id = ? AND expires_at > CURRENT_TIMESTAMP RETURNING resends
+
+"#,
+            user_id
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .unwrap_or(0);
+
+        if resends > MAX_REGISTER_TOKEN_RESENDS {
+            return Ok(None);
+        }
+
+        Self::delete_for_user(user_id, &mut *conn).await?;
+        let rand_token = generate_register_token();
+        let register_token = sqlx::query_as!(
+            RegisterToken,
+            r#"INSERT INTO registration_tokens (register_token, user_id, resends) VALUES (
+                ?, ?, ?
+            ) RETURNING *
+
+            "#,
+            rand_token,
+            user_id,
+            resends
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(Some(register_token))
+    }
+
+    /// Counts a guess against the register token of the user with the given email and returns
+    /// the token with its updated number of attempts.
+    pub async fn record_attempt(
+        email: &str,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<Option<RegisterToken>, Error> {
+        let register_token = sqlx::query_as!(
+            RegisterToken,
+            r#"UPDATE registration_tokens SET attempts = attempts + 1
+            WHERE user_id = (SELECT id FROM users WHERE email = ?) RETURNING *
+
+"#,
+            email
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(register_token)
+    }
+
+    /// Deletes every register token issued to a user.
+    pub async fn delete_for_user(
+        user_id: i64,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"DELETE FROM registration_tokens WHERE user_id = ?
+
+"#,
+            user_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
 }
 
 fn generate_register_token() -> String {
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, prelude::FromRow};
+
+use crate::Error;
+
+/// A named group of permissions that can be assigned to users, e.g. `admin`.
+#[derive(Clone, Debug, FromRow, Deserialize, Serialize)]
+pub struct Role {
+    pub id: i64,
+    pub name: String,
+}
+
+impl Role {
+    /// Loads every role assigned to a user.
+    pub async fn load_for_user(
+        user_id: i64,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<Vec<Role>, Error> {
+        let roles = sqlx::query_as!(
+            Role,
+            r#"SELECT roles.id, roles.name FROM roles
+            JOIN users_roles ON users_roles.role_id = roles.id
+            WHERE users_roles.user_id = ?
+
+"#,
+            user_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(roles)
+    }
+
+    /// Assigns the role with the given name to a user.
+    ///
+    /// Assigning a role the user already has, or one that doesn't exist, is a no-op.
+    pub async fn assign(
+        user_id: i64,
+        name: &str,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"INSERT OR IGNORE INTO users_roles (user_id, role_id)
+            SELECT ?, id FROM roles WHERE name = ?
+
+"#,
+            user_id,
+            name
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Revokes the role with the given name from a user.
+    pub async fn revoke(
+        user_id: i64,
+        name: &str,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"DELETE FROM users_roles
+            WHERE user_id = ? AND role_id = (SELECT id FROM roles WHERE name = ?)
+
+"#,
+            user_id,
+            name
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -13,7 +13,7 @@ use fake::{
     faker::internet::{en::Password, en::SafeEmail},
 };
 
-use crate::{Error, ResultExt};
+use crate::{Error, ResultExt, schema::Schema};
 
 #[derive(Clone, FromRow, Deserialize, Serialize)]
 pub struct User {
@@ -23,18 +23,27 @@ pub struct User {
     pub status: UserStatus,
 }
 
-#[derive(Clone, Deserialize, Serialize, Debug, Type)]
-#[sqlx(type_name = "TEXT")]
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Type)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
 pub enum UserStatus {
+    /// The user has confirmed their email address.
     Confirmed,
+    /// The user has registered but not yet confirmed their email address.
     Pending,
+    /// The user has been suspended and can no longer log in.
+    Suspended,
+    /// The user has been deleted and is treated as if they don't exist.
+    Deleted,
 }
 
 impl From<String> for UserStatus {
     fn from(val: String) -> Self {
-        match val.as_str() {
+        // Older rows were written with their variant name, e.g. "Confirmed"
+        match val.to_lowercase().as_str() {
             "confirmed" => UserStatus::Confirmed,
             "pending" => UserStatus::Pending,
+            "suspended" => UserStatus::Suspended,
+            "deleted" => UserStatus::Deleted,
             _ => UserStatus::Pending,
         }
     }
@@ -60,7 +69,7 @@ impl std::fmt::Debug for User {
 /// ```
 /// let user: RegisterUser = Faker.fake();
 /// ```
-#[derive(Deserialize, Validate, Clone, Debug)]
+#[derive(Deserialize, Validate, Clone, Debug, Schema)]
 #[cfg_attr(feature = "test-helpers", derive(serde::Serialize))]
 pub struct RegisterUser {
     #[validate(email(message = "Must be a valid email address"))]
@@ -99,7 +108,7 @@ impl Dummy<Faker> for RegisterUser {
 /// ```
 /// let creds: UserCredentials = Faker.fake();
 /// ```
-#[derive(Deserialize, Validate, Clone, Debug)]
+#[derive(Deserialize, Validate, Clone, Debug, Schema)]
 #[cfg_attr(feature = "test-helpers", derive(serde::Serialize))]
 pub struct UserCredentials {
     #[validate(email(message = "Must be a valid email address"))]
@@ -208,6 +217,28 @@ impl User {
 
         Ok(user)
     }
+
+    pub async fn update_password(
+        id: i64,
+        password: &str,
+        executor: impl sqlx::Executor<'_, Database = Sqlite>,
+    ) -> Result<User, Error> {
+        let password_hash = generate_password_hash(password)?;
+
+        let user = sqlx::query_as!(
+            User,
+            r#"update users set password_hash = (?) where id = (?) returning *
+
+"#,
+            password_hash,
+            id
+        )
+        .fetch_optional(executor)
+        .await?
+        .ok_or(Error::NoRecordFound)?;
+
+        Ok(user)
+    }
 }
 
 /// ------------------------------------------------------------------------
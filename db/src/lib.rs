@@ -1,19 +1,42 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, str::FromStr, time::Duration};
 
-use nohead_rs_config::DatabaseConfig;
-use sqlx::{Sqlite, Transaction, sqlite::SqlitePoolOptions};
+use nohead_rs_config::{DatabaseConfig, JournalMode, Synchronous};
+use sqlx::{
+    Sqlite, Transaction,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
+};
 
 pub use serde::de::DeserializeOwned;
 pub use sqlx::SqlitePool as DbPool;
 pub use sqlx::test as db_test;
 pub use validator::Validate;
 
+// Lets the code generated by `#[derive(Entity)]` refer to this crate by name from within it.
+extern crate self as nohead_rs_db;
+
 /// Custom migrator set to the correct path within the api testing environment
 pub static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("../db/migrations");
 
 /// Entity definitions and related general queries.
 pub mod entities;
 
+/// Applying, reverting and inspecting migrations, and seeding the database.
+pub mod migrate;
+
+/// Query params and helpers for listing records a page at a time.
+pub mod pagination;
+
+/// JSON schemas of entities and changesets, e.g. for the OpenAPI document of the API.
+pub mod schema;
+
+/// Re-exports used by the code generated by `#[derive(Entity)]`.
+#[doc(hidden)]
+pub mod __private {
+    pub use async_trait::async_trait;
+    pub use serde_json;
+    pub use sqlx;
+}
+
 /// Starts a new database transaction.
 ///
 /// Example:
@@ -36,14 +59,76 @@ pub async fn transaction(db_pool: &DbPool) -> Result<Transaction<'static, Sqlite
 }
 
 /// Creates a connection pool to the database specified in the passed [`{{project-name}}-config::DatabaseConfig`]
+///
+/// When a separate read pool is configured this pool only opens a single connection, so it
+/// should be used for writes and [`connect_read_pool`] for everything else.
 pub async fn connect_pool(config: &DatabaseConfig) -> Result<DbPool, Error> {
+    let max_connections = match config.read_pool {
+        Some(_) => 1,
+        None => config.max_connections,
+    };
+
     let pool = SqlitePoolOptions::new()
-        .connect(config.url.as_str())
+        .max_connections(max_connections)
+        .min_connections(config.min_connections.min(max_connections))
+        .connect_with(connect_options(config)?.journal_mode(journal_mode(config)))
         .await?;
 
     Ok(pool)
 }
 
+/// Creates a pool of read only connections if `[database.read_pool]` is configured, otherwise
+/// returns the passed pool so that reads and writes share it.
+pub async fn connect_read_pool(config: &DatabaseConfig, db_pool: &DbPool) -> Result<DbPool, Error> {
+    let Some(read_pool) = &config.read_pool else {
+        return Ok(db_pool.clone());
+    };
+
+    // The journal mode is persisted in the database by the writer and can't be changed by a
+    // read only connection, so it is left alone here.
+    let pool = SqlitePoolOptions::new()
+        .max_connections(read_pool.max_connections)
+        .min_connections(read_pool.min_connections)
+        .connect_with(connect_options(config)?.read_only(true))
+        .await?;
+
+    Ok(pool)
+}
+
+fn connect_options(config: &DatabaseConfig) -> Result<SqliteConnectOptions, Error> {
+    let synchronous = match config.synchronous {
+        Synchronous::Off => SqliteSynchronous::Off,
+        Synchronous::Normal => SqliteSynchronous::Normal,
+        Synchronous::Full => SqliteSynchronous::Full,
+        Synchronous::Extra => SqliteSynchronous::Extra,
+    };
+
+    let options = SqliteConnectOptions::from_str(&config.url)?
+        .synchronous(synchronous)
+        .busy_timeout(Duration::from_millis(config.busy_timeout))
+        .foreign_keys(config.foreign_keys);
+
+    let options = config
+        .pragmas
+        .iter()
+        .fold(options, |options, (key, value)| {
+            options.pragma(key.clone(), value.clone())
+        });
+
+    Ok(options)
+}
+
+fn journal_mode(config: &DatabaseConfig) -> SqliteJournalMode {
+    match config.journal_mode {
+        JournalMode::Delete => SqliteJournalMode::Delete,
+        JournalMode::Truncate => SqliteJournalMode::Truncate,
+        JournalMode::Persist => SqliteJournalMode::Persist,
+        JournalMode::Memory => SqliteJournalMode::Memory,
+        JournalMode::Wal => SqliteJournalMode::Wal,
+        JournalMode::Off => SqliteJournalMode::Off,
+    }
+}
+
 /// Errors that can occur as a result of a data layer operation.
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -65,6 +150,18 @@ pub enum Error {
     /// An error occurred while hashing a password.
     #[error("password hashing failed")]
     PasswordHashError(#[from] argon2::password_hash::Error),
+    /// Applying or reverting migrations failed.
+    #[error("migration failed")]
+    MigrateError(#[from] sqlx::migrate::MigrateError),
+    /// A migration without a down script was asked to be reverted.
+    #[error("migration {0} cannot be reverted")]
+    IrreversibleMigration(i64),
+    /// The database has applied migrations that this build doesn't know about.
+    #[error("database has unknown migrations: {0:?}")]
+    UnknownMigrations(Vec<i64>),
+    /// Reading a file from disk failed, e.g. a seed file.
+    #[error("reading file failed")]
+    IoError(#[from] std::io::Error),
 }
 
 /// ------------------------------------------------------------------------------------------
@@ -0,0 +1,223 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use nohead_rs_config::DatabaseConfig;
+use sqlx::{
+    Sqlite,
+    migrate::{Migrate, MigrateDatabase},
+};
+
+use crate::{DbPool, Error, MIGRATOR, connect_pool, transaction};
+
+/// What happened to the database when migrations were run at startup.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StartupMigration {
+    /// The pending migrations were applied, which may have been none.
+    Applied(Vec<i64>),
+    /// The database is a read-only LiteFS replica, which receives migrations from the primary.
+    SkippedOnReplica,
+}
+
+/// The state of a single migration in a database.
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    /// Whether the migration has been applied to the database.
+    pub applied: bool,
+    /// Whether the migration was changed after it was applied.
+    pub checksum_mismatch: bool,
+    /// Whether the migration has a down script that `undo` can run.
+    pub reversible: bool,
+}
+
+/// Creates the database file if it doesn't exist yet.
+///
+/// Returns `true` when a new database was created.
+pub async fn create_database_if_missing(config: &DatabaseConfig) -> Result<bool, Error> {
+    if Sqlite::database_exists(&config.url).await? {
+        return Ok(false);
+    }
+
+    Sqlite::create_database(&config.url).await?;
+
+    Ok(true)
+}
+
+/// Applies every pending migration.
+pub async fn run(db_pool: &DbPool) -> Result<(), Error> {
+    MIGRATOR.run(db_pool).await?;
+
+    Ok(())
+}
+
+/// Reverts the last `steps` applied migrations, returning the versions that were reverted.
+///
+/// Fails without reverting anything if one of the migrations has no down script.
+pub async fn undo(db_pool: &DbPool, steps: usize) -> Result<Vec<i64>, Error> {
+    let mut conn = db_pool.acquire().await?;
+    let mut applied: Vec<i64> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+    applied.sort_unstable_by(|a, b| b.cmp(a));
+
+    let reverted: Vec<i64> = applied.iter().take(steps).copied().collect();
+    if let Some(version) = reverted
+        .iter()
+        .find(|version| !is_reversible(**version))
+        .copied()
+    {
+        return Err(Error::IrreversibleMigration(version));
+    }
+
+    // `undo` reverts every migration newer than the target version.
+    let target = applied.get(steps).copied().unwrap_or(0);
+    MIGRATOR.undo(&mut *conn, target).await?;
+
+    Ok(reverted)
+}
+
+/// Brings the database up to date when the app starts, see `[database] auto_migrate`.
+///
+/// The database is created if it doesn't exist yet. On a LiteFS replica nothing is written,
+/// but the database is still checked. Fails if the database has applied migrations that
+/// this build doesn't know about, e.g. after rolling back a deploy, since its queries may
+/// not match the schema.
+pub async fn run_on_startup(config: &DatabaseConfig) -> Result<StartupMigration, Error> {
+    let replica = is_litefs_replica(config);
+    if !replica {
+        create_database_if_missing(config).await?;
+    }
+
+    let db_pool = connect_pool(config).await?;
+
+    let unknown = unknown_migrations(&db_pool).await?;
+    if !unknown.is_empty() {
+        return Err(Error::UnknownMigrations(unknown));
+    }
+
+    if replica {
+        return Ok(StartupMigration::SkippedOnReplica);
+    }
+
+    let pending = status(&db_pool)
+        .await?
+        .into_iter()
+        .filter(|migration| !migration.applied)
+        .map(|migration| migration.version)
+        .collect();
+    run(&db_pool).await?;
+    db_pool.close().await;
+
+    Ok(StartupMigration::Applied(pending))
+}
+
+/// Whether the database lives on a LiteFS replica, where it is read only.
+///
+/// LiteFS puts a `.primary` file next to the database on every node but the primary.
+pub fn is_litefs_replica(config: &DatabaseConfig) -> bool {
+    let path = config
+        .url
+        .trim_start_matches("sqlite:")
+        .trim_start_matches("//");
+    let path = path.split('?').next().unwrap_or_default();
+
+    Path::new(path)
+        .parent()
+        .is_some_and(|dir| dir.join(".primary").exists())
+}
+
+/// Lists the versions of migrations that were applied to the database but aren't in [`MIGRATOR`].
+pub async fn unknown_migrations(db_pool: &DbPool) -> Result<Vec<i64>, Error> {
+    // Check for the migrations table first rather than creating it, which would write to replicas.
+    let has_migrations_table = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations') as "exists!: bool"
+
+"#
+    )
+    .fetch_one(db_pool)
+    .await?;
+    if !has_migrations_table {
+        return Ok(vec![]);
+    }
+
+    let mut conn = db_pool.acquire().await?;
+    let unknown = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .filter(|version| {
+            MIGRATOR
+                .iter()
+                .all(|migration| migration.version != *version)
+        })
+        .collect();
+
+    Ok(unknown)
+}
+
+/// Lists every known migration along with whether it has been applied.
+pub async fn status(db_pool: &DbPool) -> Result<Vec<MigrationStatus>, Error> {
+    let mut conn = db_pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+
+    let applied: HashMap<i64, Vec<u8>> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| (migration.version, migration.checksum.into_owned()))
+        .collect();
+
+    let status = MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| {
+            let checksum = applied.get(&migration.version);
+
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.to_string(),
+                applied: checksum.is_some(),
+                checksum_mismatch: checksum
+                    .is_some_and(|checksum| *checksum != *migration.checksum),
+                reversible: is_reversible(migration.version),
+            }
+        })
+        .collect();
+
+    Ok(status)
+}
+
+/// Runs every `.sql` file in `dir` in file name order within a single transaction.
+///
+/// Returns the files that were run. Seeds should be written so that running them
+/// twice is harmless, e.g. with `INSERT OR IGNORE`.
+pub async fn seed(db_pool: &DbPool, dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "sql"))
+        .collect();
+    files.sort();
+
+    let mut tx = transaction(db_pool).await?;
+    for file in &files {
+        let sql = fs::read_to_string(file)?;
+        sqlx::raw_sql(&sql).execute(&mut *tx).await?;
+    }
+    tx.commit().await?;
+
+    Ok(files)
+}
+
+fn is_reversible(version: i64) -> bool {
+    MIGRATOR.iter().any(|migration| {
+        migration.version == version && migration.migration_type.is_down_migration()
+    })
+}
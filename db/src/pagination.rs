@@ -0,0 +1,166 @@
+use serde::{Deserialize, Deserializer, Serialize, de::IntoDeserializer as _};
+
+/// The page size used when a request doesn't ask for one.
+pub const DEFAULT_PER_PAGE: u32 = 25;
+
+/// The largest page size a request can ask for.
+pub const MAX_PER_PAGE: u32 = 100;
+
+/// The direction to sort a list in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    /// The SQL keyword for the sort order.
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// ------------------------------------------------------------------------
+/// # Query params for listing a page of records
+/// ------------------------------------------------------------------------
+///
+/// Deserialize it from the query string of a list route, e.g.
+/// `/todos?page=2&per_page=50&sort=description&order=desc&filter=milk`, and
+/// pass it to `load_page`. Every field is optional and out of range values are
+/// clamped, so any query string produces a valid page.
+///
+/// `sort` is only ever used as a column name after being checked against the
+/// columns an entity allows with [`ListParams::sort_column`].
+///
+/// # Example
+///
+/// ```rust
+/// async fn read_all(
+///     Query(params): Query<ListParams>,
+///     State(app_state): State<AppState>,
+/// ) -> Result<Page<Todo>, Error> {
+///     Todo::load_page(&params, user.id, &app_state.db_pool).await
+/// }
+/// ```
+/// ------------------------------------------------------------------------
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ListParams {
+    /// The page to load, starting at 1.
+    pub page: Option<u32>,
+    /// The number of records per page, at most [`MAX_PER_PAGE`].
+    pub per_page: Option<u32>,
+    /// The column to sort by.
+    pub sort: Option<String>,
+    /// The direction to sort in.
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub order: Option<SortOrder>,
+    /// A search term to filter the records by, each entity decides which columns it matches.
+    pub filter: Option<String>,
+}
+
+/// Reads an empty `order=` as no order, which forms submit for an unselected option.
+fn empty_as_none<'de, D>(deserializer: D) -> Result<Option<SortOrder>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)?.as_deref() {
+        None | Some("") => Ok(None),
+        Some(order) => SortOrder::deserialize(order.into_deserializer()).map(Some),
+    }
+}
+
+impl ListParams {
+    pub fn page(&self) -> u32 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn per_page(&self) -> u32 {
+        self.per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE)
+    }
+
+    /// The number of records to `LIMIT` the query to.
+    pub fn limit(&self) -> i64 {
+        i64::from(self.per_page())
+    }
+
+    /// The number of records to skip with `OFFSET`.
+    pub fn offset(&self) -> i64 {
+        i64::from(self.page() - 1) * self.limit()
+    }
+
+    pub fn order(&self) -> SortOrder {
+        self.order.unwrap_or_default()
+    }
+
+    /// The column to sort by, falling back to `default` when the requested column isn't one of
+    /// the `allowed` columns.
+    pub fn sort_column<'a>(&self, allowed: &[&'a str], default: &'a str) -> &'a str {
+        self.sort
+            .as_deref()
+            .and_then(|sort| allowed.iter().find(|column| **column == sort))
+            .copied()
+            .unwrap_or(default)
+    }
+
+    /// The filter as a `LIKE` pattern, or `None` when there is nothing to filter by.
+    pub fn filter_pattern(&self) -> Option<String> {
+        self.filter
+            .as_deref()
+            .map(str::trim)
+            .filter(|filter| !filter.is_empty())
+            .map(|filter| {
+                let escaped = filter
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_");
+                format!("%{escaped}%")
+            })
+    }
+}
+
+/// A page of records along with what's needed to link to the pages around it.
+#[derive(Clone, Debug, Serialize)]
+pub struct Page<T> {
+    /// The records on this page.
+    pub items: Vec<T>,
+    /// The current page, starting at 1.
+    pub page: u32,
+    pub per_page: u32,
+    /// The number of records across all pages.
+    pub total: i64,
+    pub total_pages: u32,
+    /// The page after this one, if there is one.
+    pub next_page: Option<u32>,
+    /// The page before this one, if there is one.
+    pub prev_page: Option<u32>,
+    /// The params the page was loaded with, for building links that keep the sort and filter.
+    pub params: ListParams,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, total: i64, params: &ListParams) -> Self {
+        let page = params.page();
+        let per_page = params.per_page();
+        let total_pages = u32::try_from(total.max(0))
+            .unwrap_or(u32::MAX)
+            .div_ceil(per_page);
+
+        Page {
+            items,
+            page,
+            per_page,
+            total,
+            total_pages,
+            next_page: (page < total_pages).then_some(page + 1),
+            prev_page: (page > 1).then(|| (page - 1).min(total_pages.max(1))),
+            params: params.clone(),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use serde_json::{Map, Value, json};
+
+pub use nohead_rs_macros::Schema;
+
+/// ------------------------------------------------------------------------
+/// # A type that is described by a JSON schema, e.g. in the OpenAPI document
+/// ------------------------------------------------------------------------
+///
+/// Rather than writing the schema by hand, derive it with `#[derive(Schema)]`, which reads
+/// the field types, doc comments, `#[serde(...)]` and `#[validate(...)]` attributes, see
+/// [`nohead_rs_macros::Schema`].
+///
+/// # Example
+///
+/// ```rust
+/// #[derive(Deserialize, Validate, Schema)]
+/// pub struct NoteChangeset {
+///     /// What the note says.
+///     #[validate(length(min = 1))]
+///     pub text: String,
+/// }
+/// ```
+/// ------------------------------------------------------------------------
+pub trait Schema {
+    /// The name of the schema, which other schemas refer to it by.
+    fn name() -> &'static str;
+
+    /// The JSON schema of the type.
+    fn schema() -> Value;
+
+    /// Adds the schema of the type, and of the types it refers to, to the schemas of an
+    /// OpenAPI document.
+    fn register(schemas: &mut Map<String, Value>)
+    where
+        Self: Sized,
+    {
+        schemas.insert(Self::name().to_string(), Self::schema());
+    }
+}
+
+/// A reference to the schema of a type in the components of an OpenAPI document.
+pub fn reference<T: Schema>() -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", T::name()) })
+}
+
+/// Lets a schema also be `null`, for `Option` fields.
+pub fn nullable(schema: Value) -> Value {
+    match schema.get("type").and_then(Value::as_str) {
+        Some(kind) => {
+            let mut schema = schema.clone();
+            schema["type"] = json!([kind, "null"]);
+            schema
+        }
+        None => json!({ "anyOf": [schema, { "type": "null" }] }),
+    }
+}
@@ -0,0 +1,511 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Ident, LitStr, Path, Type};
+
+/// The arguments of the `#[entity(...)]` attribute on the record struct.
+struct EntityArgs {
+    table: LitStr,
+    changeset: Path,
+    owner: Option<Ident>,
+    sortable: Vec<Ident>,
+    filterable: Vec<Ident>,
+    default_sort: Option<Ident>,
+}
+
+/// A field of the record struct and the column it is stored in.
+struct Column {
+    ident: Ident,
+    ty: Type,
+    id: bool,
+    skip: bool,
+}
+
+impl Column {
+    /// The column as selected by `query_as!`, forcing it to be non-null unless the field is an
+    /// `Option` so that sqlx doesn't have to infer nullability from the query.
+    fn select(&self) -> String {
+        if is_option(&self.ty) {
+            self.ident.to_string()
+        } else {
+            format!(r#"{0} as "{0}!""#, self.ident)
+        }
+    }
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let args = parse_args(&input)?;
+    let columns = parse_columns(&input)?;
+
+    let name = &input.ident;
+    let table = args.table.value();
+    let changeset = &args.changeset;
+
+    let id = match columns.iter().find(|column| column.id) {
+        Some(id) => id,
+        None => columns
+            .iter()
+            .find(|column| column.ident == "id")
+            .ok_or_else(|| {
+                syn::Error::new_spanned(
+                    name,
+                    "entity needs an `id` field or a field marked with `#[entity(id)]`",
+                )
+            })?,
+    };
+    let id_ident = &id.ident;
+    let id_ty = &id.ty;
+
+    if let Some(owner) = args
+        .owner
+        .as_ref()
+        .filter(|owner| !columns.iter().any(|column| column.ident == **owner))
+    {
+        return Err(syn::Error::new_spanned(
+            owner,
+            "the owner column must be a field of the entity",
+        ));
+    }
+
+    // The columns written from the changeset on create and update
+    let writable: Vec<&Column> = columns
+        .iter()
+        .filter(|column| {
+            column.ident != *id_ident && !column.skip && Some(&column.ident) != args.owner.as_ref()
+        })
+        .collect();
+    if writable.is_empty() {
+        return Err(syn::Error::new_spanned(
+            name,
+            "entity needs at least one field that is written from the changeset",
+        ));
+    }
+    let writable_idents: Vec<&Ident> = writable.iter().map(|column| &column.ident).collect();
+
+    let select = columns
+        .iter()
+        .map(Column::select)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let select_plain = columns
+        .iter()
+        .map(|column| column.ident.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    // Owned entities scope every query to the owner and bind the owner id last
+    let owner_clause = args
+        .owner
+        .as_ref()
+        .map(|owner| format!(" and {owner} = ?"))
+        .unwrap_or_default();
+    let owner_arg = args.owner.as_ref().map(|_| quote!(, owner_id));
+    let owner_param = args.owner.as_ref().map(|_| quote!(owner_id: i64,));
+
+    let load_all_sql = lit(match &args.owner {
+        Some(owner) => format!("select {select} from {table} where {owner} = ?"),
+        None => format!("select {select} from {table}"),
+    });
+    let load_sql = lit(format!(
+        "select {select} from {table} where {id_ident} = ?{owner_clause}"
+    ));
+    let insert_columns = writable_idents
+        .iter()
+        .map(ToString::to_string)
+        .chain(args.owner.iter().map(ToString::to_string))
+        .collect::<Vec<_>>();
+    let create_sql = lit(format!(
+        "insert into {table} ({}) values ({}) returning {select}",
+        insert_columns.join(", "),
+        vec!["?"; insert_columns.len()].join(", ")
+    ));
+    let update_sql = lit(format!(
+        "update {table} set {} where {id_ident} = ?{owner_clause} returning {select}",
+        writable_idents
+            .iter()
+            .map(|ident| format!("{ident} = ?"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+    let delete_sql = lit(format!(
+        "delete from {table} where {id_ident} = ?{owner_clause} returning {select}"
+    ));
+
+    let load_page = expand_load_page(&args, name, id_ident, &select_plain, &columns);
+
+    let entity_trait = match args.owner {
+        Some(_) => quote!(OwnedEntity),
+        None => quote!(Entity),
+    };
+    let owner_value = args.owner.as_ref().map(|_| quote!(owner_id));
+    let owner_value_arg = args.owner.as_ref().map(|_| quote!(owner_id,));
+
+    Ok(quote! {
+        #[::nohead_rs_db::__private::async_trait]
+        impl ::nohead_rs_db::entities::#entity_trait for #name {
+            type Id = #id_ty;
+
+            type Record<'a> = #name;
+
+            type Changeset = #changeset;
+
+            async fn load_all<'a>(
+                #owner_param
+                executor: impl ::nohead_rs_db::__private::sqlx::Executor<'_, Database = ::nohead_rs_db::__private::sqlx::Sqlite>,
+            ) -> Result<Vec<Self::Record<'a>>, ::nohead_rs_db::Error> {
+                let records = ::nohead_rs_db::__private::sqlx::query_as!(#name, #load_all_sql #owner_arg)
+                    .fetch_all(executor)
+                    .await?;
+
+                Ok(records)
+            }
+
+            #load_page
+
+            async fn load<'a>(
+                id: Self::Id,
+                #owner_param
+                executor: impl ::nohead_rs_db::__private::sqlx::Executor<'_, Database = ::nohead_rs_db::__private::sqlx::Sqlite>,
+            ) -> Result<Self::Record<'a>, ::nohead_rs_db::Error> {
+                let record = ::nohead_rs_db::__private::sqlx::query_as!(#name, #load_sql, id #owner_arg)
+                    .fetch_optional(executor)
+                    .await?
+                    .ok_or(::nohead_rs_db::Error::NoRecordFound)?;
+
+                Ok(record)
+            }
+
+            async fn create<'a>(
+                record: Self::Changeset,
+                #owner_param
+                executor: impl ::nohead_rs_db::__private::sqlx::Executor<'_, Database = ::nohead_rs_db::__private::sqlx::Sqlite>,
+            ) -> Result<Self::Record<'a>, ::nohead_rs_db::Error> {
+                use ::nohead_rs_db::ResultExt as _;
+
+                ::nohead_rs_db::Validate::validate(&record)?;
+
+                let record = ::nohead_rs_db::__private::sqlx::query_as!(
+                    #name,
+                    #create_sql,
+                    #(record.#writable_idents,)*
+                    #owner_value
+                )
+                .fetch_one(executor)
+                .await
+                .map_constraint_err()?;
+
+                Ok(record)
+            }
+
+            async fn create_batch(
+                records: Vec<Self::Changeset>,
+                #owner_param
+                db_pool: &::nohead_rs_db::DbPool,
+            ) -> Result<Vec<Self::Record<'_>>, ::nohead_rs_db::Error> {
+                let mut tx = ::nohead_rs_db::transaction(db_pool).await?;
+
+                let mut results: Vec<Self::Record<'_>> = vec![];
+
+                for record in records {
+                    let result = Self::create(record, #owner_value_arg &mut *tx).await?;
+                    results.push(result);
+                }
+
+                tx.commit().await?;
+
+                Ok(results)
+            }
+
+            async fn update<'a>(
+                id: Self::Id,
+                record: Self::Changeset,
+                #owner_param
+                executor: impl ::nohead_rs_db::__private::sqlx::Executor<'_, Database = ::nohead_rs_db::__private::sqlx::Sqlite>,
+            ) -> Result<Self::Record<'a>, ::nohead_rs_db::Error> {
+                use ::nohead_rs_db::ResultExt as _;
+
+                ::nohead_rs_db::Validate::validate(&record)?;
+
+                let record = ::nohead_rs_db::__private::sqlx::query_as!(
+                    #name,
+                    #update_sql,
+                    #(record.#writable_idents,)*
+                    id
+                    #owner_arg
+                )
+                .fetch_optional(executor)
+                .await
+                .map_constraint_err()?
+                .ok_or(::nohead_rs_db::Error::NoRecordFound)?;
+
+                Ok(record)
+            }
+
+            async fn delete<'a>(
+                id: Self::Id,
+                #owner_param
+                executor: impl ::nohead_rs_db::__private::sqlx::Executor<'_, Database = ::nohead_rs_db::__private::sqlx::Sqlite>,
+            ) -> Result<Self::Record<'a>, ::nohead_rs_db::Error> {
+                let record = ::nohead_rs_db::__private::sqlx::query_as!(#name, #delete_sql, id #owner_arg)
+                    .fetch_optional(executor)
+                    .await?
+                    .ok_or(::nohead_rs_db::Error::NoRecordFound)?;
+
+                Ok(record)
+            }
+
+            async fn delete_batch(
+                ids: Vec<Self::Id>,
+                #owner_param
+                db_pool: &::nohead_rs_db::DbPool,
+            ) -> Result<Vec<Self::Record<'_>>, ::nohead_rs_db::Error> {
+                let mut tx = ::nohead_rs_db::transaction(db_pool).await?;
+
+                let mut results: Vec<Self::Record<'_>> = vec![];
+
+                for id in ids {
+                    let result = Self::delete(id, #owner_value_arg &mut *tx).await?;
+                    results.push(result);
+                }
+
+                tx.commit().await?;
+
+                Ok(results)
+            }
+        }
+    })
+}
+
+/// Expands `load_page`, which counts the matching records with a checked query and loads the
+/// page itself with a `QueryBuilder`, as the sort column can't be bound as a parameter.
+fn expand_load_page(
+    args: &EntityArgs,
+    name: &Ident,
+    id: &Ident,
+    select_plain: &str,
+    columns: &[Column],
+) -> TokenStream {
+    let table = args.table.value();
+
+    let sortable: Vec<String> = if args.sortable.is_empty() {
+        columns
+            .iter()
+            .map(|column| column.ident.to_string())
+            .collect()
+    } else {
+        args.sortable.iter().map(ToString::to_string).collect()
+    };
+    let default_sort = args.default_sort.as_ref().unwrap_or(id).to_string();
+    // Records with the same sort value keep their order across pages
+    let tie_breaker = format!(", {id} ");
+
+    // Numbered parameters let the filter be bound once and matched against every column
+    let filter_param = if args.owner.is_some() { 2 } else { 1 };
+    let filter_clause = (!args.filterable.is_empty()).then(|| {
+        let matches = args
+            .filterable
+            .iter()
+            .map(|column| format!(r"{column} like ?{filter_param} escape '\'"))
+            .collect::<Vec<_>>()
+            .join(" or ");
+        format!("(?{filter_param} is null or {matches})")
+    });
+    let conditions = args
+        .owner
+        .iter()
+        .map(|owner| format!("{owner} = ?1"))
+        .chain(filter_clause)
+        .collect::<Vec<_>>();
+    let count_sql = lit(if conditions.is_empty() {
+        format!("select count(*) from {table}")
+    } else {
+        format!(
+            "select count(*) from {table} where {}",
+            conditions.join(" and ")
+        )
+    });
+
+    let owner_param = args.owner.as_ref().map(|_| quote!(owner_id: i64,));
+    let owner_count_arg = args.owner.as_ref().map(|_| quote!(, owner_id));
+    let owner_condition = args.owner.as_ref().map(|owner| {
+        let clause = format!(" and {owner} = ");
+        quote! {
+            query.push(#clause).push_bind(owner_id);
+        }
+    });
+
+    let (filter, filter_count_arg, filter_condition) = if args.filterable.is_empty() {
+        (None, None, None)
+    } else {
+        let first = format!(r" and ({} like ", args.filterable[0]);
+        let rest = args.filterable[1..]
+            .iter()
+            .map(|column| format!(r" or {column} like "));
+        (
+            Some(quote!(let filter = params.filter_pattern();)),
+            Some(quote!(, filter)),
+            Some(quote! {
+                if let Some(filter) = filter {
+                    query.push(#first).push_bind(filter.clone()).push(r" escape '\'");
+                    #(query.push(#rest).push_bind(filter.clone()).push(r" escape '\'");)*
+                    query.push(")");
+                }
+            }),
+        )
+    };
+
+    let select_sql = format!("select {select_plain} from {table} where 1 = 1");
+
+    quote! {
+        async fn load_page<'a>(
+            params: &::nohead_rs_db::pagination::ListParams,
+            #owner_param
+            db_pool: &::nohead_rs_db::DbPool,
+        ) -> Result<::nohead_rs_db::pagination::Page<Self::Record<'a>>, ::nohead_rs_db::Error> {
+            #filter
+
+            let total = ::nohead_rs_db::__private::sqlx::query_scalar!(
+                #count_sql
+                #owner_count_arg
+                #filter_count_arg
+            )
+            .fetch_one(db_pool)
+            .await?;
+
+            let mut query = ::nohead_rs_db::__private::sqlx::QueryBuilder::<
+                ::nohead_rs_db::__private::sqlx::Sqlite,
+            >::new(#select_sql);
+            #owner_condition
+            #filter_condition
+            query
+                .push(" order by ")
+                .push(params.sort_column(&[#(#sortable),*], #default_sort))
+                .push(" ")
+                .push(params.order().as_sql())
+                .push(#tie_breaker)
+                .push(params.order().as_sql())
+                .push(" limit ")
+                .push_bind(params.limit())
+                .push(" offset ")
+                .push_bind(params.offset());
+
+            let records = query.build_query_as::<#name>().fetch_all(db_pool).await?;
+
+            Ok(::nohead_rs_db::pagination::Page::new(records, total, params))
+        }
+    }
+}
+
+fn parse_args(input: &DeriveInput) -> syn::Result<EntityArgs> {
+    let mut table = None;
+    let mut changeset = None;
+    let mut owner = None;
+    let mut sortable = vec![];
+    let mut filterable = vec![];
+    let mut default_sort = None;
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("entity"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                table = Some(meta.value()?.parse::<LitStr>()?);
+            } else if meta.path.is_ident("changeset") {
+                changeset = Some(meta.value()?.parse::<Path>()?);
+            } else if meta.path.is_ident("owner") {
+                owner = Some(meta.value()?.parse::<Ident>()?);
+            } else if meta.path.is_ident("default_sort") {
+                default_sort = Some(meta.value()?.parse::<Ident>()?);
+            } else if meta.path.is_ident("sortable") {
+                meta.parse_nested_meta(|column| {
+                    sortable.push(column.path.require_ident()?.clone());
+                    Ok(())
+                })?;
+            } else if meta.path.is_ident("filterable") {
+                meta.parse_nested_meta(|column| {
+                    filterable.push(column.path.require_ident()?.clone());
+                    Ok(())
+                })?;
+            } else {
+                return Err(meta.error("unknown entity attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(EntityArgs {
+        table: table.ok_or_else(|| {
+            syn::Error::new_spanned(&input.ident, r#"missing `#[entity(table = "...")]`"#)
+        })?,
+        changeset: changeset.ok_or_else(|| {
+            syn::Error::new_spanned(&input.ident, "missing `#[entity(changeset = ...)]`")
+        })?,
+        owner,
+        sortable,
+        filterable,
+        default_sort,
+    })
+}
+
+fn parse_columns(input: &DeriveInput) -> syn::Result<Vec<Column>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Entity can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Entity can only be derived for structs with named fields",
+        ));
+    };
+
+    fields
+        .named
+        .iter()
+        .map(|field| {
+            let mut column = Column {
+                ident: field.ident.clone().expect("named fields have an ident"),
+                ty: field.ty.clone(),
+                id: false,
+                skip: false,
+            };
+
+            for attr in field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("entity"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("id") {
+                        column.id = true;
+                    } else if meta.path.is_ident("skip") {
+                        column.skip = true;
+                    } else {
+                        return Err(meta.error("unknown entity field attribute"));
+                    }
+                    Ok(())
+                })?;
+            }
+
+            Ok(column)
+        })
+        .collect()
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+fn lit(sql: String) -> LitStr {
+    LitStr::new(&sql, proc_macro2::Span::call_site())
+}
@@ -0,0 +1,100 @@
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
+
+mod entity;
+mod schema;
+
+/// ------------------------------------------------------------------------
+/// # Derive the CRUD queries of an entity from its record struct
+/// ------------------------------------------------------------------------
+///
+/// Generates an implementation of `nohead_rs_db::entities::Entity`, or of
+/// `OwnedEntity` when an `owner` column is given, with the same compile time
+/// checked `query_as!` queries you would otherwise write by hand.
+///
+/// Changesets are validated before they are written and unique constraint
+/// violations are mapped with `ResultExt::map_constraint_err`.
+///
+/// # Example
+///
+/// ```rust
+/// #[derive(Serialize, Deserialize, FromRow, Entity)]
+/// #[entity(
+///     table = "todos",
+///     changeset = TodoChangeset,
+///     owner = user_id,
+///     sortable(id, description),
+///     filterable(description)
+/// )]
+/// pub struct Todo {
+///     pub id: i64,
+///     pub description: String,
+///     pub user_id: i64,
+/// }
+/// ```
+///
+/// ## Struct attributes
+///
+/// - `table = "..."`: the table the records are stored in (required).
+/// - `changeset = Type`: the changeset used to create and update records (required).
+/// - `owner = column`: scopes every query to the user id in this column and implements
+///   `OwnedEntity` instead of `Entity`.
+/// - `sortable(columns...)`: the columns `load_page` may sort by, defaults to all of them.
+/// - `filterable(columns...)`: the text columns the `load_page` filter is matched against.
+/// - `default_sort = column`: the column `load_page` sorts by by default, defaults to the id.
+///
+/// ## Field attributes
+///
+/// - `#[entity(id)]`: marks the primary key, defaults to the field named `id`.
+/// - `#[entity(skip)]`: a column the database fills in, e.g. `created_at`, that is never
+///   written from the changeset.
+///
+/// Every other field is written from the changeset field with the same name.
+/// ------------------------------------------------------------------------
+#[proc_macro_derive(Entity, attributes(entity))]
+pub fn derive_entity(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    entity::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// ------------------------------------------------------------------------
+/// # Derive the JSON schema of a struct, e.g. for the OpenAPI document
+/// ------------------------------------------------------------------------
+///
+/// Generates an implementation of `nohead_rs_db::schema::Schema` from the fields of the
+/// struct as serde sees them, so that the schema can't drift from what is actually sent.
+///
+/// # Example
+///
+/// ```rust
+/// /// A changeset for creating or updating a todo.
+/// #[derive(Deserialize, Validate, Schema)]
+/// pub struct TodoChangeset {
+///     /// What to do.
+///     #[validate(length(min = 1, message = "Description must be at least 1 character long"))]
+///     pub description: String,
+/// }
+/// ```
+///
+/// - Doc comments become the descriptions of the schema and its properties.
+/// - `Option` fields are nullable and not required, unless `#[validate(required)]`.
+/// - `#[serde(skip)]`, `rename` and `flatten` are followed.
+/// - `#[validate(...)]` checks become constraints: `length` becomes `minLength` and
+///   `maxLength`, or `minItems` and `maxItems` for `Vec`s, `range` becomes `minimum` and
+///   `maximum`, `email` and `url` become a `format` and `must_match` becomes `x-must-match`.
+///   Other checks can't be expressed and are left out.
+///
+/// Fields of other types than strings, numbers, booleans, `Option` and `Vec` refer to the
+/// schema of their type, which must implement `Schema` as well.
+/// ------------------------------------------------------------------------
+#[proc_macro_derive(Schema)]
+pub fn derive_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    schema::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
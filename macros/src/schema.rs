@@ -0,0 +1,348 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Expr, ExprLit, ExprUnary, Fields, GenericArgument, Lit, LitStr,
+    PathArguments, Type, meta::ParseNestedMeta, parenthesized,
+};
+
+/// A field of the struct as it is (de)serialized.
+struct Property {
+    name: String,
+    ty: Type,
+    description: Option<String>,
+    /// The fields of a `#[serde(flatten)]` field are part of the struct itself.
+    flatten: bool,
+    /// Whether `#[validate(required)]` makes an `Option` field required anyway.
+    required: bool,
+    /// The JSON schema keywords of the `#[validate(...)]` checks of the field.
+    constraints: Vec<(&'static str, TokenStream)>,
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Schema can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Schema can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut properties = vec![];
+    for field in &fields.named {
+        if let Some(property) = parse_property(field)? {
+            properties.push(property);
+        }
+    }
+
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // Only the summary, the rest of the doc comment of a struct is usually about the code.
+    let description = match doc(&input.attrs).first() {
+        Some(description) => quote!(schema["description"] = #description.into();),
+        None => quote!(),
+    };
+
+    let (flattened, properties): (Vec<Property>, Vec<Property>) = properties
+        .into_iter()
+        .partition(|property| property.flatten);
+    let flattened_types: Vec<&Type> = flattened.iter().map(|property| &property.ty).collect();
+    let flattened = flattened.iter().map(|property| {
+        let ty = &property.ty;
+        quote!(::nohead_rs_db::schema::reference::<#ty>())
+    });
+    let required = properties
+        .iter()
+        .filter(|property| property.required || inner_type(&property.ty, "Option").is_none())
+        .map(|property| &property.name);
+    let referenced = flattened_types.into_iter().chain(
+        properties
+            .iter()
+            .filter_map(|property| referenced_type(&property.ty)),
+    );
+    let inserts = properties.iter().map(|property| {
+        let name = &property.name;
+        let schema = type_schema(&property.ty);
+        let constraints = property.constraints.iter().map(|(keyword, value)| {
+            quote!(property[#keyword] = ::nohead_rs_db::__private::serde_json::json!(#value);)
+        });
+        let description = property
+            .description
+            .as_ref()
+            .map(|description| quote!(property["description"] = #description.into();));
+
+        quote! {
+            let mut property = #schema;
+            #(#constraints)*
+            #description
+            properties.insert(#name.to_string(), property);
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::nohead_rs_db::schema::Schema for #name #ty_generics #where_clause {
+            fn name() -> &'static str {
+                #name_str
+            }
+
+            fn schema() -> ::nohead_rs_db::__private::serde_json::Value {
+                let mut properties = ::nohead_rs_db::__private::serde_json::Map::new();
+                #(#inserts)*
+
+                let mut schema = ::nohead_rs_db::__private::serde_json::json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": [#(#required),*],
+                });
+                #description
+
+                let flattened: Vec<::nohead_rs_db::__private::serde_json::Value> =
+                    vec![#(#flattened),*];
+                if flattened.is_empty() {
+                    schema
+                } else {
+                    let mut all_of = flattened;
+                    all_of.push(schema);
+                    ::nohead_rs_db::__private::serde_json::json!({ "allOf": all_of })
+                }
+            }
+
+            fn register(
+                schemas: &mut ::nohead_rs_db::__private::serde_json::Map<
+                    String,
+                    ::nohead_rs_db::__private::serde_json::Value,
+                >,
+            ) {
+                schemas.insert(Self::name().to_string(), Self::schema());
+                #(<#referenced as ::nohead_rs_db::schema::Schema>::register(schemas);)*
+            }
+        }
+    })
+}
+
+/// Reads a field, returning `None` for fields serde skips.
+fn parse_property(field: &syn::Field) -> syn::Result<Option<Property>> {
+    let mut property = Property {
+        name: field
+            .ident
+            .as_ref()
+            .expect("named fields have an ident")
+            .to_string(),
+        ty: field.ty.clone(),
+        description: Some(doc(&field.attrs).join("\n\n")).filter(|doc| !doc.is_empty()),
+        flatten: false,
+        required: false,
+        constraints: vec![],
+    };
+    let is_vec = inner_type(&field.ty, "Vec").is_some();
+    let mut skip = false;
+
+    for attr in &field.attrs {
+        if attr.path().is_ident("serde") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip")
+                    || meta.path.is_ident("skip_serializing")
+                    || meta.path.is_ident("skip_deserializing")
+                {
+                    skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    property.flatten = true;
+                } else if meta.path.is_ident("rename") {
+                    property.name = meta.value()?.parse::<LitStr>()?.value();
+                } else {
+                    skip_meta(&meta)?;
+                }
+                Ok(())
+            })?;
+        } else if attr.path().is_ident("validate") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("length") {
+                    let (min, max) = if is_vec {
+                        ("minItems", "maxItems")
+                    } else {
+                        ("minLength", "maxLength")
+                    };
+                    meta.parse_nested_meta(|arg| {
+                        if arg.path.is_ident("min") {
+                            property.constraints.extend(number(&arg)?.map(|n| (min, n)));
+                        } else if arg.path.is_ident("max") {
+                            property.constraints.extend(number(&arg)?.map(|n| (max, n)));
+                        } else if arg.path.is_ident("equal") {
+                            if let Some(equal) = number(&arg)? {
+                                property.constraints.push((min, equal.clone()));
+                                property.constraints.push((max, equal));
+                            }
+                        } else {
+                            skip_meta(&arg)?;
+                        }
+                        Ok(())
+                    })?;
+                } else if meta.path.is_ident("range") {
+                    meta.parse_nested_meta(|arg| {
+                        if arg.path.is_ident("min") {
+                            property
+                                .constraints
+                                .extend(number(&arg)?.map(|n| ("minimum", n)));
+                        } else if arg.path.is_ident("max") {
+                            property
+                                .constraints
+                                .extend(number(&arg)?.map(|n| ("maximum", n)));
+                        } else {
+                            skip_meta(&arg)?;
+                        }
+                        Ok(())
+                    })?;
+                } else if meta.path.is_ident("email") {
+                    property.constraints.push(("format", quote!("email")));
+                    skip_meta(&meta)?;
+                } else if meta.path.is_ident("url") {
+                    property.constraints.push(("format", quote!("uri")));
+                    skip_meta(&meta)?;
+                } else if meta.path.is_ident("must_match") {
+                    meta.parse_nested_meta(|arg| {
+                        if arg.path.is_ident("other") {
+                            let other = arg.value()?.parse::<LitStr>()?.value();
+                            property.constraints.push(("x-must-match", quote!(#other)));
+                        } else {
+                            skip_meta(&arg)?;
+                        }
+                        Ok(())
+                    })?;
+                } else if meta.path.is_ident("required") {
+                    property.required = true;
+                    skip_meta(&meta)?;
+                } else {
+                    // Checks that JSON schema can't express, e.g. custom functions.
+                    skip_meta(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+    }
+
+    Ok((!skip).then_some(property))
+}
+
+/// The schema of a field type, other types than the primitive ones must implement `Schema`.
+fn type_schema(ty: &Type) -> TokenStream {
+    if let Some(inner) = inner_type(ty, "Option") {
+        let inner = type_schema(inner);
+        return quote!(::nohead_rs_db::schema::nullable(#inner));
+    }
+    if let Some(inner) = inner_type(ty, "Vec") {
+        let inner = type_schema(inner);
+        return quote!(::nohead_rs_db::__private::serde_json::json!({
+            "type": "array",
+            "items": #inner,
+        }));
+    }
+
+    match primitive(ty) {
+        Some(schema) => quote!(::nohead_rs_db::__private::serde_json::json!(#schema)),
+        None => quote!(::nohead_rs_db::schema::reference::<#ty>()),
+    }
+}
+
+/// The schema of strings, numbers and booleans.
+fn primitive(ty: &Type) -> Option<TokenStream> {
+    let name = match ty {
+        Type::Path(path) => path.path.segments.last()?.ident.to_string(),
+        Type::Reference(reference) => match &*reference.elem {
+            Type::Path(path) if path.path.is_ident("str") => "String".to_string(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    match name.as_str() {
+        "String" => Some(quote!({ "type": "string" })),
+        "bool" => Some(quote!({ "type": "boolean" })),
+        "i64" | "u64" | "isize" | "usize" => Some(quote!({ "type": "integer", "format": "int64" })),
+        "i32" | "u32" | "i16" | "u16" | "i8" | "u8" => {
+            Some(quote!({ "type": "integer", "format": "int32" }))
+        }
+        "f64" | "f32" => Some(quote!({ "type": "number" })),
+        _ => None,
+    }
+}
+
+/// The type a field refers to the schema of, if it isn't a primitive one.
+fn referenced_type(ty: &Type) -> Option<&Type> {
+    match inner_type(ty, "Option").or_else(|| inner_type(ty, "Vec")) {
+        Some(inner) => referenced_type(inner),
+        None if primitive(ty).is_some() => None,
+        None => Some(ty),
+    }
+}
+
+/// The type wrapped in `wrapper`, e.g. the `T` of `Option<T>`.
+fn inner_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+/// The paragraphs of the doc comment of an item.
+fn doc(attrs: &[Attribute]) -> Vec<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta.require_name_value().ok()?.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(line),
+                ..
+            }) => Some(line.value().trim().to_string()),
+            _ => None,
+        })
+        .collect();
+
+    lines
+        .split(String::is_empty)
+        .map(|paragraph| paragraph.join(" "))
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect()
+}
+
+/// A number literal argument, e.g. the `1` of `length(min = 1)`.
+///
+/// Other arguments, e.g. constants, can't be read by a macro and are left out of the schema.
+fn number(meta: &ParseNestedMeta) -> syn::Result<Option<TokenStream>> {
+    match meta.value()?.parse::<Expr>()? {
+        expr @ (Expr::Lit(ExprLit {
+            lit: Lit::Int(_) | Lit::Float(_),
+            ..
+        })
+        | Expr::Unary(ExprUnary { .. })) => Ok(Some(quote!(#expr))),
+        _ => Ok(None),
+    }
+}
+
+/// Skips the arguments of an attribute this derive doesn't care about.
+fn skip_meta(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let args;
+        parenthesized!(args in meta.input);
+        args.parse::<TokenStream>()?;
+    }
+
+    Ok(())
+}
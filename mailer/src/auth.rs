@@ -1,34 +1,72 @@
-use nohead_rs_config::Config;
+use minijinja::{Value, context};
 
-use crate::{EmailClient, EmailPayload};
+use crate::{EmailClient, EmailPayload, EmailTemplates, Error, Preview, RenderedEmail};
 
 pub struct AuthMailer;
 
 impl AuthMailer {
     pub fn send_confirmation(
         email_client: &EmailClient,
-        config: &Config,
+        templates: &EmailTemplates,
         email_recipient: &str,
         register_token: &str,
-    ) -> EmailPayload {
-        let subject = "Please confirm your registration".to_string();
-
-        let text = format!(
-            "Welcome to {}!\nEnter the code to confirm your registration: {}",
-            config.app.name, register_token
-        );
-        let html = format!(
-            "Welcome to {}!<br />\
-        Enter the code to confirm your registration: {}",
-            config.app.name, register_token
-        );
-
-        EmailPayload::new(
-            email_client.sender.clone(),
-            vec![email_recipient.to_owned()],
-            subject,
-            html,
-            text,
-        )
+    ) -> Result<EmailPayload, Error> {
+        let email = templates.render(
+            "auth/confirmation",
+            Self::confirmation_context(register_token),
+        )?;
+
+        Self::payload(email_client, email_recipient, email)
+    }
+
+    pub fn send_password_reset(
+        email_client: &EmailClient,
+        templates: &EmailTemplates,
+        email_recipient: &str,
+        reset_token: &str,
+    ) -> Result<EmailPayload, Error> {
+        let email = templates.render(
+            "auth/password_reset",
+            Self::password_reset_context(templates, reset_token),
+        )?;
+
+        Self::payload(email_client, email_recipient, email)
+    }
+
+    /// The emails of the mailer with sample tokens, see [`crate::previews`].
+    pub fn previews(templates: &EmailTemplates) -> Vec<Preview> {
+        vec![
+            Preview {
+                name: "auth/confirmation",
+                context: Self::confirmation_context("123456"),
+            },
+            Preview {
+                name: "auth/password_reset",
+                context: Self::password_reset_context(templates, "sample-reset-token"),
+            },
+        ]
+    }
+
+    fn confirmation_context(register_token: &str) -> Value {
+        context! { register_token => register_token }
+    }
+
+    fn password_reset_context(templates: &EmailTemplates, reset_token: &str) -> Value {
+        context! { reset_url => format!("{}/auth/password/reset?token={}", templates.host(), reset_token) }
+    }
+
+    fn payload(
+        email_client: &EmailClient,
+        email_recipient: &str,
+        email: RenderedEmail,
+    ) -> Result<EmailPayload, Error> {
+        EmailPayload::builder()
+            .from(&email_client.sender)
+            .to(email_recipient)
+            .subject(&email.subject)
+            .html(&email.html)
+            .text(&email.text)
+            .tag("category", "auth")
+            .build()
     }
 }
@@ -0,0 +1,236 @@
+//! Prepares the HTML of emails for mail clients, which mostly ignore stylesheets, and derives
+//! the text of emails from it.
+
+use cssparser::{
+    AtRuleParser, AtRuleType, CowRcStr, ParseError, Parser, ParserInput, QualifiedRuleParser,
+    RuleListParser, SourceLocation,
+};
+use kuchikiki::{NodeRef, Selectors, iter::NodeIterator as _, traits::TendrilSink as _};
+
+use crate::Error;
+
+/// Moves the rules of `<style>` elements into the `style` attributes of the elements they select.
+///
+/// Rules with pseudo-classes or pseudo-elements, e.g. `a:hover`, and at-rules such as `@media`
+/// stay in the stylesheet, for the mail clients that read it. Styles that are already inline
+/// take precedence.
+pub fn inline_css(html: &str) -> String {
+    let document = kuchikiki::parse_html().one(html);
+    let styles: Vec<NodeRef> = document
+        .select("style")
+        .map(|styles| styles.map(|style| style.as_node().clone()).collect())
+        .unwrap_or_default();
+
+    let mut rules = vec![];
+    let mut kept = vec![];
+    for style in &styles {
+        for rule in parse_stylesheet(&style.text_contents()) {
+            match rule {
+                Rule::Inline {
+                    selectors,
+                    declarations,
+                } => match Selectors::compile(&selectors) {
+                    Ok(selectors) => rules.extend(
+                        selectors
+                            .0
+                            .into_iter()
+                            .map(|selector| (selector, declarations.clone())),
+                    ),
+                    Err(()) => kept.push(format!("{selectors} {{ {declarations} }}")),
+                },
+                Rule::Keep(css) => kept.push(css),
+            }
+        }
+    }
+    // Higher specificity wins, and later rules win among the same specificity.
+    rules.sort_by_key(|(selector, _)| selector.specificity());
+
+    for element in document.descendants().elements() {
+        let declarations: Vec<&str> = rules
+            .iter()
+            .filter(|(selector, _)| selector.matches(&element))
+            .map(|(_, declarations)| declarations.as_str())
+            .collect();
+        if declarations.is_empty() {
+            continue;
+        }
+
+        let mut attributes = element.attributes.borrow_mut();
+        let style = match attributes.get("style") {
+            Some(inline) => format!("{}; {inline}", declarations.join("; ")),
+            None => declarations.join("; "),
+        };
+        attributes.insert("style", style);
+    }
+
+    let mut styles = styles.into_iter();
+    if let Some(style) = styles.next().filter(|_| !kept.is_empty()) {
+        style.children().for_each(|child| child.detach());
+        style.append(NodeRef::new_text(kept.join("\n")));
+    }
+    styles.for_each(|style| style.detach());
+
+    document.to_string()
+}
+
+/// Turns HTML into text, for the mail clients that don't show HTML.
+///
+/// Links are listed as footnotes, e.g. `Follow [this link][1].` and `[1]: https://...`.
+pub fn to_text(html: &str) -> Result<String, Error> {
+    let text = html2text::config::plain()
+        .no_link_wrapping()
+        .string_from_read(html.as_bytes(), TEXT_WIDTH)?;
+
+    Ok(text.trim().to_string())
+}
+
+/// Decodes the entities of HTML text, e.g. `&amp;` to `&`.
+pub fn decode(text: &str) -> String {
+    kuchikiki::parse_html().one(text).text_contents()
+}
+
+/// The width text emails are wrapped at, as lines should be at most 78 characters long.
+const TEXT_WIDTH: usize = 78;
+
+/// A rule of a stylesheet.
+enum Rule {
+    /// A rule that can be inlined, e.g. `p.note { color: red }`.
+    Inline {
+        selectors: String,
+        declarations: String,
+    },
+    /// A rule that stays in the stylesheet, as written.
+    Keep(String),
+}
+
+/// Splits a stylesheet into its rules, keeping the ones that can't be parsed as written.
+fn parse_stylesheet(css: &str) -> Vec<Rule> {
+    let mut input = ParserInput::new(css);
+    let mut parser = Parser::new(&mut input);
+
+    RuleListParser::new_for_stylesheet(&mut parser, Stylesheet)
+        .map(|rule| rule.unwrap_or_else(|(_, css)| Rule::Keep(css.trim().to_string())))
+        .collect()
+}
+
+struct Stylesheet;
+
+impl<'i> QualifiedRuleParser<'i> for Stylesheet {
+    type Prelude = String;
+    type QualifiedRule = Rule;
+    type Error = ();
+
+    fn parse_prelude<'t>(
+        &mut self,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<String, ParseError<'i, ()>> {
+        Ok(rest(input))
+    }
+
+    fn parse_block<'t>(
+        &mut self,
+        selectors: String,
+        _location: SourceLocation,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Rule, ParseError<'i, ()>> {
+        let declarations = rest(input).trim_end_matches(';').trim_end().to_string();
+
+        // Elements are never hovered or focused, and pseudo-elements have no attributes.
+        Ok(if selectors.contains(':') {
+            Rule::Keep(format!("{selectors} {{ {declarations} }}"))
+        } else {
+            Rule::Inline {
+                selectors,
+                declarations,
+            }
+        })
+    }
+}
+
+impl<'i> AtRuleParser<'i> for Stylesheet {
+    type PreludeNoBlock = String;
+    type PreludeBlock = String;
+    type AtRule = Rule;
+    type Error = ();
+
+    fn parse_prelude<'t>(
+        &mut self,
+        name: CowRcStr<'i>,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<AtRuleType<String, String>, ParseError<'i, ()>> {
+        Ok(AtRuleType::WithBlock(format!("@{name} {}", rest(input))))
+    }
+
+    fn parse_block<'t>(
+        &mut self,
+        prelude: String,
+        _location: SourceLocation,
+        input: &mut Parser<'i, 't>,
+    ) -> Result<Rule, ParseError<'i, ()>> {
+        Ok(Rule::Keep(format!("{prelude} {{ {} }}", rest(input))))
+    }
+}
+
+/// The rest of the input as written.
+fn rest(input: &mut Parser<'_, '_>) -> String {
+    let start = input.position();
+    while input.next().is_ok() {}
+
+    input.slice_from(start).trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_css_moves_simple_rules_into_style_attributes() {
+        let html = r#"<html><head><style>
+            /* the brand */
+            p { color: #333; margin: 0 }
+            .note, #footer { font-family: Helvetica }
+            p.note { color: red; }
+            a:hover { color: blue; }
+            @media (max-width: 600px) { p { margin: 2px } }
+        </style></head><body><p class="note" style="margin: 4px">Hi</p><p id="footer">Bye</p></body></html>"#;
+
+        let inlined = inline_css(html);
+
+        assert!(
+            inlined.contains(
+                r#"<p class="note" style="color: #333; margin: 0; font-family: Helvetica; color: red; margin: 4px">Hi</p>"#
+            ),
+            "{inlined}"
+        );
+        assert!(
+            inlined.contains(
+                r#"<p id="footer" style="color: #333; margin: 0; font-family: Helvetica">Bye</p>"#
+            ),
+            "{inlined}"
+        );
+        assert!(
+            inlined.contains(
+                "<style>a:hover { color: blue }\n@media (max-width: 600px) { p { margin: 2px } }</style></head>"
+            ),
+            "rules that can't be inlined should stay: {inlined}"
+        );
+    }
+
+    #[test]
+    fn to_text_keeps_paragraphs_and_links() {
+        let html = r#"<html><head><title>Reset</title><style>p { color: red }</style></head>
+            <body>
+              <p>Hello &amp; welcome</p>
+              <p>We received   a request.<br>Follow
+                 <a href="https://example.com/reset?a=1&amp;b=2">this link</a>.</p>
+            </body></html>"#;
+
+        let text = to_text(html).unwrap();
+
+        assert_eq!(
+            text,
+            "Hello & welcome\n\nWe received a request.\nFollow [this link][1].\n\n\
+            [1]: https://example.com/reset?a=1&b=2"
+        );
+    }
+}
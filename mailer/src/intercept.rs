@@ -0,0 +1,108 @@
+use nohead_rs_config::MailerConfig;
+use tracing::info;
+
+use crate::{EmailPayload, message};
+
+/// Keeps emails from reaching real users, e.g. in staging, as configured in `[mailer]`.
+#[derive(Clone, Debug, Default)]
+pub struct Intercept {
+    /// The address that gets the emails of everyone else.
+    to: Option<String>,
+    /// The domains whose recipients still get their emails.
+    allowed_domains: Vec<String>,
+    subject_prefix: Option<String>,
+}
+
+impl Intercept {
+    pub fn new(config: &MailerConfig) -> Self {
+        Self {
+            to: config.intercept_to.clone(),
+            allowed_domains: config.allowed_domains.clone(),
+            subject_prefix: config.subject_prefix.clone(),
+        }
+    }
+
+    /// Prefixes the subject of the email and sends it to the `intercept_to` address instead of
+    /// the recipients that aren't allowed, logging who they were.
+    pub fn apply(&self, mut email: EmailPayload) -> EmailPayload {
+        if let Some(prefix) = &self.subject_prefix {
+            email.subject = format!("{prefix} {}", email.subject);
+        }
+
+        let Some(intercept_to) = &self.to else {
+            return email;
+        };
+        let mut intercepted = vec![];
+        for recipients in [&mut email.to, &mut email.cc, &mut email.bcc] {
+            let (allowed, denied): (Vec<String>, Vec<String>) = std::mem::take(recipients)
+                .into_iter()
+                .partition(|recipient| self.allows(recipient));
+            *recipients = allowed;
+            intercepted.extend(denied);
+        }
+
+        if !intercepted.is_empty() {
+            info!(
+                original_to = intercepted.join(", "),
+                intercept_to,
+                subject = email.subject,
+                "intercepted email"
+            );
+            if !email.to.contains(intercept_to) {
+                email.to.push(intercept_to.clone());
+            }
+        }
+
+        email
+    }
+
+    fn allows(&self, recipient: &str) -> bool {
+        let Ok(mailbox) = message::mailbox(recipient) else {
+            return false;
+        };
+
+        self.allowed_domains
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(mailbox.email.domain()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(to: &[&str]) -> EmailPayload {
+        to.iter()
+            .fold(EmailPayload::builder(), |builder, to| builder.to(to))
+            .from("dev@notebar.io")
+            .bcc("audit@example.com")
+            .subject("Hello")
+            .html("<p>Hi</p>")
+            .text("Hi")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn apply_sends_to_the_intercept_address_unless_the_domain_is_allowed() {
+        let intercept = Intercept {
+            to: Some("team@notebar.io".to_string()),
+            allowed_domains: vec!["notebar.io".to_string()],
+            subject_prefix: Some("[staging]".to_string()),
+        };
+
+        let intercepted = intercept.apply(email(&["jane@example.com", "joe@NOTEBAR.io"]));
+
+        assert_eq!(intercepted.to(), ["joe@NOTEBAR.io", "team@notebar.io"]);
+        assert!(intercepted.bcc().is_empty());
+        assert_eq!(intercepted.subject(), "[staging] Hello");
+    }
+
+    #[test]
+    fn apply_leaves_emails_alone_by_default() {
+        let email = Intercept::default().apply(email(&["jane@example.com"]));
+
+        assert_eq!(email.to(), ["jane@example.com"]);
+        assert_eq!(email.subject(), "Hello");
+    }
+}
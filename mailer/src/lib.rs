@@ -1,115 +1,128 @@
 pub mod auth;
+mod html;
+mod intercept;
+mod message;
+mod payload;
+pub mod templates;
+pub mod transport;
 
 use core::time;
+use std::sync::Arc;
 
-use nohead_rs_config::MailerConfig;
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use validator::{Validate, ValidationError};
+use intercept::Intercept;
+use nohead_rs_config::{MailerConfig, TransportConfig};
+use validator::Validate;
 
-#[derive(Serialize, Deserialize, Validate)]
-pub struct EmailPayload {
-    #[validate(email(message = "must be a valid email address"))]
-    from: String,
-    #[validate(custom(function = "validate_collection_of_emails"))]
-    to: Vec<String>,
-    #[validate(length(min = 1, message = "must be at least 1 character"))]
-    subject: String,
-    html: String,
-    text: String,
-}
+pub use payload::{Attachment, EmailPayload, EmailPayloadBuilder, Tag};
+pub use templates::{EmailTemplates, Preview, RenderedEmail};
+pub use transport::{
+    FileTransport, LogTransport, MemoryTransport, ResendTransport, SentEmail, SmtpTransport,
+    Transport,
+};
 
-fn validate_collection_of_emails(emails: &Vec<String>) -> Result<(), ValidationError> {
-    for email in emails {
-        if !validator::ValidateEmail::validate_email(&email) {
-            return Err(ValidationError::new("must be a valid email address"));
-        }
-    }
-    Ok(())
+/// Sends emails with the [`Transport`] selected in the [`MailerConfig`].
+#[derive(Clone, Debug)]
+pub struct EmailClient {
+    sender: String,
+    transport: Arc<dyn Transport>,
+    /// The transport again when it is a [`MemoryTransport`], so that its emails can be read.
+    memory: Option<MemoryTransport>,
+    intercept: Intercept,
 }
 
-impl EmailPayload {
-    pub fn new(
-        from: String,
-        #[cfg(debug_assertions)] mut to: Vec<String>,
-        // mutable in debug mode to allow changing
-        // the recipient
-        #[cfg(not(debug_assertions))] to: Vec<String>,
-        subject: String,
-        html: String,
-        text: String,
-    ) -> Self {
-        if cfg!(debug_assertions) {
-            // Change the recipient to a test email address if we are in debug mode
-            let test_recipient = "delivered@resend.dev".to_string();
-            to = vec![test_recipient];
+impl EmailClient {
+    /// Builds the client with the transport of the `[mailer.transport]` section.
+    ///
+    /// Fails when the transport is missing settings, e.g. the Resend API key.
+    pub fn new(config: &MailerConfig) -> Result<Self, Error> {
+        let timeout = time::Duration::from_millis(config.timeout);
+        let mut memory = None;
+
+        let transport: Arc<dyn Transport> = match &config.transport {
+            TransportConfig::Resend { base_url, api_key } => {
+                let api_key = resolve_api_key(api_key.as_deref(), |name| std::env::var(name).ok())?;
+                Arc::new(ResendTransport::new(base_url, &api_key, timeout)?)
+            }
+            TransportConfig::Smtp {
+                host,
+                port,
+                tls,
+                username,
+                password,
+            } => {
+                let credentials = username.clone().zip(password.clone());
+                Arc::new(SmtpTransport::new(host, *port, *tls, credentials, timeout)?)
+            }
+            TransportConfig::File { path } => Arc::new(FileTransport::new(path)),
+            TransportConfig::Log => Arc::new(LogTransport),
+            TransportConfig::Memory => {
+                let transport = MemoryTransport::default();
+                memory = Some(transport.clone());
+                Arc::new(transport)
+            }
         };
 
+        Ok(Self {
+            sender: config.sender.clone(),
+            transport,
+            memory,
+            intercept: Intercept::new(config),
+        })
+    }
+
+    /// Builds the client with a transport of its own, e.g. one that isn't in [`transport`].
+    pub fn with_transport(sender: &str, transport: impl Transport + 'static) -> Self {
         Self {
-            from,
-            to,
-            subject,
-            html,
-            text,
+            sender: sender.to_string(),
+            transport: Arc::new(transport),
+            memory: None,
+            intercept: Intercept::default(),
         }
     }
-}
 
-#[derive(Clone)]
-pub struct EmailClient {
-    http_client: Client,
-    base_url: String,
-    sender: String,
-    authorization_token: String,
-}
-
-// Manual implementation of Debug for EmailClient to redact the authorization token
-impl std::fmt::Debug for EmailClient {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("EmailClient")
-            .field("http_client", &self.http_client)
-            .field("base_url", &self.base_url)
-            .field("sender", &self.sender)
-            .field("authorization_token", &"[redacted")
-            .finish()
+    /// The transport the emails are kept in, when the `memory` transport is configured.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let emails = app_state.email_client.memory().unwrap().emails();
+    /// assert_eq!(emails[0].subject(), "Please confirm your registration");
+    /// ```
+    pub fn memory(&self) -> Option<&MemoryTransport> {
+        self.memory.as_ref()
     }
-}
 
-impl EmailClient {
-    pub fn new(config: &MailerConfig) -> Self {
-        let timeout = time::Duration::from_millis(config.timeout);
-        let http_client = Client::builder().timeout(timeout).build().unwrap();
-        let authorization_token =
-            std::env::var("RESEND_API_KEY").expect("RESEND_API_KEY must be set in .env");
-
-        Self {
-            http_client,
-            base_url: config.base_url.clone(),
-            sender: config.sender.clone(),
-            authorization_token,
-        }
+    /// The emails that were sent, newest first, when the transport keeps them, see
+    /// [`Transport::sent`].
+    pub async fn sent(&self) -> Result<Vec<SentEmail>, Error> {
+        self.transport.sent().await
     }
 
+    /// Sends an email, to the `intercept_to` address instead of its recipients when set.
     pub async fn send_email(&self, payload: EmailPayload) -> Result<(), Error> {
         payload.validate()?;
+        let payload = self.intercept.apply(payload);
 
-        let url = format!("{}/emails", self.base_url);
-
-        let res = self
-            .http_client
-            .post(url)
-            .header(
-                "Authorization",
-                format!("Bearer {}", self.authorization_token),
-            )
-            .json(&payload)
-            .send()
-            .await?;
+        self.transport.send(&payload).await
+    }
+}
 
-        res.error_for_status()?; // return an error if the response status is not 2xx
+/// The Resend API key, from the config or else the `RESEND_API_KEY` variable read with `env`.
+fn resolve_api_key(
+    configured: Option<&str>,
+    env: impl Fn(&str) -> Option<String>,
+) -> Result<String, Error> {
+    configured
+        .map(str::to_string)
+        .or_else(|| env("RESEND_API_KEY"))
+        .ok_or_else(|| {
+            Error::Config("RESEND_API_KEY must be set to send emails with Resend".into())
+        })
+}
 
-        Ok(())
-    }
+/// The previews of every mailer, add those of new mailers here.
+pub fn previews(templates: &EmailTemplates) -> Vec<Preview> {
+    auth::AuthMailer::previews(templates)
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -120,6 +133,27 @@ pub enum Error {
     // A reqwest error occurred
     #[error("reqwest error")]
     Request(#[from] reqwest::Error),
+    // The transport is missing settings
+    #[error("invalid mailer configuration: {0}")]
+    Config(String),
+    // The email could not be made into a message, e.g. for an invalid mailbox
+    #[error("invalid email message: {0}")]
+    Message(String),
+    // The message could not be built
+    #[error("could not build email message")]
+    Build(#[from] lettre::error::Error),
+    // The SMTP server could not be reached or refused the email
+    #[error("smtp error")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+    // Writing the email, to a file or a connection, failed
+    #[error("io error")]
+    Io(#[from] std::io::Error),
+    // An email template could not be rendered
+    #[error("could not render email template")]
+    Template(#[from] minijinja::Error),
+    // The text of an email could not be derived from its HTML
+    #[error("could not convert email html to text")]
+    Text(#[from] html2text::Error),
 }
 
 #[cfg(test)]
@@ -133,26 +167,6 @@ mod tests {
         },
     };
 
-    use nohead_rs_config::{Config, Environment, load_config};
-    use wiremock::matchers::{any, header, header_exists, method, path};
-    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
-
-    struct SendEmailBodyMatcher;
-
-    impl wiremock::Match for SendEmailBodyMatcher {
-        fn matches(&self, request: &Request) -> bool {
-            let result: Result<serde_json::Value, _> = serde_json::from_slice(&request.body);
-            if let Ok(body) = result {
-                body.get("from").is_some()
-                    && body.get("to").is_some()
-                    && body.get("subject").is_some()
-                    && body.get("html").is_some()
-                    && body.get("text").is_some()
-            } else {
-                false
-            }
-        }
-    }
     /// ------------------------------------------------------------------------
     /// Manual impl Dummy to allow dummy Vec for to field
     /// ------------------------------------------------------------------------
@@ -169,88 +183,64 @@ mod tests {
                 subject: Sentence(1..2).fake_with_rng(rng),
                 html: Paragraph(1..10).fake_with_rng(rng),
                 text: Paragraph(1..10).fake_with_rng(rng),
+                ..Default::default()
             }
         }
     }
 
-    fn get_test_email_client(mock_server: &MockServer) -> EmailClient {
-        let config: Config = load_config(&Environment::Test).unwrap();
-
-        let mailer_config = MailerConfig {
-            base_url: mock_server.uri().to_string(),
-            sender: config.mailer.sender.clone(),
-            timeout: config.mailer.timeout,
-        };
-        EmailClient::new(&mailer_config)
+    fn mailer_config(transport: TransportConfig) -> MailerConfig {
+        MailerConfig {
+            sender: "dev@notebar.io".to_string(),
+            timeout: 2000,
+            transport,
+            intercept_to: None,
+            allowed_domains: vec![],
+            subject_prefix: None,
+        }
     }
 
     #[tokio::test]
-    async fn send_email_succeeds_if_the_server_returns_200() {
-        let mock_server = MockServer::start().await;
-        let email_client = get_test_email_client(&mock_server);
-
-        Mock::given(any())
-            .respond_with(ResponseTemplate::new(200))
-            .expect(1)
-            .mount(&mock_server)
-            .await;
+    async fn send_email_keeps_emails_in_memory() {
+        let email_client = EmailClient::new(&mailer_config(TransportConfig::Memory)).unwrap();
 
         let payload: EmailPayload = Faker.fake();
-        let result = email_client.send_email(payload).await;
+        email_client.send_email(payload.clone()).await.unwrap();
 
-        assert!(result.is_ok());
-    }
-
-    #[tokio::test]
-    async fn send_email_sends_the_expected_request() {
-        let mock_server = MockServer::start().await;
-        let email_client = get_test_email_client(&mock_server);
-
-        Mock::given(header_exists("Authorization"))
-            .and(header("Content-Type", "application/json"))
-            .and(path("/emails"))
-            .and(method("POST"))
-            .and(SendEmailBodyMatcher)
-            .respond_with(ResponseTemplate::new(200))
-            .expect(1)
-            .mount(&mock_server)
-            .await;
+        let emails = email_client.memory().unwrap().emails();
+        assert_eq!(emails.len(), 1);
+        assert_eq!(emails[0].subject(), payload.subject());
 
-        let payload: EmailPayload = Faker.fake();
-        let _ = email_client.send_email(payload).await;
+        let sent = email_client.sent().await.unwrap();
+        assert_eq!(sent[0].id, "1");
+        assert_eq!(sent[0].email.subject(), payload.subject());
     }
 
     #[tokio::test]
-    async fn send_email_fails_if_the_server_returns_400() {
-        let mock_server = MockServer::start().await;
-        let email_client = get_test_email_client(&mock_server);
-
-        Mock::given(any())
-            .respond_with(ResponseTemplate::new(400))
-            .expect(1)
-            .mount(&mock_server)
-            .await;
+    async fn send_email_rejects_invalid_payloads() {
+        let email_client = EmailClient::new(&mailer_config(TransportConfig::Memory)).unwrap();
 
-        let payload: EmailPayload = Faker.fake();
+        let payload = EmailPayload {
+            to: vec!["not an email".to_string()],
+            ..Faker.fake()
+        };
         let result = email_client.send_email(payload).await;
 
-        assert!(result.is_err());
+        assert!(matches!(result, Err(Error::Validation(_))));
+        assert!(email_client.memory().unwrap().emails().is_empty());
     }
 
-    #[tokio::test]
-    async fn send_email_times_out_if_the_server_takes_too_long() {
-        let mock_server = MockServer::start().await;
-        let email_client = get_test_email_client(&mock_server);
-
-        Mock::given(any())
-            .respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(100)))
-            .expect(1)
-            .mount(&mock_server)
-            .await;
-
-        let payload: EmailPayload = Faker.fake();
-        let result = email_client.send_email(payload).await;
-
-        assert!(result.is_err());
+    #[test]
+    fn resend_needs_an_api_key() {
+        let env = |name: &str| (name == "RESEND_API_KEY").then(|| "re_env".to_string());
+
+        assert_eq!(
+            resolve_api_key(Some("re_config"), env).unwrap(),
+            "re_config"
+        );
+        assert_eq!(resolve_api_key(None, env).unwrap(), "re_env");
+        assert!(matches!(
+            resolve_api_key(None, |_| None),
+            Err(Error::Config(_))
+        ));
     }
 }
@@ -0,0 +1,126 @@
+//! Builds emails as RFC 5322 messages with lettre, for the transports that don't take JSON.
+
+use lettre::{
+    Message,
+    message::{
+        Attachment as AttachmentPart, Mailbox, MultiPart,
+        header::{ContentType, HeaderName, HeaderValue},
+    },
+};
+use rand::Rng as _;
+
+use crate::{EmailPayload, Error};
+
+/// Builds an email with its text and HTML as alternatives, which mail clients pick from,
+/// followed by its attachments.
+///
+/// The `bcc` recipients are only part of the envelope, they are left out of the headers.
+pub fn build(email: &EmailPayload) -> Result<Message, Error> {
+    let mut builder = Message::builder()
+        .from(mailbox(email.from())?)
+        .subject(email.subject())
+        .message_id(None);
+    for to in email.to() {
+        builder = builder.to(mailbox(to)?);
+    }
+    for cc in email.cc() {
+        builder = builder.cc(mailbox(cc)?);
+    }
+    for bcc in email.bcc() {
+        builder = builder.bcc(mailbox(bcc)?);
+    }
+    for reply_to in email.reply_to() {
+        builder = builder.reply_to(mailbox(reply_to)?);
+    }
+    for (name, value) in email.headers() {
+        let name = HeaderName::new_from_ascii(name.clone()).map_err(invalid)?;
+        builder = builder.raw_header(HeaderValue::new(name, value.clone()));
+    }
+
+    let alternative =
+        MultiPart::alternative_plain_html(email.text().to_string(), email.html().to_string());
+    if email.attachments().is_empty() {
+        return Ok(builder.multipart(alternative)?);
+    }
+
+    let mut mixed = MultiPart::mixed().multipart(alternative);
+    for attachment in email.attachments() {
+        let content_type = ContentType::parse(attachment.content_type()).map_err(invalid)?;
+        mixed = mixed.singlepart(
+            AttachmentPart::new(attachment.filename().to_string())
+                .body(attachment.content(), content_type),
+        );
+    }
+
+    Ok(builder.multipart(mixed)?)
+}
+
+/// A mailbox such as `dev@notebar.io` or `Dev <dev@notebar.io>`.
+pub fn mailbox(mailbox: &str) -> Result<Mailbox, Error> {
+    mailbox.parse().map_err(invalid)
+}
+
+/// A random id, for file names.
+pub fn random_id() -> String {
+    rand::rng()
+        .sample_iter(rand::distr::Alphanumeric)
+        .map(char::from)
+        .take(16)
+        .collect()
+}
+
+fn invalid(err: impl std::fmt::Display) -> Error {
+    Error::Message(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Attachment;
+
+    #[test]
+    fn build_adds_the_headers_and_attachments() {
+        let email = EmailPayload::builder()
+            .from("dev@notebar.io")
+            .to("jane@example.com")
+            .cc("joe@example.com")
+            .bcc("audit@example.com")
+            .reply_to("support@notebar.io")
+            .subject("Your invoice")
+            .html("<p>Attached</p>")
+            .text("Attached")
+            .header("List-Unsubscribe", "<https://example.com/unsubscribe>")
+            .attachment(Attachment::new("invoice.pdf", "application/pdf", b"%PDF"))
+            .build()
+            .unwrap();
+
+        let message = build(&email).unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+
+        for header in [
+            "Cc: joe@example.com\r\n",
+            "Reply-To: support@notebar.io\r\n",
+            "List-Unsubscribe: <https://example.com/unsubscribe>\r\n",
+            "Content-Type: multipart/mixed;",
+            "Content-Disposition: attachment; filename=\"invoice.pdf\"\r\n",
+            "Content-Type: application/pdf\r\n",
+        ] {
+            assert!(
+                formatted.contains(header),
+                "{header} is missing:\n{formatted}"
+            );
+        }
+        assert!(
+            !formatted.contains("audit@example.com"),
+            "bcc should be hidden"
+        );
+        assert!(
+            message
+                .envelope()
+                .to()
+                .iter()
+                .any(|address| address.to_string() == "audit@example.com"),
+            "bcc should still get the email"
+        );
+    }
+}
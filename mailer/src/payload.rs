@@ -0,0 +1,381 @@
+use std::collections::BTreeMap;
+
+use base64::{Engine as _, prelude::BASE64_STANDARD};
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+use crate::Error;
+
+/// An email, serialized as Resend expects it, see
+/// <https://resend.com/docs/api-reference/emails/send-email>.
+///
+/// Build it with [`EmailPayload::builder`].
+#[derive(Serialize, Deserialize, Validate, Clone, Debug, Default)]
+pub struct EmailPayload {
+    #[validate(email(message = "must be a valid email address"))]
+    pub(crate) from: String,
+    #[validate(
+        length(min = 1, message = "must have at least 1 recipient"),
+        custom(function = "validate_collection_of_emails")
+    )]
+    pub(crate) to: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[validate(custom(function = "validate_collection_of_emails"))]
+    pub(crate) cc: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[validate(custom(function = "validate_collection_of_emails"))]
+    pub(crate) bcc: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[validate(custom(function = "validate_collection_of_emails"))]
+    pub(crate) reply_to: Vec<String>,
+    #[validate(length(min = 1, message = "must be at least 1 character"))]
+    pub(crate) subject: String,
+    pub(crate) html: String,
+    pub(crate) text: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[validate(custom(function = "validate_headers"))]
+    pub(crate) headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[validate(nested)]
+    pub(crate) tags: Vec<Tag>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[validate(nested)]
+    pub(crate) attachments: Vec<Attachment>,
+}
+
+/// A tag the email service keeps with the email, e.g. to tell apart the kinds of emails in
+/// its statistics and webhooks.
+#[derive(Serialize, Deserialize, Validate, Clone, Debug, PartialEq)]
+pub struct Tag {
+    #[validate(
+        length(min = 1, max = 256, message = "must be 1 to 256 characters long"),
+        custom(function = "validate_tag")
+    )]
+    pub name: String,
+    #[validate(
+        length(min = 1, max = 256, message = "must be 1 to 256 characters long"),
+        custom(function = "validate_tag")
+    )]
+    pub value: String,
+}
+
+/// A file that is sent along with the email, e.g. an invoice.
+#[derive(Serialize, Deserialize, Validate, Clone, Debug, PartialEq)]
+pub struct Attachment {
+    #[validate(
+        length(min = 1, message = "must be at least 1 character"),
+        custom(function = "validate_filename")
+    )]
+    pub(crate) filename: String,
+    #[validate(custom(function = "validate_content_type"))]
+    pub(crate) content_type: String,
+    /// The content as base64.
+    pub(crate) content: String,
+}
+
+impl Attachment {
+    pub fn new(filename: &str, content_type: &str, content: impl AsRef<[u8]>) -> Self {
+        Self {
+            filename: filename.to_string(),
+            content_type: content_type.to_string(),
+            content: BASE64_STANDARD.encode(content),
+        }
+    }
+
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// The content of the file.
+    pub fn content(&self) -> Vec<u8> {
+        BASE64_STANDARD.decode(&self.content).unwrap_or_default()
+    }
+}
+
+impl EmailPayload {
+    /// Starts building an email.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let payload = EmailPayload::builder()
+    ///     .from(&email_client.sender)
+    ///     .to(&user.email)
+    ///     .subject("Your invoice")
+    ///     .html(&email.html)
+    ///     .text(&email.text)
+    ///     .header("List-Unsubscribe", "<https://example.com/unsubscribe>")
+    ///     .tag("category", "invoice")
+    ///     .attachment(Attachment::new("invoice.pdf", "application/pdf", pdf))
+    ///     .build()?;
+    /// ```
+    pub fn builder() -> EmailPayloadBuilder {
+        EmailPayloadBuilder::default()
+    }
+
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    pub fn to(&self) -> &[String] {
+        &self.to
+    }
+
+    pub fn cc(&self) -> &[String] {
+        &self.cc
+    }
+
+    pub fn bcc(&self) -> &[String] {
+        &self.bcc
+    }
+
+    pub fn reply_to(&self) -> &[String] {
+        &self.reply_to
+    }
+
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    pub fn html(&self) -> &str {
+        &self.html
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn headers(&self) -> &BTreeMap<String, String> {
+        &self.headers
+    }
+
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    pub fn attachments(&self) -> &[Attachment] {
+        &self.attachments
+    }
+
+    /// Everyone the email is sent to, including `cc` and `bcc`.
+    pub fn recipients(&self) -> impl Iterator<Item = &String> {
+        self.to.iter().chain(&self.cc).chain(&self.bcc)
+    }
+}
+
+/// Builds an [`EmailPayload`], which is validated by [`EmailPayloadBuilder::build`].
+#[derive(Clone, Debug, Default)]
+pub struct EmailPayloadBuilder {
+    payload: EmailPayload,
+}
+
+impl EmailPayloadBuilder {
+    pub fn from(mut self, from: &str) -> Self {
+        self.payload.from = from.to_string();
+        self
+    }
+
+    /// Adds a recipient, call it again for each other one.
+    pub fn to(mut self, to: &str) -> Self {
+        self.payload.to.push(to.to_string());
+        self
+    }
+
+    pub fn cc(mut self, cc: &str) -> Self {
+        self.payload.cc.push(cc.to_string());
+        self
+    }
+
+    pub fn bcc(mut self, bcc: &str) -> Self {
+        self.payload.bcc.push(bcc.to_string());
+        self
+    }
+
+    pub fn reply_to(mut self, reply_to: &str) -> Self {
+        self.payload.reply_to.push(reply_to.to_string());
+        self
+    }
+
+    pub fn subject(mut self, subject: &str) -> Self {
+        self.payload.subject = subject.to_string();
+        self
+    }
+
+    pub fn html(mut self, html: &str) -> Self {
+        self.payload.html = html.to_string();
+        self
+    }
+
+    pub fn text(mut self, text: &str) -> Self {
+        self.payload.text = text.to_string();
+        self
+    }
+
+    /// Sets a header of the email, e.g. `List-Unsubscribe`.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.payload
+            .headers
+            .insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn tag(mut self, name: &str, value: &str) -> Self {
+        self.payload.tags.push(Tag {
+            name: name.to_string(),
+            value: value.to_string(),
+        });
+        self
+    }
+
+    pub fn attachment(mut self, attachment: Attachment) -> Self {
+        self.payload.attachments.push(attachment);
+        self
+    }
+
+    /// Fails with [`Error::Validation`] when e.g. an address or header is invalid.
+    pub fn build(self) -> Result<EmailPayload, Error> {
+        self.payload.validate()?;
+
+        Ok(self.payload)
+    }
+}
+
+fn validate_collection_of_emails(emails: &Vec<String>) -> Result<(), ValidationError> {
+    for email in emails {
+        if !validator::ValidateEmail::validate_email(&email) {
+            return Err(ValidationError::new("must be a valid email address"));
+        }
+    }
+    Ok(())
+}
+
+/// Header names are printable ASCII without colons, and neither may break the line they are
+/// on, which would let them add headers of their own.
+fn validate_headers(headers: &BTreeMap<String, String>) -> Result<(), ValidationError> {
+    for (name, value) in headers {
+        let valid_name = !name.is_empty() && name.chars().all(|c| c.is_ascii_graphic() && c != ':');
+        if !valid_name {
+            return Err(ValidationError::new("must be a valid header name"));
+        }
+        if value.contains(['\r', '\n']) {
+            return Err(ValidationError::new("must not contain line breaks"));
+        }
+    }
+    Ok(())
+}
+
+/// Resend only accepts letters, numbers, underscores and dashes in tags.
+fn validate_tag(tag: &str) -> Result<(), ValidationError> {
+    if tag
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new(
+            "must only contain letters, numbers, underscores and dashes",
+        ))
+    }
+}
+
+fn validate_filename(filename: &str) -> Result<(), ValidationError> {
+    if filename.contains(['\r', '\n', '"', '/', '\\']) {
+        Err(ValidationError::new("must be a valid file name"))
+    } else {
+        Ok(())
+    }
+}
+
+/// A media type such as `application/pdf`.
+fn validate_content_type(content_type: &str) -> Result<(), ValidationError> {
+    let valid_part = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+    };
+
+    match content_type.split_once('/') {
+        Some((kind, subtype)) if valid_part(kind) && valid_part(subtype) => Ok(()),
+        _ => Err(ValidationError::new("must be a valid content type")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builder() -> EmailPayloadBuilder {
+        EmailPayload::builder()
+            .from("dev@notebar.io")
+            .to("jane@example.com")
+            .subject("Your invoice")
+            .html("<p>Attached</p>")
+            .text("Attached")
+    }
+
+    #[test]
+    fn build_validates_the_payload() {
+        let payload = builder()
+            .cc("joe@example.com")
+            .header("List-Unsubscribe", "<https://example.com/unsubscribe>")
+            .tag("category", "invoice")
+            .attachment(Attachment::new("invoice.pdf", "application/pdf", b"%PDF"))
+            .build()
+            .unwrap();
+
+        assert_eq!(payload.cc(), ["joe@example.com"]);
+        assert_eq!(payload.attachments()[0].content(), b"%PDF");
+
+        for (invalid, field) in [
+            (builder().bcc("not an email"), "bcc"),
+            (builder().reply_to("not an email"), "reply_to"),
+            (
+                builder().header("X-Note", "hi\r\nBcc: everyone@example.com"),
+                "headers",
+            ),
+            (builder().header("Bad Name", "hi"), "headers"),
+            (builder().tag("category", "in voice"), "tags"),
+            (
+                builder().attachment(Attachment::new("invoice.pdf", "pdf", b"%PDF")),
+                "attachments",
+            ),
+        ] {
+            let Err(Error::Validation(errors)) = invalid.build() else {
+                panic!("{field} should have been invalid");
+            };
+            assert!(errors.errors().contains_key(field), "{field}: {errors:?}");
+        }
+    }
+
+    #[test]
+    fn payloads_serialize_as_resend_expects() {
+        let payload = builder()
+            .reply_to("support@notebar.io")
+            .tag("category", "invoice")
+            .attachment(Attachment::new("invoice.pdf", "application/pdf", b"%PDF"))
+            .build()
+            .unwrap();
+
+        let json = serde_json::to_value(&payload).unwrap();
+
+        assert_eq!(json["reply_to"], serde_json::json!(["support@notebar.io"]));
+        assert_eq!(
+            json["tags"],
+            serde_json::json!([{ "name": "category", "value": "invoice" }])
+        );
+        assert_eq!(
+            json["attachments"],
+            serde_json::json!([{
+                "filename": "invoice.pdf",
+                "content_type": "application/pdf",
+                "content": "JVBERg==",
+            }])
+        );
+        assert!(json.get("cc").is_none(), "empty fields should be left out");
+    }
+}
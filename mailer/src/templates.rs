@@ -0,0 +1,184 @@
+use std::{path::Path, sync::Arc};
+
+use minijinja::{ErrorKind, path_loader};
+use minijinja_autoreload::AutoReloader;
+use nohead_rs_config::Config;
+use serde::Serialize;
+
+use crate::{Error, html};
+
+/// The subject and bodies of an email, rendered from its templates.
+#[derive(Clone, Debug)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+/// An email rendered with sample data, which the development mailbox shows before anyone
+/// has to send it.
+#[derive(Clone, Debug)]
+pub struct Preview {
+    /// The email, e.g. `auth/confirmation`.
+    pub name: &'static str,
+    pub context: minijinja::Value,
+}
+
+/// ------------------------------------------------------------------------
+/// # Renders emails from the templates in `mailer/templates`
+/// ------------------------------------------------------------------------
+///
+/// An email is a template such as `auth/confirmation.html`, which usually extends
+/// `layout.html` and sets the `subject` block. The text of the email is rendered from
+/// `auth/confirmation.txt` when there is one, and generated from the HTML otherwise.
+///
+/// The `<style>` rules of the HTML are inlined, as many mail clients ignore stylesheets, and
+/// every template can use the `app_name` and `host` of the [`Config`].
+///
+/// # Example
+///
+/// ```rust
+/// let email = templates.render(
+///     "auth/confirmation",
+///     minijinja::context! { register_token => "123456" },
+/// )?;
+/// ```
+/// ------------------------------------------------------------------------
+#[derive(Clone)]
+pub struct EmailTemplates {
+    reloader: Arc<AutoReloader>,
+    host: String,
+}
+
+impl std::fmt::Debug for EmailTemplates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmailTemplates")
+            .field("host", &self.host)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EmailTemplates {
+    pub fn new(config: &Config) -> Self {
+        let templates_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates");
+        let app_name = config.app.name.clone();
+        let host = config.server.host.clone();
+        let global_host = host.clone();
+
+        let reloader = AutoReloader::new(move |notifier| {
+            let mut env = minijinja::Environment::new();
+            // Watch the template directory for changes in debug mode
+            if cfg!(debug_assertions) {
+                notifier.set_fast_reload(true);
+                notifier.watch_path(&templates_path, true);
+            }
+            env.set_loader(path_loader(&templates_path));
+            env.add_global("app_name", app_name.clone());
+            env.add_global("host", global_host.clone());
+            Ok(env)
+        });
+
+        Self {
+            reloader: Arc::new(reloader),
+            host,
+        }
+    }
+
+    /// The host of the app, which links in emails start with.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Renders the email `name`, e.g. `auth/confirmation` for `auth/confirmation.html`.
+    pub fn render<S: Serialize>(&self, name: &str, data: S) -> Result<RenderedEmail, Error> {
+        let env = self.reloader.acquire_env()?;
+        let context = minijinja::Value::from_serialize(data);
+
+        let template = env.get_template(&format!("{name}.html"))?;
+        let subject = template.eval_to_state(&context)?.render_block("subject")?;
+        let html = html::inline_css(&template.render(&context)?);
+
+        let text = match env.get_template(&format!("{name}.txt")) {
+            Ok(template) => template.render(&context)?.trim().to_string(),
+            Err(err) if err.kind() == ErrorKind::TemplateNotFound => html::to_text(&html)?,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(RenderedEmail {
+            // The subject was escaped along with the rest of the HTML.
+            subject: html::decode(subject.trim()),
+            html,
+            text,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nohead_rs_config::{Environment, load_config};
+
+    fn templates() -> EmailTemplates {
+        let mut config: Config = load_config(&Environment::Test).unwrap();
+        config.app.name = "Tom & Jerry".to_string();
+
+        EmailTemplates::new(&config)
+    }
+
+    #[test]
+    fn render_fills_in_the_layout_and_globals() {
+        let email = templates()
+            .render(
+                "auth/confirmation",
+                minijinja::context! { register_token => "<123456>" },
+            )
+            .unwrap();
+
+        assert_eq!(email.subject, "Please confirm your registration");
+        assert!(
+            email.html.contains("Welcome to Tom &amp; Jerry!"),
+            "{}",
+            email.html
+        );
+        assert!(
+            email.html.contains("&lt;123456&gt;"),
+            "the HTML should be escaped"
+        );
+        assert!(
+            email.html.contains("<div class=\"container\" style=\""),
+            "the styles should be inlined: {}",
+            email.html
+        );
+        assert!(
+            email.text.contains("Welcome to Tom & Jerry!") && email.text.contains("<123456>"),
+            "the text should be generated from the HTML: {}",
+            email.text
+        );
+    }
+
+    #[test]
+    fn render_prefers_a_text_template() {
+        let email = templates()
+            .render(
+                "auth/password_reset",
+                minijinja::context! { reset_url => "http://localhost/reset?token=a&b" },
+            )
+            .unwrap();
+
+        assert_eq!(email.subject, "Reset your password");
+        assert!(
+            email
+                .text
+                .contains("Follow this link to choose a new one: http://localhost/reset?token=a&b"),
+            "{}",
+            email.text
+        );
+        assert!(
+            email
+                .html
+                .contains("href=\"http://localhost/reset?token=a&amp;b\""),
+            "{}",
+            email.html
+        );
+    }
+}
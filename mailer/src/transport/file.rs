@@ -0,0 +1,144 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use tracing::{info, warn};
+
+use super::{SentEmail, Transport};
+use crate::{EmailPayload, Error, message};
+
+/// Writes each email to a `.eml` file in a directory, which mail clients can open.
+///
+/// The files are named after the time they were written at, so they sort oldest first. Each
+/// email is also written as JSON next to its `.eml` file, which is what [`Transport::sent`]
+/// reads back for the development mailbox.
+#[derive(Clone, Debug)]
+pub struct FileTransport {
+    path: PathBuf,
+}
+
+impl FileTransport {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl Transport for FileTransport {
+    async fn send(&self, email: &EmailPayload) -> Result<(), Error> {
+        tokio::fs::create_dir_all(&self.path).await?;
+
+        let name = format!(
+            "{}-{}",
+            Utc::now().format(TIMESTAMP_FORMAT),
+            message::random_id()
+        );
+        let file = self.path.join(format!("{name}.eml"));
+        tokio::fs::write(&file, message::build(email)?.formatted()).await?;
+        let json = serde_json::to_vec_pretty(email).map_err(std::io::Error::other)?;
+        tokio::fs::write(self.path.join(format!("{name}.json")), json).await?;
+        info!("wrote email \"{}\" to {}", email.subject(), file.display());
+
+        Ok(())
+    }
+
+    async fn sent(&self) -> Result<Vec<SentEmail>, Error> {
+        let mut entries = match tokio::fs::read_dir(&self.path).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut sent = vec![];
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let Some(id) = path
+                .file_stem()
+                .filter(|_| {
+                    path.extension()
+                        .is_some_and(|extension| extension == "json")
+                })
+                .and_then(|stem| stem.to_str())
+            else {
+                continue;
+            };
+            let Some(sent_at) = id
+                .split_once('-')
+                .and_then(|(timestamp, _)| {
+                    NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()
+                })
+                .map(|sent_at| sent_at.and_utc())
+            else {
+                continue;
+            };
+
+            // Files written by other versions of the app may not parse, which shouldn't hide
+            // the other emails.
+            match serde_json::from_slice(&tokio::fs::read(&path).await?) {
+                Ok(email) => sent.push(SentEmail {
+                    id: id.to_string(),
+                    sent_at,
+                    email,
+                }),
+                Err(err) => warn!("skipped unreadable email {}: {err}", path.display()),
+            }
+        }
+        sent.sort_by(|a, b| b.id.cmp(&a.id));
+
+        Ok(sent)
+    }
+}
+
+/// Sorts as text in the order the emails were written.
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.3f";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fake::{Fake, Faker};
+
+    #[tokio::test]
+    async fn send_writes_an_eml_file() {
+        let path = std::env::temp_dir().join(format!("nohead-rs-mails-{}", std::process::id()));
+        let transport = FileTransport::new(&path);
+
+        let payload: EmailPayload = Faker.fake();
+        transport.send(&payload).await.unwrap();
+
+        let files: Vec<_> = std::fs::read_dir(&path)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().unwrap() == "eml")
+            .collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].extension().unwrap(), "eml");
+
+        let eml = std::fs::read_to_string(&files[0]).unwrap();
+        assert!(eml.contains(&format!("Subject: {}\r\n", payload.subject())));
+        assert!(eml.contains("Content-Type: multipart/alternative;"));
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn sent_reads_the_emails_back_newest_first() {
+        let path = std::env::temp_dir().join(format!("nohead-rs-sent-{}", std::process::id()));
+        let transport = FileTransport::new(&path);
+        assert!(transport.sent().await.unwrap().is_empty());
+
+        let first: EmailPayload = Faker.fake();
+        let second: EmailPayload = Faker.fake();
+        transport.send(&first).await.unwrap();
+        // The file names only tell apart emails sent in different milliseconds.
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        transport.send(&second).await.unwrap();
+
+        let sent = transport.sent().await.unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].email.subject(), second.subject());
+        assert_eq!(sent[1].email.subject(), first.subject());
+        assert!(sent[0].sent_at >= sent[1].sent_at);
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
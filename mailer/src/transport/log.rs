@@ -0,0 +1,24 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use super::Transport;
+use crate::{EmailPayload, Error};
+
+/// Logs emails instead of sending them, along with their text.
+#[derive(Clone, Debug)]
+pub struct LogTransport;
+
+#[async_trait]
+impl Transport for LogTransport {
+    async fn send(&self, email: &EmailPayload) -> Result<(), Error> {
+        info!(
+            from = email.from(),
+            to = email.to().join(", "),
+            subject = email.subject(),
+            "email not sent, logged instead:\n{}",
+            email.text()
+        );
+
+        Ok(())
+    }
+}
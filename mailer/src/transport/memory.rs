@@ -0,0 +1,53 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use super::{SentEmail, Transport};
+use crate::{EmailPayload, Error};
+
+/// Keeps the emails it is given, so that tests can check what was sent.
+///
+/// Clones share the same emails.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryTransport {
+    emails: Arc<Mutex<Vec<SentEmail>>>,
+}
+
+impl MemoryTransport {
+    /// The emails that were sent, oldest first.
+    pub fn emails(&self) -> Vec<EmailPayload> {
+        self.emails
+            .lock()
+            .expect("poisoned email lock")
+            .iter()
+            .map(|sent| sent.email.clone())
+            .collect()
+    }
+
+    /// Forgets the emails that were sent so far.
+    pub fn clear(&self) {
+        self.emails.lock().expect("poisoned email lock").clear();
+    }
+}
+
+#[async_trait]
+impl Transport for MemoryTransport {
+    async fn send(&self, email: &EmailPayload) -> Result<(), Error> {
+        let mut emails = self.emails.lock().expect("poisoned email lock");
+        let id = (emails.len() + 1).to_string();
+        emails.push(SentEmail {
+            id,
+            sent_at: Utc::now(),
+            email: email.clone(),
+        });
+
+        Ok(())
+    }
+
+    async fn sent(&self) -> Result<Vec<SentEmail>, Error> {
+        let emails = self.emails.lock().expect("poisoned email lock");
+
+        Ok(emails.iter().rev().cloned().collect())
+    }
+}
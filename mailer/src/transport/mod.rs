@@ -0,0 +1,64 @@
+//! The ways emails are delivered, one of which is selected in the `[mailer.transport]` section
+//! of the config, see [`nohead_rs_config::TransportConfig`].
+//!
+//! Only production needs an email service, development writes emails to `.eml` files and tests
+//! keep them in memory, so neither needs a network or an API key.
+
+mod file;
+mod log;
+mod memory;
+mod resend;
+mod smtp;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{EmailPayload, Error};
+
+pub use file::FileTransport;
+pub use log::LogTransport;
+pub use memory::MemoryTransport;
+pub use resend::ResendTransport;
+pub use smtp::SmtpTransport;
+
+/// ------------------------------------------------------------------------
+/// # Delivers emails that were validated by the [`crate::EmailClient`]
+/// ------------------------------------------------------------------------
+///
+/// Implement it to send emails some other way, and pass it to
+/// [`crate::EmailClient::with_transport`].
+///
+/// # Example
+///
+/// ```rust
+/// #[derive(Debug)]
+/// struct Discard;
+///
+/// #[async_trait]
+/// impl Transport for Discard {
+///     async fn send(&self, _email: &EmailPayload) -> Result<(), Error> {
+///         Ok(())
+///     }
+/// }
+/// ```
+/// ------------------------------------------------------------------------
+#[async_trait]
+pub trait Transport: std::fmt::Debug + Send + Sync {
+    async fn send(&self, email: &EmailPayload) -> Result<(), Error>;
+
+    /// The emails that were sent, newest first, for the transports that keep them such as the
+    /// [`FileTransport`]. The others keep none.
+    async fn sent(&self) -> Result<Vec<SentEmail>, Error> {
+        Ok(vec![])
+    }
+}
+
+/// An email that a [`Transport`] kept after sending it.
+#[derive(Serialize, Clone, Debug)]
+pub struct SentEmail {
+    /// Tells the email apart from the others of the transport.
+    pub id: String,
+    pub sent_at: DateTime<Utc>,
+    pub email: EmailPayload,
+}
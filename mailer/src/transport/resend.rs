@@ -0,0 +1,213 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::Client;
+
+use super::Transport;
+use crate::{EmailPayload, Error};
+
+/// Sends emails with the HTTP API of [Resend](https://resend.com/docs/api-reference/emails/send-email).
+#[derive(Clone)]
+pub struct ResendTransport {
+    http_client: Client,
+    base_url: String,
+    authorization_token: String,
+}
+
+// Manual implementation of Debug for ResendTransport to redact the authorization token
+impl std::fmt::Debug for ResendTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResendTransport")
+            .field("http_client", &self.http_client)
+            .field("base_url", &self.base_url)
+            .field("authorization_token", &"[redacted]")
+            .finish()
+    }
+}
+
+impl ResendTransport {
+    pub fn new(base_url: &str, api_key: &str, timeout: Duration) -> Result<Self, Error> {
+        let http_client = Client::builder().timeout(timeout).build()?;
+
+        Ok(Self {
+            http_client,
+            base_url: base_url.to_string(),
+            authorization_token: api_key.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for ResendTransport {
+    async fn send(&self, email: &EmailPayload) -> Result<(), Error> {
+        let url = format!("{}/emails", self.base_url);
+
+        let res = self
+            .http_client
+            .post(url)
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.authorization_token),
+            )
+            .json(email)
+            .send()
+            .await?;
+
+        res.error_for_status()?; // return an error if the response status is not 2xx
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EmailClient;
+    use fake::{Fake, Faker};
+
+    use crate::Attachment;
+    use nohead_rs_config::{Config, Environment, load_config};
+    use wiremock::matchers::{any, body_partial_json, header, header_exists, method, path};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    struct SendEmailBodyMatcher;
+
+    impl wiremock::Match for SendEmailBodyMatcher {
+        fn matches(&self, request: &Request) -> bool {
+            let result: Result<serde_json::Value, _> = serde_json::from_slice(&request.body);
+            if let Ok(body) = result {
+                body.get("from").is_some()
+                    && body.get("to").is_some()
+                    && body.get("subject").is_some()
+                    && body.get("html").is_some()
+                    && body.get("text").is_some()
+            } else {
+                false
+            }
+        }
+    }
+
+    fn get_test_email_client(mock_server: &MockServer) -> EmailClient {
+        let config: Config = load_config(&Environment::Test).unwrap();
+
+        let transport = ResendTransport::new(
+            &mock_server.uri(),
+            "test",
+            Duration::from_millis(config.mailer.timeout),
+        )
+        .unwrap();
+        EmailClient::with_transport(&config.mailer.sender, transport)
+    }
+
+    #[tokio::test]
+    async fn send_email_succeeds_if_the_server_returns_200() {
+        let mock_server = MockServer::start().await;
+        let email_client = get_test_email_client(&mock_server);
+
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let payload: EmailPayload = Faker.fake();
+        let result = email_client.send_email(payload).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn send_email_sends_the_expected_request() {
+        let mock_server = MockServer::start().await;
+        let email_client = get_test_email_client(&mock_server);
+
+        Mock::given(header_exists("Authorization"))
+            .and(header("Content-Type", "application/json"))
+            .and(path("/emails"))
+            .and(method("POST"))
+            .and(SendEmailBodyMatcher)
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let payload: EmailPayload = Faker.fake();
+        let _ = email_client.send_email(payload).await;
+    }
+
+    #[tokio::test]
+    async fn send_email_sends_the_extra_fields_as_resend_expects() {
+        let mock_server = MockServer::start().await;
+        let email_client = get_test_email_client(&mock_server);
+
+        Mock::given(path("/emails"))
+            .and(body_partial_json(serde_json::json!({
+                "cc": ["joe@example.com"],
+                "bcc": ["audit@example.com"],
+                "reply_to": ["support@notebar.io"],
+                "headers": { "List-Unsubscribe": "<https://example.com/unsubscribe>" },
+                "tags": [{ "name": "category", "value": "invoice" }],
+                "attachments": [{
+                    "filename": "invoice.pdf",
+                    "content_type": "application/pdf",
+                    "content": "JVBERg==",
+                }],
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let payload = EmailPayload::builder()
+            .from("dev@notebar.io")
+            .to("jane@example.com")
+            .cc("joe@example.com")
+            .bcc("audit@example.com")
+            .reply_to("support@notebar.io")
+            .subject("Your invoice")
+            .html("<p>Attached</p>")
+            .text("Attached")
+            .header("List-Unsubscribe", "<https://example.com/unsubscribe>")
+            .tag("category", "invoice")
+            .attachment(Attachment::new("invoice.pdf", "application/pdf", b"%PDF"))
+            .build()
+            .unwrap();
+        let result = email_client.send_email(payload).await;
+
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[tokio::test]
+    async fn send_email_fails_if_the_server_returns_400() {
+        let mock_server = MockServer::start().await;
+        let email_client = get_test_email_client(&mock_server);
+
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(400))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let payload: EmailPayload = Faker.fake();
+        let result = email_client.send_email(payload).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn send_email_times_out_if_the_server_takes_too_long() {
+        let mock_server = MockServer::start().await;
+        let email_client = get_test_email_client(&mock_server);
+
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(100)))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let payload: EmailPayload = Faker.fake();
+        let result = email_client.send_email(payload).await;
+
+        assert!(result.is_err());
+    }
+}
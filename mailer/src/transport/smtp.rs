@@ -0,0 +1,181 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport as _, Tokio1Executor,
+    transport::smtp::authentication::Credentials,
+};
+use nohead_rs_config::SmtpTls;
+
+use super::Transport;
+use crate::{EmailPayload, Error, message};
+
+/// Sends emails to an SMTP server, e.g. that of an email service or a local Mailpit.
+///
+/// Every email is sent over a connection of its own, which is secured as configured with
+/// [`SmtpTls`] and logged in to when there are credentials.
+#[derive(Clone)]
+pub struct SmtpTransport {
+    host: String,
+    port: u16,
+    tls: SmtpTls,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+// Manual implementation of Debug for SmtpTransport to keep the credentials out of it
+impl std::fmt::Debug for SmtpTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmtpTransport")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("tls", &self.tls)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SmtpTransport {
+    /// Fails when TLS can't be set up for the host.
+    pub fn new(
+        host: &str,
+        port: u16,
+        tls: SmtpTls,
+        credentials: Option<(String, String)>,
+        timeout: Duration,
+    ) -> Result<Self, Error> {
+        let builder = match tls {
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+            SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+        }
+        .port(port)
+        .timeout(Some(timeout));
+        let builder = match credentials {
+            Some((username, password)) => builder.credentials(Credentials::new(username, password)),
+            None => builder,
+        };
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            tls,
+            transport: builder.build(),
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for SmtpTransport {
+    async fn send(&self, email: &EmailPayload) -> Result<(), Error> {
+        self.transport.send(message::build(email)?).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{Engine as _, prelude::BASE64_STANDARD};
+    use fake::{Fake, Faker};
+    use tokio::{
+        io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufStream},
+        net::TcpListener,
+    };
+
+    /// Plays an SMTP server that accepts one email, returning the commands it received.
+    async fn serve_one(listener: TcpListener) -> Vec<String> {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut stream = BufStream::new(stream);
+        let mut commands = vec![];
+
+        stream.write_all(b"220 localhost ready\r\n").await.unwrap();
+        stream.flush().await.unwrap();
+        loop {
+            let mut line = String::new();
+            if stream.read_line(&mut line).await.unwrap() == 0 {
+                break;
+            }
+            let command = line.trim_end().to_string();
+            let reply: &[u8] = match command.split(' ').next().unwrap() {
+                "EHLO" => b"250-localhost\r\n250 AUTH PLAIN\r\n",
+                "AUTH" => b"235 authenticated\r\n",
+                "MAIL" | "RCPT" => b"250 ok\r\n",
+                "DATA" => {
+                    stream.write_all(b"354 go ahead\r\n").await.unwrap();
+                    stream.flush().await.unwrap();
+                    let mut data = String::new();
+                    while !data.ends_with("\r\n.\r\n") {
+                        stream.read_line(&mut data).await.unwrap();
+                    }
+                    commands.push("DATA".to_string());
+                    commands.push(data);
+                    stream.write_all(b"250 queued\r\n").await.unwrap();
+                    stream.flush().await.unwrap();
+                    continue;
+                }
+                "QUIT" => b"221 bye\r\n",
+                _ => b"500 unknown command\r\n",
+            };
+            commands.push(command);
+            stream.write_all(reply).await.unwrap();
+            stream.flush().await.unwrap();
+        }
+
+        commands
+    }
+
+    #[tokio::test]
+    async fn send_delivers_the_email_to_the_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(serve_one(listener));
+
+        let transport = SmtpTransport::new(
+            "127.0.0.1",
+            port,
+            SmtpTls::None,
+            Some(("user".to_string(), "secret".to_string())),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        let payload: EmailPayload = Faker.fake();
+        transport.send(&payload).await.unwrap();
+
+        let commands = server.await.unwrap();
+        assert!(commands[0].starts_with("EHLO "), "{commands:?}");
+        assert_eq!(
+            commands[1],
+            format!("AUTH PLAIN {}", BASE64_STANDARD.encode("\0user\0secret"))
+        );
+        assert_eq!(commands[2], format!("MAIL FROM:<{}>", payload.from()));
+        assert_eq!(commands[3], format!("RCPT TO:<{}>", payload.to()[0]));
+        assert_eq!(commands[4], "DATA");
+        assert!(commands[5].contains(&format!("Subject: {}\r\n", payload.subject())));
+    }
+
+    #[tokio::test]
+    async fn send_fails_when_the_server_refuses_the_email() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.write_all(b"554 no service here\r\n").await.unwrap();
+        });
+
+        let transport = SmtpTransport::new(
+            "127.0.0.1",
+            port,
+            SmtpTls::None,
+            None,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        let payload: EmailPayload = Faker.fake();
+        let result = transport.send(&payload).await;
+
+        assert!(
+            matches!(&result, Err(Error::Smtp(err)) if err.is_permanent()),
+            "{result:?}"
+        );
+    }
+}
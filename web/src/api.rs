@@ -0,0 +1,94 @@
+//! The versioned JSON API, for clients other than the browser.
+//!
+//! It mounts the same controllers as the app under [`PREFIX`], but only ever answers JSON and
+//! authenticates requests with API tokens instead of the browser session, see
+//! [`bearer_auth`]. Tokens are issued and revoked through the `/tokens` routes.
+//!
+//! # Example
+//!
+//! ```sh
+//! curl -X POST localhost:8080/api/v1/tokens \
+//!     -H 'Content-Type: application/json' \
+//!     -d '{"email": "user@example.com", "password": "password", "name": "my phone"}'
+//! curl localhost:8080/api/v1/todos -H 'Authorization: Bearer nh_...'
+//! ```
+
+use std::time::Duration;
+
+use axum::{
+    Router,
+    extract::Request,
+    http::{HeaderValue, header::ACCEPT, header::LOCATION},
+    middleware::{from_fn_with_state, map_request, map_response},
+    response::Response,
+};
+use axum_login::AuthManagerLayer;
+use tower::ServiceBuilder;
+use tower_http::{timeout::TimeoutLayer, trace::TraceLayer};
+use tower_sessions_sqlx_store::SqliteStore;
+
+use crate::{
+    controllers::{Controller, api_tokens::ApiTokenController, todos::TodoController},
+    error::Error,
+    middlewares::{
+        auth::{AuthBackend, bearer_auth},
+        error_pages::error_pages,
+    },
+    router::RouteTable,
+    state::AppState,
+};
+
+/// Where the current version of the API is mounted.
+pub const PREFIX: &str = "/api/v1";
+
+pub fn router(
+    app_state: &AppState,
+    auth_layer: AuthManagerLayer<AuthBackend, SqliteStore, tower_sessions::service::SignedCookie>,
+    table: &mut RouteTable,
+) -> Router {
+    Router::new()
+        .merge(table.nest(PREFIX, TodoController::ROUTES, TodoController::router()))
+        .merge(table.nest(
+            PREFIX,
+            ApiTokenController::ROUTES,
+            ApiTokenController::router(),
+        ))
+        .fallback(|| async { Error::NotFound })
+        .with_state(app_state.clone())
+        .layer(ServiceBuilder::new().layer((
+            TraceLayer::new_for_http(),
+            TimeoutLayer::new(Duration::from_secs(10)),
+            map_request(json_only),
+            map_response(prefix_location),
+            from_fn_with_state(app_state.clone(), error_pages),
+            from_fn_with_state(app_state.clone(), bearer_auth),
+            // Only to check the permissions of users, the session itself is never loaded as
+            // `bearer_auth` drops the cookies.
+            auth_layer,
+        )))
+}
+
+/// Answers every request with JSON, whatever it accepts.
+async fn json_only(mut request: Request) -> Request {
+    let headers = request.headers_mut();
+    headers.remove("hx-request");
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+
+    request
+}
+
+/// Points the locations of new records at the API, the controllers only know the paths of
+/// the app.
+async fn prefix_location(mut response: Response) -> Response {
+    let location = response
+        .headers()
+        .get(LOCATION)
+        .and_then(|location| location.to_str().ok())
+        .filter(|location| location.starts_with('/'))
+        .and_then(|location| HeaderValue::from_str(&format!("{PREFIX}{location}")).ok());
+    if let Some(location) = location {
+        response.headers_mut().insert(LOCATION, location);
+    }
+
+    response
+}
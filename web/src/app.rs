@@ -13,11 +13,16 @@ use tokio::{
 
 use crate::{
     initializers::view_engine::engine::ViewEngineInitializer,
-    middlewares::auth::AuthSessionManager, router::init_router, state::AppState, tracing::Tracing,
+    middlewares::auth::AuthSessionManager,
+    router::{RouteTable, init_router},
+    state::AppState,
+    tracing::Tracing,
 };
 
 pub struct App {
     pub router: Router,
+    /// The routes of [`App::router`], see [`RouteTable`].
+    pub routes: RouteTable,
     pub app_state: AppState,
     pub deletion_task: JoinHandle<Result<(), session_store::Error>>,
     pub worker_monitor_task: JoinHandle<Result<(), std::io::Error>>,
@@ -42,10 +47,11 @@ impl App {
         view_engine.before_run(app_state.clone())?;
 
         // Initialize the router
-        let router = init_router(&app_state, auth_layer, worker.storage, view_engine)?;
+        let (router, routes) = init_router(&app_state, auth_layer, worker.storage, view_engine)?;
 
         Ok(Self {
             router,
+            routes,
             app_state,
             deletion_task,
             worker_monitor_task: worker.monitor_task,
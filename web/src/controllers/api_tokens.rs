@@ -0,0 +1,123 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{Method, StatusCode},
+    routing::{delete, get},
+};
+use nohead_rs_db::entities::{
+    api_token::{ApiToken, IssuedApiToken, NewApiToken},
+    user::{UserCredentials, UserStatus},
+};
+
+use crate::{
+    error::Error,
+    forms::ValidForm,
+    middlewares::auth::{AuthSession, CurrentUser},
+    openapi::{OpenApi, Operation},
+    router::Routes,
+    state::AppState,
+};
+
+/// Personal access tokens for the JSON API, see [`crate::middlewares::auth::bearer_auth`].
+pub struct ApiTokenController;
+
+impl ApiTokenController {
+    pub const ROUTES: Routes = &[
+        ("/tokens", &[Method::GET, Method::POST]),
+        ("/tokens/{id}", &[Method::DELETE]),
+    ];
+
+    pub fn router() -> Router<AppState> {
+        Router::new()
+            .route("/tokens", get(Self::read_all).post(Self::create))
+            .route("/tokens/{id}", delete(Self::delete))
+    }
+
+    /// Describes the token routes under `path` for the OpenAPI document.
+    pub fn openapi(path: &str, doc: OpenApi) -> OpenApi {
+        doc.operation(
+            Method::GET,
+            path,
+            Operation::new("List the API tokens of the current user")
+                .tag("tokens")
+                .bearer_auth()
+                .list_response::<ApiToken>(StatusCode::OK, "the tokens, newest first"),
+        )
+        .operation(
+            Method::POST,
+            path,
+            Operation::new("Issue an API token")
+                .description("The token is only ever part of this response.")
+                .tag("tokens")
+                .body::<NewApiToken>("application/json")
+                .response::<IssuedApiToken>(StatusCode::CREATED, "the new token")
+                .errors(&[
+                    Error::Unauthenticated,
+                    Error::PendingUser,
+                    Error::SuspendedUser,
+                    Error::Database(nohead_rs_db::Error::ValidationError(Default::default())),
+                ]),
+        )
+        .operation(
+            Method::DELETE,
+            &format!("{path}/{{id}}"),
+            Operation::new("Revoke an API token")
+                .tag("tokens")
+                .bearer_auth()
+                .path_param("id")
+                .empty_response(StatusCode::NO_CONTENT, "the token was revoked")
+                .errors(&[Error::Database(nohead_rs_db::Error::NoRecordFound)]),
+        )
+    }
+
+    /// Lists the tokens of the current user, without the tokens themselves.
+    pub async fn read_all(
+        CurrentUser(user): CurrentUser,
+        State(app_state): State<AppState>,
+    ) -> Result<Json<Vec<ApiToken>>, Error> {
+        let api_tokens = ApiToken::load_all(user.id, &app_state.db_read_pool).await?;
+
+        Ok(Json(api_tokens))
+    }
+
+    /// Issues a token to the user with the credentials that were sent.
+    ///
+    /// The token is only ever part of this response.
+    pub async fn create(
+        auth_session: AuthSession,
+        State(app_state): State<AppState>,
+        ValidForm(new_token): ValidForm<NewApiToken>,
+    ) -> Result<(StatusCode, Json<IssuedApiToken>), Error> {
+        let creds = UserCredentials {
+            email: new_token.email,
+            password: new_token.password,
+            next: None,
+        };
+
+        let user = match auth_session.authenticate(creds).await {
+            Ok(Some(user)) => user,
+            Ok(None) => return Err(Error::Unauthenticated),
+            Err(axum_login::Error::Backend(err)) => return Err(err),
+            Err(e) => return Err(Error::Unexpected(e.into())),
+        };
+        // Pending users may be let into a limited session, but not into the API.
+        if user.status != UserStatus::Confirmed {
+            return Err(Error::PendingUser);
+        }
+
+        let issued = ApiToken::create(&new_token.name, user.id, &app_state.db_pool).await?;
+
+        Ok((StatusCode::CREATED, Json(issued)))
+    }
+
+    /// Revokes a token of the current user.
+    pub async fn delete(
+        CurrentUser(user): CurrentUser,
+        Path(id): Path<i64>,
+        State(app_state): State<AppState>,
+    ) -> Result<StatusCode, Error> {
+        ApiToken::revoke(id, user.id, &app_state.db_pool).await?;
+
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
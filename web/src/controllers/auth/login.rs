@@ -1,14 +1,20 @@
 use axum::Router;
-use axum::extract::Query;
-use axum::routing::get;
-use axum::{Form, response::Redirect};
+use axum::extract::{Query, State};
+use axum::http::{Method, StatusCode};
+use axum::middleware::from_fn_with_state;
+use axum::response::Redirect;
+use axum::routing::{get, post};
 use nohead_rs_db::entities::user::UserCredentials;
 use serde::Deserialize;
 
 use crate::error::Error;
+use crate::forms::{ValidForm, form_page};
 use crate::initializers::view_engine::engine::{View, ViewEngine};
 use crate::middlewares::auth::AuthSession;
 use crate::middlewares::flash::{Flash, IncomingFlashes};
+use crate::openapi::{OpenApi, Operation};
+use crate::redirect::SafeRedirect;
+use crate::router::Routes;
 use crate::state::AppState;
 use crate::views::auth::login::LoginView;
 
@@ -22,38 +28,90 @@ pub struct NextUrl {
 pub struct LoginController;
 
 impl LoginController {
+    pub const ROUTES: Routes = &[("/auth/login", &[Method::GET, Method::POST])];
+
     pub fn router() -> Router<AppState> {
         Router::new().route(
             "/auth/login",
-            get(LoginController::index).post(LoginController::login),
+            get(LoginController::index).merge(
+                post(LoginController::login)
+                    .route_layer(from_fn_with_state(LoginView::FORM, form_page)),
+            ),
+        )
+    }
+
+    /// Describes the login form for the OpenAPI document.
+    pub fn openapi(doc: OpenApi) -> OpenApi {
+        doc.operation(
+            Method::POST,
+            "/auth/login",
+            Operation::new("Log in")
+                .description(
+                    "Sent by the login form, along with its `csrf_token`. Starts a browser \
+                    session, the JSON API authenticates with API tokens instead.",
+                )
+                .tag("auth")
+                .body::<UserCredentials>("application/x-www-form-urlencoded")
+                .empty_response(
+                    StatusCode::SEE_OTHER,
+                    "to the `next` page once logged in, or back to the login form",
+                )
+                .errors(&[
+                    Error::InvalidCsrfToken,
+                    Error::Database(nohead_rs_db::Error::ValidationError(Default::default())),
+                ]),
         )
     }
 
     pub async fn index(
+        State(app_state): State<AppState>,
         v: ViewEngine<View>,
         Query(NextUrl { next }): Query<NextUrl>,
         flashes: IncomingFlashes,
     ) -> (IncomingFlashes, LoginView) {
+        let next = next.and_then(|next| SafeRedirect::parse(&next, &app_state.config.server.host));
+
         (flashes.clone(), LoginView::Index(v, flashes, next))
     }
 
     pub async fn login(
+        State(app_state): State<AppState>,
         mut auth_session: AuthSession,
         flash: Flash,
-        Form(creds): Form<UserCredentials>,
+        ValidForm(creds): ValidForm<UserCredentials>,
     ) -> Result<(Flash, Redirect), Error> {
+        // Only ever redirect back to this site.
+        let next = creds
+            .next
+            .as_deref()
+            .and_then(|next| SafeRedirect::parse(next, &app_state.config.server.host));
+
         let user = match auth_session.authenticate(creds.clone()).await {
             Ok(Some(user)) => user,
             Ok(None) => {
                 let mut login_url = "/auth/login".to_string();
-                if let Some(next) = creds.next {
-                    login_url = format!("{}?next={}", login_url, next);
+                if let Some(next) = next {
+                    let query = serde_urlencoded::to_string([("next", next.as_str())])
+                        .map_err(|e| Error::Unexpected(e.into()))?;
+                    login_url = format!("{}?{}", login_url, query);
                 };
                 return Ok((
                     flash.error("❌ invalid credentials"),
                     Redirect::to(&login_url),
                 ));
             }
+            Err(axum_login::Error::Backend(Error::PendingUser)) => {
+                return Ok((
+                    flash.warning("please confirm your email before logging in"),
+                    Redirect::to("/auth/register/confirm"),
+                ));
+            }
+            Err(axum_login::Error::Backend(Error::SuspendedUser)) => {
+                return Ok((
+                    flash.error("❌ your account has been suspended"),
+                    Redirect::to("/auth/login"),
+                ));
+            }
             Err(e) => return Err(Error::Unexpected(e.into())),
         };
 
@@ -62,11 +120,8 @@ impl LoginController {
             .await
             .map_err(|e| Error::Unexpected(e.into()))?;
 
-        if let Some(ref next) = creds.next {
-            Ok((
-                flash.success("✅ successfully logged in"),
-                Redirect::to(next),
-            ))
+        if let Some(next) = next {
+            Ok((flash.success("✅ successfully logged in"), next.into()))
         } else {
             Ok((
                 flash.success("✅ successfully logged in"),
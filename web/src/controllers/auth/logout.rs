@@ -1,14 +1,17 @@
-use axum::{Router, response::Redirect, routing::post};
+use axum::{Router, http::Method, response::Redirect, routing::post};
 
 use crate::{
     error::Error,
     middlewares::{auth::AuthSession, flash::Flash},
+    router::Routes,
     state::AppState,
 };
 
 pub struct LogoutController;
 
 impl LogoutController {
+    pub const ROUTES: Routes = &[("/auth/logout", &[Method::POST])];
+
     pub fn router() -> Router<AppState> {
         Router::new().route("/auth/logout", post(LogoutController::logout))
     }
@@ -1,4 +1,6 @@
 pub mod login;
 pub mod logout;
+pub mod password_forgot;
+pub mod password_reset;
 pub mod register;
 pub mod register_confirm;
@@ -0,0 +1,80 @@
+use axum::{
+    Extension, Form, Router, extract::State, http::Method, response::Redirect, routing::get,
+};
+use nohead_rs_db::{
+    Validate as _,
+    entities::{
+        password_reset_token::{ForgotPassword, PasswordResetToken},
+        user::User,
+    },
+    transaction,
+};
+use nohead_rs_mailer::{EmailPayload, auth::AuthMailer};
+use nohead_rs_worker::{Storage, WorkerStorage};
+
+use crate::{
+    error::Error,
+    initializers::view_engine::engine::{View, ViewEngine},
+    middlewares::flash::{Flash, IncomingFlashes},
+    router::Routes,
+    state::AppState,
+    views::auth::password_forgot::PasswordForgotView,
+};
+
+pub struct PasswordForgotController;
+
+impl PasswordForgotController {
+    pub const ROUTES: Routes = &[("/auth/password/forgot", &[Method::GET, Method::POST])];
+
+    pub fn router() -> Router<AppState> {
+        Router::new().route(
+            "/auth/password/forgot",
+            get(PasswordForgotController::index).post(PasswordForgotController::forgot),
+        )
+    }
+
+    pub async fn index(
+        v: ViewEngine<View>,
+        flashes: IncomingFlashes,
+    ) -> (IncomingFlashes, PasswordForgotView) {
+        (flashes.clone(), PasswordForgotView::Index(v, flashes))
+    }
+
+    pub async fn forgot(
+        flash: Flash,
+        State(app_state): State<AppState>,
+        Extension(mut jobs): Extension<WorkerStorage<EmailPayload>>,
+        Form(form): Form<ForgotPassword>,
+    ) -> Result<(Flash, Redirect), Error> {
+        form.validate().map_err(nohead_rs_db::Error::from)?;
+
+        // Only send an email if the user exists, but always respond the same way
+        // so the form can't be used to find out which emails are registered.
+        if let Some(user) = User::try_get_by_email(&form.email, &app_state.db_pool).await? {
+            let mut tx = transaction(&app_state.db_pool).await?;
+            // Requesting a new reset token invalidates any previous ones
+            PasswordResetToken::delete_for_user(user.id, &mut *tx).await?;
+            let reset_token = PasswordResetToken::create(user.id, &mut *tx).await?;
+            let email = AuthMailer::send_password_reset(
+                &app_state.email_client,
+                &app_state.email_templates,
+                &user.email,
+                &reset_token.reset_token,
+            )?;
+            tx.commit().await.map_err(|e| Error::Database(e.into()))?;
+
+            // Send the password reset email in a background job
+            jobs.push(email)
+                .await
+                .map_err(|e| {
+                    tracing::error!("failed to send password reset email: {:?}", e);
+                })
+                .ok();
+        }
+
+        Ok((
+            flash.info("please check your email for a password reset link"),
+            Redirect::to("/auth/login"),
+        ))
+    }
+}
@@ -0,0 +1,76 @@
+use axum::{
+    Form, Router,
+    extract::{Query, State},
+    http::Method,
+    response::Redirect,
+    routing::get,
+};
+use nohead_rs_db::{
+    entities::{
+        password_reset_token::{PasswordResetToken, ResetPassword},
+        user::User,
+    },
+    transaction,
+};
+use serde::Deserialize;
+
+use crate::{
+    error::Error,
+    initializers::view_engine::engine::{View, ViewEngine},
+    middlewares::flash::{Flash, IncomingFlashes},
+    router::Routes,
+    state::AppState,
+    views::auth::password_reset::PasswordResetView,
+};
+
+// This allows us to extract the emailed reset token from the query string so it
+// can be posted back with the new password.
+#[derive(Debug, Deserialize)]
+pub struct ResetTokenQuery {
+    token: Option<String>,
+}
+
+pub struct PasswordResetController;
+
+impl PasswordResetController {
+    pub const ROUTES: Routes = &[("/auth/password/reset", &[Method::GET, Method::POST])];
+
+    pub fn router() -> Router<AppState> {
+        Router::new().route(
+            "/auth/password/reset",
+            get(PasswordResetController::index).post(PasswordResetController::reset),
+        )
+    }
+
+    pub async fn index(
+        v: ViewEngine<View>,
+        Query(ResetTokenQuery { token }): Query<ResetTokenQuery>,
+        flashes: IncomingFlashes,
+    ) -> (IncomingFlashes, PasswordResetView) {
+        (flashes.clone(), PasswordResetView::Index(v, flashes, token))
+    }
+
+    pub async fn reset(
+        flash: Flash,
+        State(app_state): State<AppState>,
+        Form(form): Form<ResetPassword>,
+    ) -> Result<(Flash, Redirect), Error> {
+        let mut tx = transaction(&app_state.db_pool).await?;
+        // Get the user id by the emailed reset token
+        let user_id = PasswordResetToken::try_get_user_id_by_reset_token(&form, &mut *tx)
+            .await?
+            .ok_or(Error::InvalidPasswordResetToken)?;
+        // Tokens are single use, so remove it along with any others issued to the user
+        PasswordResetToken::delete_for_user(user_id, &mut *tx).await?;
+        // The password hash doubles as the session auth hash, so updating it
+        // invalidates every existing session for the user.
+        User::update_password(user_id, &form.password, &mut *tx).await?;
+        // Commit the transaction
+        tx.commit().await.map_err(|e| Error::Database(e.into()))?;
+
+        Ok((
+            flash.success("your password has been reset, please log in"),
+            Redirect::to("/auth/login"),
+        ))
+    }
+}
@@ -1,11 +1,21 @@
 use crate::{
     error::Error,
+    forms::{ValidForm, form_page},
     initializers::view_engine::engine::{View, ViewEngine},
     middlewares::flash::{Flash, IncomingFlashes},
+    openapi::{OpenApi, Operation},
+    router::Routes,
     state::AppState,
     views::auth::register::RegisterView,
 };
-use axum::{Extension, Form, Router, extract::State, response::Redirect, routing::get};
+use axum::{
+    Extension, Router,
+    extract::State,
+    http::{Method, StatusCode},
+    middleware::from_fn_with_state,
+    response::Redirect,
+    routing::{get, post},
+};
 use nohead_rs_db::{
     entities::{
         register_token::RegisterToken,
@@ -19,10 +29,36 @@ use nohead_rs_worker::{Storage, WorkerStorage};
 pub struct RegisterController;
 
 impl RegisterController {
+    pub const ROUTES: Routes = &[("/auth/register", &[Method::GET, Method::POST])];
+
     pub fn router() -> Router<AppState> {
         Router::new().route(
             "/auth/register",
-            get(RegisterController::index).post(RegisterController::register),
+            get(RegisterController::index).merge(
+                post(RegisterController::register)
+                    .route_layer(from_fn_with_state(RegisterView::FORM, form_page)),
+            ),
+        )
+    }
+
+    /// Describes the register form for the OpenAPI document.
+    pub fn openapi(doc: OpenApi) -> OpenApi {
+        doc.operation(
+            Method::POST,
+            "/auth/register",
+            Operation::new("Register a new user")
+                .description("Sent by the register form, along with its `csrf_token`.")
+                .tag("auth")
+                .body::<RegisterUser>("application/x-www-form-urlencoded")
+                .empty_response(StatusCode::SEE_OTHER, "to confirm the email of the user")
+                .errors(&[
+                    Error::InvalidCsrfToken,
+                    Error::Database(nohead_rs_db::Error::ValidationError(Default::default())),
+                    Error::Database(nohead_rs_db::Error::UniqueConstraint(vec![(
+                        "email".into(),
+                        String::new(),
+                    )])),
+                ]),
         )
     }
 
@@ -37,27 +73,29 @@ impl RegisterController {
         flash: Flash,
         State(app_state): State<AppState>,
         Extension(mut jobs): Extension<WorkerStorage<EmailPayload>>,
-        Form(form): Form<RegisterUser>,
+        ValidForm(form): ValidForm<RegisterUser>,
     ) -> Result<(Flash, Redirect), Error> {
         let mut tx = transaction(&app_state.db_pool).await?;
         let user = User::create(form, &mut *tx).await?;
         let register_token = RegisterToken::create(user.id, &mut *tx).await?;
+        // Render the email before committing, so that nobody is registered without one
+        let email = AuthMailer::send_confirmation(
+            &app_state.email_client,
+            &app_state.email_templates,
+            &user.email,
+            &register_token.register_token,
+        )?;
         tx.commit()
             .await
             .map_err(|e| Error::Database(nohead_rs_db::Error::DatabaseError(e)))?;
 
         // Send the confirmation email in a background job
-        jobs.push(AuthMailer::send_confirmation(
-            &app_state.email_client,
-            &app_state.config,
-            &user.email,
-            &register_token.register_token,
-        ))
-        .await
-        .map_err(|e| {
-            tracing::error!("failed to send confirmation email: {:?}", e);
-        })
-        .ok();
+        jobs.push(email)
+            .await
+            .map_err(|e| {
+                tracing::error!("failed to send confirmation email: {:?}", e);
+            })
+            .ok();
 
         // Redirect to the confirmation page
         Ok((
@@ -1,11 +1,22 @@
-use axum::{Form, Router, extract::State, response::Redirect, routing::get};
+use axum::{
+    Extension, Form, Router,
+    extract::State,
+    http::Method,
+    response::Redirect,
+    routing::{get, post},
+};
 use nohead_rs_db::{
+    Validate as _,
     entities::{
-        register_token::{RegisterToken, RegisterTokenValidate},
+        register_token::{
+            RegisterToken, RegisterTokenResend, RegisterTokenValidate, RegisterTokenVerification,
+        },
         user::{User, UserStatus},
     },
     transaction,
 };
+use nohead_rs_mailer::{EmailPayload, auth::AuthMailer};
+use nohead_rs_worker::{Storage, WorkerStorage};
 
 use crate::{
     error::Error,
@@ -14,6 +25,7 @@ use crate::{
         auth::AuthSession,
         flash::{Flash, IncomingFlashes},
     },
+    router::Routes,
     state::AppState,
     views::auth::register_confirm::RegisterConfirmView,
 };
@@ -21,11 +33,21 @@ use crate::{
 pub struct RegisterConfirmController;
 
 impl RegisterConfirmController {
+    pub const ROUTES: Routes = &[
+        ("/auth/register/confirm", &[Method::GET, Method::POST]),
+        ("/auth/register/resend", &[Method::POST]),
+    ];
+
     pub fn router() -> Router<AppState> {
-        Router::new().route(
-            "/auth/register/confirm",
-            get(RegisterConfirmController::index).post(RegisterConfirmController::verify),
-        )
+        Router::new()
+            .route(
+                "/auth/register/confirm",
+                get(RegisterConfirmController::index).post(RegisterConfirmController::verify),
+            )
+            .route(
+                "/auth/register/resend",
+                post(RegisterConfirmController::resend),
+            )
     }
 
     pub async fn index(
@@ -42,10 +64,22 @@ impl RegisterConfirmController {
         Form(form): Form<RegisterTokenValidate>,
     ) -> Result<(Flash, Redirect), Error> {
         let mut tx = transaction(&state.db_pool).await?;
-        // Get the user id by the user input register token
-        let user_id = RegisterToken::try_get_user_id_by_register_token(form, &mut *tx)
-            .await?
-            .ok_or_else(|| Error::InvalidRegisterToken)?;
+        // Check the user input register token, counting wrong guesses
+        let verification = RegisterToken::verify(form, &mut tx).await?;
+        let user_id = match verification {
+            RegisterTokenVerification::Verified(user_id) => user_id,
+            rejected => {
+                // Commit so that the failed attempt is counted
+                tx.commit().await.map_err(|e| Error::Database(e.into()))?;
+                return Err(match rejected {
+                    RegisterTokenVerification::Expired => Error::ExpiredRegisterToken,
+                    RegisterTokenVerification::TooManyAttempts => {
+                        Error::TooManyRegisterTokenAttempts
+                    }
+                    _ => Error::InvalidRegisterToken,
+                });
+            }
+        };
         // Update the user status to from pending to confirmed
         let user = User::update_status(user_id, UserStatus::Confirmed, &mut *tx).await?;
         // Commit the transaction
@@ -56,12 +90,61 @@ impl RegisterConfirmController {
             .login(&user)
             .await
             .map_err(|e| Error::Unexpected(e.into()))?;
-        //FIX: Create a better error to allow user
-        //to retry
 
         Ok((
             flash.success("Welcome! You are now registered"),
             Redirect::to("/"),
         ))
     }
+
+    pub async fn resend(
+        flash: Flash,
+        State(state): State<AppState>,
+        Extension(mut jobs): Extension<WorkerStorage<EmailPayload>>,
+        Form(form): Form<RegisterTokenResend>,
+    ) -> Result<(Flash, Redirect), Error> {
+        form.validate().map_err(nohead_rs_db::Error::from)?;
+
+        // Only send a new code to users that still need to confirm, but always respond the
+        // same way so the form can't be used to find out which emails are registered.
+        if let Some(user) = User::try_get_by_email(&form.email, &state.db_pool)
+            .await?
+            .filter(|user| matches!(user.status, UserStatus::Pending))
+        {
+            let mut tx = transaction(&state.db_pool).await?;
+            // Requesting a new register token invalidates the previous one, as long as it
+            // wasn't replaced too often already
+            let email = match RegisterToken::reissue(user.id, &mut tx).await? {
+                Some(register_token) => Some(AuthMailer::send_confirmation(
+                    &state.email_client,
+                    &state.email_templates,
+                    &user.email,
+                    &register_token.register_token,
+                )?),
+                None => None,
+            };
+            // Commit either way so that the resend is counted
+            tx.commit().await.map_err(|e| Error::Database(e.into()))?;
+
+            match email {
+                // Send the confirmation email in a background job
+                Some(email) => jobs
+                    .push(email)
+                    .await
+                    .map_err(|e| {
+                        tracing::error!("failed to send confirmation email: {:?}", e);
+                    })
+                    .ok(),
+                None => {
+                    tracing::warn!(user_id = user.id, "too many register token resends");
+                    None
+                }
+            };
+        }
+
+        Ok((
+            flash.info("please check your email for a new confirmation code"),
+            Redirect::to("/auth/register/confirm"),
+        ))
+    }
 }
@@ -1,9 +1,10 @@
-use axum::{Router, routing::get};
+use axum::{Router, http::Method, routing::get};
 
 use crate::{
     error::Result,
     initializers::view_engine::engine::{View, ViewEngine},
     middlewares::flash::IncomingFlashes,
+    router::Routes,
     state::AppState,
     views::home::HomeView,
 };
@@ -11,6 +12,8 @@ use crate::{
 pub struct HomeController;
 
 impl HomeController {
+    pub const ROUTES: Routes = &[("/", &[Method::GET])];
+
     pub fn router() -> Router<AppState> {
         Router::new().route("/", get(HomeController::index))
     }
@@ -0,0 +1,122 @@
+use axum::{
+    Extension, Router,
+    extract::{Path, State},
+    http::Method,
+    routing::get,
+};
+use nohead_rs_mailer::EmailPayload;
+use nohead_rs_worker::{WorkerStorage, queued_emails};
+
+use crate::{
+    error::{Error, Result},
+    initializers::view_engine::engine::{View, ViewEngine},
+    middlewares::flash::IncomingFlashes,
+    router::Routes,
+    state::AppState,
+    views::mailbox::MailboxView,
+};
+
+/// Shows the emails the app sent or queued, and previews its email templates.
+///
+/// It is only mounted in development, see [`crate::router::init_router`], where the emails are
+/// written to files rather than sent.
+pub struct MailboxController;
+
+impl MailboxController {
+    pub const ROUTES: Routes = &[
+        ("/_dev/mailbox", &[Method::GET]),
+        ("/_dev/mailbox/sent/{id}", &[Method::GET]),
+        ("/_dev/mailbox/queued/{id}", &[Method::GET]),
+        ("/_dev/mailbox/previews/{*name}", &[Method::GET]),
+    ];
+
+    pub fn router() -> Router<AppState> {
+        Router::new()
+            .route("/_dev/mailbox", get(MailboxController::index))
+            .route("/_dev/mailbox/sent/{id}", get(MailboxController::sent))
+            .route("/_dev/mailbox/queued/{id}", get(MailboxController::queued))
+            .route(
+                "/_dev/mailbox/previews/{*name}",
+                get(MailboxController::preview),
+            )
+    }
+
+    pub async fn index(
+        v: ViewEngine<View>,
+        flashes: IncomingFlashes,
+        State(app_state): State<AppState>,
+        Extension(jobs): Extension<WorkerStorage<EmailPayload>>,
+    ) -> Result<(IncomingFlashes, MailboxView)> {
+        let sent = app_state.email_client.sent().await?;
+        let queued = queued_emails(&jobs).await?;
+        let previews = nohead_rs_mailer::previews(&app_state.email_templates)
+            .into_iter()
+            .map(|preview| preview.name)
+            .collect();
+
+        Ok((
+            flashes.clone(),
+            MailboxView::Index(v, sent, queued, previews, flashes),
+        ))
+    }
+
+    pub async fn sent(
+        v: ViewEngine<View>,
+        flashes: IncomingFlashes,
+        Path(id): Path<String>,
+        State(app_state): State<AppState>,
+    ) -> Result<(IncomingFlashes, MailboxView)> {
+        let sent = app_state
+            .email_client
+            .sent()
+            .await?
+            .into_iter()
+            .find(|sent| sent.id == id)
+            .ok_or(Error::NotFound)?;
+
+        Ok((
+            flashes.clone(),
+            MailboxView::Email(v, "Sent", sent.email, flashes),
+        ))
+    }
+
+    pub async fn queued(
+        v: ViewEngine<View>,
+        flashes: IncomingFlashes,
+        Path(id): Path<String>,
+        Extension(jobs): Extension<WorkerStorage<EmailPayload>>,
+    ) -> Result<(IncomingFlashes, MailboxView)> {
+        let queued = queued_emails(&jobs)
+            .await?
+            .into_iter()
+            .find(|queued| queued.id == id)
+            .ok_or(Error::NotFound)?;
+
+        Ok((
+            flashes.clone(),
+            MailboxView::Email(v, "Queued", queued.email, flashes),
+        ))
+    }
+
+    /// Renders a template with the sample data of its mailer, again on every request so that
+    /// changes to the template show up right away.
+    pub async fn preview(
+        v: ViewEngine<View>,
+        flashes: IncomingFlashes,
+        Path(name): Path<String>,
+        State(app_state): State<AppState>,
+    ) -> Result<(IncomingFlashes, MailboxView)> {
+        let preview = nohead_rs_mailer::previews(&app_state.email_templates)
+            .into_iter()
+            .find(|preview| preview.name == name)
+            .ok_or(Error::NotFound)?;
+        let email = app_state
+            .email_templates
+            .render(preview.name, &preview.context)?;
+
+        Ok((
+            flashes.clone(),
+            MailboxView::Preview(v, preview, email, flashes),
+        ))
+    }
+}
@@ -1,22 +1,53 @@
 use async_trait::async_trait;
 use axum::{
     Form, Router,
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::{Method, StatusCode},
+    middleware::from_fn_with_state,
     response::{IntoResponse, Redirect},
+    routing::MethodRouter,
 };
-use nohead_rs_db::{DeserializeOwned, Validate};
+use nohead_rs_db::{
+    DeserializeOwned, Validate,
+    entities::permission::Permission,
+    pagination::{ListParams, Page},
+    schema::Schema,
+};
+use serde::Serialize;
 
 use crate::{
+    error::Error,
+    forms::ValidForm,
     initializers::view_engine::engine::{View, ViewEngine},
-    middlewares::flash::{Flash, IncomingFlashes},
+    middlewares::{
+        auth::{CurrentUser, permission_required},
+        flash::{Flash, IncomingFlashes},
+    },
+    negotiate::{Negotiated, ResponseFormat},
+    openapi::{OpenApi, Operation},
+    router::Routes,
     state::AppState,
 };
 
+pub mod api_tokens;
 pub mod auth;
 pub mod home;
+pub mod mailbox;
+pub mod openapi;
 pub mod ping;
 pub mod todos;
 
+/// The actions a [`Controller`] provides handlers for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    ReadAll,
+    Create,
+    CreateBatch,
+    ReadOne,
+    Update,
+    Delete,
+}
+
 /// ------------------------------------------------------------------------
 /// # A generic Controller trait for implenting a CRUD router for a model
 /// ------------------------------------------------------------------------
@@ -30,10 +61,15 @@ pub mod todos;
 /// #[async_trait]
 /// impl Controller for ExampleController {
 ///     type Id = i64;
+///     type Entity = Example;
 ///     type View = ExampleView;
 ///     type EntityChangeset = ExampleChangeset;
 ///     type Error = ExampleError;
-///     
+///
+///     const ROUTES: Routes = &[
+///         ("/", &[Method::GET, Method::POST]),
+///         ("/:id", &[Method::GET, Method::PUT, Method::DELETE]),
+///     ];
 ///
 ///     fn router() -> Router<AppState> {
 ///         Router::new()
@@ -53,58 +89,184 @@ pub mod todos;
 ///         }
 ///         // ...other methods
 /// ```
+///
+/// ## HTML and JSON
+///
+/// Every action answers with a [`Negotiated`] response, so that the same routes render the
+/// views for browsers and serialize the entity for JSON clients, see [`crate::negotiate`].
+///
+/// ## OpenAPI
+///
+/// Controllers whose entity and changeset derive `Schema` describe their actions for the
+/// OpenAPI document of the JSON API with [`Controller::openapi`], see [`crate::openapi`].
+///
+/// ## Permissions
+///
+/// Declare the permission each action requires with [`Controller::permission`] and wrap
+/// the action's handler with [`Controller::authorize`] in the router:
+///
+/// ```rust
+/// fn permission(action: Action) -> Option<&'static str> {
+///     match action {
+///         Action::Delete => Some("examples.delete"),
+///         _ => None,
+///     }
+/// }
+///
+/// fn router() -> Router<AppState> {
+///     Router::new().route(
+///         "/examples/{id}",
+///         Self::authorize(Action::Delete, delete(Self::delete)),
+///     )
+/// }
+/// ```
 /// ------------------------------------------------------------------------
 
 #[async_trait]
 pub trait Controller {
     type Id: PartialOrd;
+    type Entity: Serialize;
     type View: IntoResponse;
     type EntityChangeset: Validate + DeserializeOwned;
     type Error: IntoResponse;
 
+    /// The paths and methods of [`Controller::router`], see [`crate::router::RouteTable`]
+    const ROUTES: Routes;
+
     /// Produces a app router with all methods for the Controller
     fn router() -> Router<AppState>;
 
-    /// Index handler to list all records
+    /// The permission a user needs to perform an action, `None` lets any user through
+    fn permission(_action: Action) -> Option<&'static str> {
+        None
+    }
+
+    /// Guards the handlers of a route with the permission declared for the action
+    fn authorize(action: Action, handler: MethodRouter<AppState>) -> MethodRouter<AppState> {
+        match Self::permission(action) {
+            Some(permission) => handler.route_layer(from_fn_with_state(
+                Permission::from(permission),
+                permission_required,
+            )),
+            None => handler,
+        }
+    }
+
+    /// Describes the actions of the controller, as the JSON API answers them under `path`
+    fn openapi(path: &str, doc: OpenApi) -> OpenApi
+    where
+        Self::Entity: Schema,
+        Self::EntityChangeset: Schema,
+    {
+        let name = Self::Entity::name();
+        let record_path = format!("{path}/{{id}}");
+        let operation = |action: Action, summary: String| {
+            let operation = Operation::new(summary).tag(name).bearer_auth();
+            match Self::permission(action) {
+                Some(permission) => operation
+                    .description(format!("Needs the `{permission}` permission."))
+                    .errors(&[Error::Forbidden]),
+                None => operation,
+            }
+        };
+        let not_found = || Error::Database(nohead_rs_db::Error::NoRecordFound);
+        let invalid = || Error::Database(nohead_rs_db::Error::ValidationError(Default::default()));
+
+        doc.operation(
+            Method::GET,
+            path,
+            operation(Action::ReadAll, format!("List a page of {name} records"))
+                .list_params()
+                .page_response::<Self::Entity>(StatusCode::OK, "the page of records"),
+        )
+        .operation(
+            Method::POST,
+            path,
+            operation(Action::Create, format!("Create a {name}"))
+                .body::<Self::EntityChangeset>("application/json")
+                .response::<Self::Entity>(StatusCode::CREATED, "the new record")
+                .errors(&[invalid()]),
+        )
+        .operation(
+            Method::GET,
+            &record_path,
+            operation(Action::ReadOne, format!("Load a {name}"))
+                .path_param("id")
+                .response::<Self::Entity>(StatusCode::OK, "the record")
+                .errors(&[not_found()]),
+        )
+        .operation(
+            Method::PUT,
+            &record_path,
+            operation(Action::Update, format!("Update a {name}"))
+                .path_param("id")
+                .body::<Self::EntityChangeset>("application/json")
+                .response::<Self::Entity>(StatusCode::OK, "the updated record")
+                .errors(&[not_found(), invalid()]),
+        )
+        .operation(
+            Method::DELETE,
+            &record_path,
+            operation(Action::Delete, format!("Delete a {name}"))
+                .path_param("id")
+                .empty_response(StatusCode::NO_CONTENT, "the record was deleted")
+                .errors(&[not_found()]),
+        )
+    }
+
+    /// Index handler to list a page of records
     async fn read_all(
         v: ViewEngine<View>,
+        format: ResponseFormat,
         flashes: IncomingFlashes,
+        current_user: CurrentUser,
+        Query(params): Query<ListParams>,
         State(app_state): State<AppState>,
-    ) -> Result<(IncomingFlashes, Self::View), Self::Error>;
+    ) -> Result<(IncomingFlashes, Negotiated<Self::View, Page<Self::Entity>>), Self::Error>;
 
-    /// Create handler to create a new record
+    /// Create handler to create a new record, JSON clients get `201 Created`
     async fn create(
         flash: Flash,
+        format: ResponseFormat,
+        current_user: CurrentUser,
         State(app_state): State<AppState>,
-        Form(record): Form<Self::EntityChangeset>,
-    ) -> Result<(Flash, Redirect), Self::Error>;
+        ValidForm(record): ValidForm<Self::EntityChangeset>,
+    ) -> Result<(Flash, Negotiated<Redirect, Self::Entity>), Self::Error>;
 
     async fn create_batch(
         flash: Flash,
+        format: ResponseFormat,
+        current_user: CurrentUser,
         State(app_state): State<AppState>,
         Form(records): Form<Vec<Self::EntityChangeset>>,
-    ) -> Result<(Flash, Redirect), Self::Error>;
+    ) -> Result<(Flash, Negotiated<Redirect, Vec<Self::Entity>>), Self::Error>;
 
     /// Show handler to display a single record
     async fn read_one(
         v: ViewEngine<View>,
+        format: ResponseFormat,
         flashes: IncomingFlashes,
+        current_user: CurrentUser,
         Path(id): Path<Self::Id>,
         State(app_state): State<AppState>,
-    ) -> Result<(IncomingFlashes, Self::View), Self::Error>;
+    ) -> Result<(IncomingFlashes, Negotiated<Self::View, Self::Entity>), Self::Error>;
 
     /// Update handler to update a single record
     async fn update(
         flash: Flash,
+        format: ResponseFormat,
+        current_user: CurrentUser,
         Path(id): Path<Self::Id>,
         State(app_state): State<AppState>,
-        form: Form<Self::EntityChangeset>,
-    ) -> Result<(Flash, Redirect), Self::Error>;
+        form: ValidForm<Self::EntityChangeset>,
+    ) -> Result<(Flash, Negotiated<Redirect, Self::Entity>), Self::Error>;
 
-    /// Delete handler to delete a single record
+    /// Delete handler to delete a single record, JSON clients get `204 No Content`
     async fn delete(
         flash: Flash,
+        format: ResponseFormat,
+        current_user: CurrentUser,
         Path(id): Path<Self::Id>,
         State(app_state): State<AppState>,
-    ) -> Result<(Flash, Redirect), Self::Error>;
+    ) -> Result<(Flash, Negotiated<Redirect, ()>), Self::Error>;
 }
@@ -0,0 +1,43 @@
+use axum::{Json, Router, extract::State, http::Method, routing::get};
+use nohead_rs_config::Environment;
+use serde_json::Value;
+
+use crate::{
+    error::{Error, Result},
+    initializers::view_engine::engine::{View, ViewEngine},
+    openapi,
+    router::Routes,
+    state::AppState,
+    views::openapi::OpenApiView,
+};
+
+const SPEC_URL: &str = "/api/openapi.json";
+
+/// Serves the OpenAPI document of the JSON API, see [`crate::openapi`].
+pub struct OpenApiController;
+
+impl OpenApiController {
+    pub const ROUTES: Routes = &[(SPEC_URL, &[Method::GET]), ("/api/docs", &[Method::GET])];
+
+    pub fn router() -> Router<AppState> {
+        Router::new()
+            .route(SPEC_URL, get(OpenApiController::spec))
+            .route("/api/docs", get(OpenApiController::docs))
+    }
+
+    pub async fn spec() -> Json<Value> {
+        Json(openapi::document())
+    }
+
+    /// Browses the document with Scalar, which is only offered in development.
+    pub async fn docs(
+        State(app_state): State<AppState>,
+        v: ViewEngine<View>,
+    ) -> Result<OpenApiView> {
+        if app_state.env != Environment::Development {
+            return Err(Error::NotFound);
+        }
+
+        Ok(OpenApiView::Docs(v, SPEC_URL))
+    }
+}
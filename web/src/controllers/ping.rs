@@ -1,10 +1,16 @@
-use axum::{Router, http::StatusCode, routing::get};
+use axum::{
+    Router,
+    http::{Method, StatusCode},
+    routing::get,
+};
 
-use crate::state::AppState;
+use crate::{router::Routes, state::AppState};
 
 pub struct PingController;
 
 impl PingController {
+    pub const ROUTES: Routes = &[("/ping", &[Method::GET])];
+
     pub fn router() -> Router<AppState> {
         Router::new().route("/ping", get(PingController::ping))
     }
@@ -1,24 +1,35 @@
 use async_trait::async_trait;
 use axum::{
     Form, Router,
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::Method,
+    middleware::from_fn_with_state,
     response::Redirect,
-    routing::{get, post},
+    routing::{delete, get, post, put},
 };
-use nohead_rs_db::entities::{
-    Entity as _,
-    todo::{Todo, TodoChangeset},
+use nohead_rs_db::{
+    entities::{
+        OwnedEntity as _,
+        todo::{Todo, TodoChangeset},
+    },
+    pagination::{ListParams, Page},
 };
 
 use crate::{
     error::Error,
+    forms::{ValidForm, form_page},
     initializers::view_engine::engine::{View, ViewEngine},
-    middlewares::flash::{Flash, IncomingFlashes},
+    middlewares::{
+        auth::CurrentUser,
+        flash::{Flash, IncomingFlashes},
+    },
+    negotiate::{Negotiated, ResponseFormat},
+    router::Routes,
     state::AppState,
     views::todos::TodoView,
 };
 
-use super::Controller;
+use super::{Action, Controller};
 
 pub struct TodoController;
 
@@ -26,87 +37,147 @@ pub struct TodoController;
 impl Controller for TodoController {
     type Id = i64;
 
+    type Entity = Todo;
+
     type View = TodoView;
 
     type EntityChangeset = TodoChangeset;
 
     type Error = Error;
 
+    const ROUTES: Routes = &[
+        ("/todos", &[Method::GET, Method::POST]),
+        ("/todos/batch", &[Method::POST]),
+        ("/todos/{id}", &[Method::GET, Method::PUT, Method::DELETE]),
+    ];
+
     fn router() -> Router<AppState> {
         Router::new()
-            .route("/todos", get(Self::read_all).post(Self::create))
-            .route("/todos/batch", post(Self::create_batch))
+            .route(
+                "/todos",
+                Self::authorize(Action::ReadAll, get(Self::read_all)).merge(Self::authorize(
+                    Action::Create,
+                    post(Self::create)
+                        .route_layer(from_fn_with_state(TodoView::CREATE_FORM, form_page)),
+                )),
+            )
+            .route(
+                "/todos/batch",
+                Self::authorize(Action::CreateBatch, post(Self::create_batch)),
+            )
             .route(
                 "/todos/{id}",
-                get(Self::read_one).put(Self::update).delete(Self::delete),
+                Self::authorize(Action::ReadOne, get(Self::read_one))
+                    .merge(Self::authorize(
+                        Action::Update,
+                        put(Self::update)
+                            .route_layer(from_fn_with_state(TodoView::UPDATE_FORM, form_page)),
+                    ))
+                    .merge(Self::authorize(Action::Delete, delete(Self::delete))),
             )
     }
 
+    fn permission(action: Action) -> Option<&'static str> {
+        match action {
+            Action::ReadAll => Some("todos.read_all"),
+            Action::Create | Action::CreateBatch => Some("todos.create"),
+            Action::ReadOne => Some("todos.read_one"),
+            Action::Update => Some("todos.update"),
+            Action::Delete => Some("todos.delete"),
+        }
+    }
+
     async fn read_all(
         v: ViewEngine<View>,
+        format: ResponseFormat,
         flashes: IncomingFlashes,
+        CurrentUser(user): CurrentUser,
+        Query(params): Query<ListParams>,
         State(app_state): State<AppState>,
-    ) -> Result<(IncomingFlashes, Self::View), Self::Error> {
-        let todos = Todo::load_all(&app_state.db_pool).await?;
+    ) -> Result<(IncomingFlashes, Negotiated<Self::View, Page<Self::Entity>>), Self::Error> {
+        let todos = Todo::load_page(&params, user.id, &app_state.db_read_pool).await?;
 
-        Ok((flashes.clone(), TodoView::Index(v, todos, flashes)))
+        Ok((
+            flashes.clone(),
+            Negotiated::new(format, TodoView::Index(v, todos.clone(), flashes), todos),
+        ))
     }
 
     async fn create(
         flash: Flash,
+        format: ResponseFormat,
+        CurrentUser(user): CurrentUser,
         State(app_state): State<AppState>,
-        Form(record): Form<Self::EntityChangeset>,
-    ) -> Result<(Flash, Redirect), Self::Error> {
-        let todo = Todo::create(record, &app_state.db_pool).await?;
+        ValidForm(record): ValidForm<Self::EntityChangeset>,
+    ) -> Result<(Flash, Negotiated<Redirect, Self::Entity>), Self::Error> {
+        let todo = Todo::create(record, user.id, &app_state.db_pool).await?;
+        let location = format!("/todos/{}", todo.id);
 
         Ok((
             flash.success("✅ created new todo"),
-            Redirect::to(&format!("/todos/{}", todo.id)),
+            Negotiated::new(format, Redirect::to(&location), todo).created(&location),
         ))
     }
 
     async fn create_batch(
         flash: Flash,
+        format: ResponseFormat,
+        CurrentUser(user): CurrentUser,
         State(app_state): State<AppState>,
         Form(records): Form<Vec<Self::EntityChangeset>>,
-    ) -> Result<(Flash, Redirect), Self::Error> {
-        let _records = Todo::create_batch(records, &app_state.db_pool).await?;
+    ) -> Result<(Flash, Negotiated<Redirect, Vec<Self::Entity>>), Self::Error> {
+        let todos = Todo::create_batch(records, user.id, &app_state.db_pool).await?;
 
-        Ok((flash.success("✅ created todos"), Redirect::to("/todos")))
+        Ok((
+            flash.success("✅ created todos"),
+            Negotiated::new(format, Redirect::to("/todos"), todos).created("/todos"),
+        ))
     }
 
     async fn read_one(
         v: ViewEngine<View>,
+        format: ResponseFormat,
         flashes: IncomingFlashes,
+        CurrentUser(user): CurrentUser,
         Path(id): Path<Self::Id>,
         State(app_state): State<AppState>,
-    ) -> Result<(IncomingFlashes, Self::View), Self::Error> {
-        let todo = Todo::load(id, &app_state.db_pool).await?;
+    ) -> Result<(IncomingFlashes, Negotiated<Self::View, Self::Entity>), Self::Error> {
+        let todo = Todo::load(id, user.id, &app_state.db_read_pool).await?;
 
-        Ok((flashes.clone(), TodoView::Show(v, todo, flashes)))
+        Ok((
+            flashes.clone(),
+            Negotiated::new(format, TodoView::Show(v, todo.clone(), flashes), todo),
+        ))
     }
 
     async fn update(
         flash: Flash,
+        format: ResponseFormat,
+        CurrentUser(user): CurrentUser,
         Path(id): Path<Self::Id>,
         State(app_state): State<AppState>,
-        Form(form): Form<Self::EntityChangeset>,
-    ) -> Result<(Flash, Redirect), Self::Error> {
-        let todo = Todo::update(id, form, &app_state.db_pool).await?;
+        ValidForm(form): ValidForm<Self::EntityChangeset>,
+    ) -> Result<(Flash, Negotiated<Redirect, Self::Entity>), Self::Error> {
+        let todo = Todo::update(id, form, user.id, &app_state.db_pool).await?;
 
         Ok((
             flash.success("✅ updated todo"),
-            Redirect::to(&format!("/todos/{}", todo.id)),
+            Negotiated::new(format, Redirect::to(&format!("/todos/{}", todo.id)), todo),
         ))
     }
 
     async fn delete(
         flash: Flash,
+        format: ResponseFormat,
+        CurrentUser(user): CurrentUser,
         Path(id): Path<Self::Id>,
         State(app_state): State<AppState>,
-    ) -> Result<(Flash, Redirect), Self::Error> {
-        let _todo = Todo::delete(id, &app_state.db_pool).await?;
+    ) -> Result<(Flash, Negotiated<Redirect, ()>), Self::Error> {
+        let _todo = Todo::delete(id, user.id, &app_state.db_pool).await?;
 
-        Ok((flash.info("deleted todo"), Redirect::to("/todos")))
+        Ok((
+            flash.info("deleted todo"),
+            Negotiated::new(format, Redirect::to("/todos"), ()).no_content(),
+        ))
     }
 }
@@ -1,5 +1,5 @@
 use axum::{
-    extract::rejection::JsonRejection,
+    extract::rejection::{FormRejection, JsonRejection},
     http::{
         StatusCode,
         header::{InvalidHeaderName, InvalidHeaderValue},
@@ -10,7 +10,7 @@ use axum::{
 use color_eyre::eyre;
 use tracing::error;
 
-use crate::initializers::view_engine;
+use crate::{forms::FieldErrors, initializers::view_engine};
 
 pub type Result<T, E = Error> = color_eyre::Result<T, E>;
 
@@ -21,11 +21,52 @@ pub enum Error {
     /// Return a `401 Unauthorized` response on an invalid register token.
     #[error("invalid register token")]
     InvalidRegisterToken,
+    /// Expired register token
+    ///
+    /// Return a `410 Gone` response on an expired register token.
+    #[error("expired register token")]
+    ExpiredRegisterToken,
+    /// Too many register token guesses
+    ///
+    /// Return a `429 Too Many Requests` response once the register token has been invalidated
+    /// after too many wrong guesses.
+    #[error("too many register token attempts")]
+    TooManyRegisterTokenAttempts,
+    /// Invalid password reset token
+    ///
+    /// Return a `401 Unauthorized` response on an invalid or expired password reset token.
+    #[error("invalid password reset token")]
+    InvalidPasswordResetToken,
+    /// Unknown page
+    ///
+    /// Return a `404 Not Found` response when no route matches the request.
+    #[error("page not found")]
+    NotFound,
     /// Unauthenticated user
     ///
     /// Return a `401 Unauthorized` response on an unauthenticated user.
     #[error("unauthenticated user")]
     Unauthenticated,
+    /// Unconfirmed user
+    ///
+    /// Return a `403 Forbidden` response when a user that has not confirmed their email logs in.
+    #[error("user has not confirmed their email")]
+    PendingUser,
+    /// Suspended user
+    ///
+    /// Return a `403 Forbidden` response when a suspended user logs in.
+    #[error("user has been suspended")]
+    SuspendedUser,
+    /// Unauthorized user
+    ///
+    /// Return a `403 Forbidden` response when a user lacks the permission for an action.
+    #[error("user does not have the required permission")]
+    Forbidden,
+    /// Missing or wrong CSRF token
+    ///
+    /// Return a `403 Forbidden` response when a form is sent without the CSRF token of the session.
+    #[error("invalid CSRF token")]
+    InvalidCsrfToken,
     /// Could not render template
     ///
     /// Return `500 Internal Server Error` on a template rendering error.
@@ -56,6 +97,9 @@ pub enum Error {
     #[error(transparent)]
     JsonRejection(#[from] JsonRejection),
 
+    #[error(transparent)]
+    FormRejection(#[from] FormRejection),
+
     #[error(transparent)]
     InvalidHeaderValue(#[from] InvalidHeaderValue),
 
@@ -72,9 +116,18 @@ pub enum Error {
 }
 
 impl Error {
-    fn status_code(&self) -> StatusCode {
+    pub(crate) fn status_code(&self) -> StatusCode {
         match self {
-            Error::Unauthenticated | Error::InvalidRegisterToken => StatusCode::UNAUTHORIZED,
+            Error::Unauthenticated
+            | Error::InvalidRegisterToken
+            | Error::InvalidPasswordResetToken => StatusCode::UNAUTHORIZED,
+            Error::PendingUser
+            | Error::SuspendedUser
+            | Error::Forbidden
+            | Error::InvalidCsrfToken => StatusCode::FORBIDDEN,
+            Error::NotFound => StatusCode::NOT_FOUND,
+            Error::ExpiredRegisterToken => StatusCode::GONE,
+            Error::TooManyRegisterTokenAttempts => StatusCode::TOO_MANY_REQUESTS,
             Error::ViewEngine(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Error::Database(nohead_rs_db::Error::NoRecordFound) => StatusCode::NOT_FOUND,
             Error::Database(nohead_rs_db::Error::UniqueConstraint(_)) => {
@@ -89,103 +142,169 @@ impl Error {
             Error::Database(nohead_rs_db::Error::PasswordHashError(_)) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
-            Error::Mailer(nohead_rs_mailer::Error::Request(_)) => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::Database(
+                nohead_rs_db::Error::MigrateError(_)
+                | nohead_rs_db::Error::IrreversibleMigration(_)
+                | nohead_rs_db::Error::UnknownMigrations(_)
+                | nohead_rs_db::Error::IoError(_),
+            ) => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::Mailer(
+                nohead_rs_mailer::Error::Request(_)
+                | nohead_rs_mailer::Error::Config(_)
+                | nohead_rs_mailer::Error::Message(_)
+                | nohead_rs_mailer::Error::Build(_)
+                | nohead_rs_mailer::Error::Smtp(_)
+                | nohead_rs_mailer::Error::Io(_)
+                | nohead_rs_mailer::Error::Template(_)
+                | nohead_rs_mailer::Error::Text(_),
+            ) => StatusCode::INTERNAL_SERVER_ERROR,
             Error::Mailer(nohead_rs_mailer::Error::Validation(_)) => {
                 StatusCode::UNPROCESSABLE_ENTITY
             }
             Error::Worker(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Error::Http(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Error::JSON(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            Error::JsonRejection(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::JsonRejection(err) => err.status(),
+            Error::FormRejection(err) => err.status(),
             Error::InvalidHeaderValue(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Error::InvalidHeaderName(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Error::InvalidMethod(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Error::Unexpected(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
-}
 
-impl IntoResponse for Error {
-    fn into_response(self) -> Response {
+    /// What to tell the user about the error.
+    ///
+    /// Server errors all get the same message, their cause is only logged and, in development,
+    /// shown as the [`ErrorReport::details`].
+    pub(crate) fn message(&self) -> String {
         match self {
-            Error::InvalidRegisterToken => {
-                // TODO: Return a invalid register token view here.
-                return (self.status_code(), "invalid register token".to_string()).into_response();
-            }
-            Error::Unauthenticated => {
-                // TODO: Return a not authenticated view here.
-                return (self.status_code(), "unauthenticated".to_string()).into_response();
-            }
-            Error::ViewEngine(ref err) => {
-                // TODO: Return a not found view here.
-                error!("an error occured while rendering a template: {:?}", err);
-                return (self.status_code(), err.to_string()).into_response();
+            Error::InvalidRegisterToken => "invalid register token".to_string(),
+            Error::ExpiredRegisterToken => "expired register token".to_string(),
+            Error::TooManyRegisterTokenAttempts => "too many register token attempts".to_string(),
+            Error::InvalidPasswordResetToken => "invalid password reset token".to_string(),
+            Error::NotFound => "page not found".to_string(),
+            Error::Unauthenticated => "unauthenticated".to_string(),
+            Error::PendingUser => "please confirm your email".to_string(),
+            Error::SuspendedUser => "account suspended".to_string(),
+            Error::Forbidden => "forbidden".to_string(),
+            Error::InvalidCsrfToken => "invalid CSRF token".to_string(),
+            Error::Database(nohead_rs_db::Error::NoRecordFound) => "no record found".to_string(),
+            Error::Database(nohead_rs_db::Error::UniqueConstraint(fields)) => {
+                let fields: Vec<&str> = fields.iter().map(|(field, _)| field.as_str()).collect();
+                format!("{} is already taken", fields.join(", "))
             }
+            Error::Database(nohead_rs_db::Error::ValidationError(err)) => err.to_string(),
+            Error::Mailer(nohead_rs_mailer::Error::Validation(err)) => err.to_string(),
+            Error::FormRejection(err) if err.status().is_client_error() => err.body_text(),
+            Error::JsonRejection(err) if err.status().is_client_error() => err.body_text(),
+            _ => "something went wrong".to_string(),
+        }
+    }
 
-            Error::Database(nohead_rs_db::Error::NoRecordFound) => {
-                // TODO: Return a not found view here.
-
-                return (self.status_code(), "no record found".to_string()).into_response();
-            }
-            Error::Database(nohead_rs_db::Error::UniqueConstraint(ref _err)) => {
-                // TODO: Return a unique constaint error view here.
-            }
-            Error::Database(nohead_rs_db::Error::ValidationError(ref err)) => {
-                // TODO: Return a validation error view here.
-                return (self.status_code(), err.to_string()).into_response();
+    /// Logs errors that are the server's fault, which the user can't do anything about.
+    fn log(&self) {
+        match self {
+            Error::ViewEngine(err) => {
+                error!("an error occured while rendering a template: {:?}", err);
             }
-            Error::Database(nohead_rs_db::Error::DatabaseError(ref err)) => {
+            Error::Database(nohead_rs_db::Error::DatabaseError(err)) => {
                 error!(
                     "an error occured while interacting with the database: {:?}",
                     err
                 );
-                return (self.status_code(), err.to_string()).into_response();
             }
-            Error::Database(nohead_rs_db::Error::PasswordHashError(ref err)) => {
-                // TODO: Return a password hash error view here.
+            Error::Database(nohead_rs_db::Error::PasswordHashError(err)) => {
                 error!("an error occured while hashing a password: {:?}", err);
             }
-            Error::Mailer(nohead_rs_mailer::Error::Request(ref err)) => {
+            Error::Database(
+                err @ (nohead_rs_db::Error::MigrateError(_)
+                | nohead_rs_db::Error::IrreversibleMigration(_)
+                | nohead_rs_db::Error::UnknownMigrations(_)
+                | nohead_rs_db::Error::IoError(_)),
+            ) => {
+                error!("an error occured while managing the database: {:?}", err);
+            }
+            Error::Mailer(nohead_rs_mailer::Error::Request(err)) => {
                 error!("an error occured while sending email request: {:?}", err);
             }
-            Error::Mailer(nohead_rs_mailer::Error::Validation(ref err)) => {
+            Error::Mailer(
+                err @ (nohead_rs_mailer::Error::Config(_)
+                | nohead_rs_mailer::Error::Message(_)
+                | nohead_rs_mailer::Error::Build(_)
+                | nohead_rs_mailer::Error::Smtp(_)
+                | nohead_rs_mailer::Error::Io(_)
+                | nohead_rs_mailer::Error::Template(_)
+                | nohead_rs_mailer::Error::Text(_)),
+            ) => {
+                error!("an error occured while sending an email: {:?}", err);
+            }
+            Error::Mailer(nohead_rs_mailer::Error::Validation(err)) => {
                 error!("invalid inputs to mailer: {:?}", err);
             }
-
-            Error::Worker(ref err) => {
+            Error::Worker(err) => {
                 error!("an error occured while interacting with worker: {:?}", err);
             }
-
-            Error::Http(ref err) => {
+            Error::Http(err) => {
                 error!("an error occured while interacting with http: {:?}", err);
             }
-
-            Error::JSON(ref err) => {
+            Error::JSON(err) => {
                 error!("an error occured while parsing json: {:?}", err);
             }
-
-            Error::JsonRejection(ref err) => {
+            Error::JsonRejection(err) if err.status().is_server_error() => {
                 error!("an error occured while parsing json: {:?}", err);
             }
-
-            Error::InvalidHeaderValue(ref err) => {
+            Error::InvalidHeaderValue(err) => {
                 error!("an error occured while parsing header value: {:?}", err);
             }
-
-            Error::InvalidHeaderName(ref err) => {
+            Error::InvalidHeaderName(err) => {
                 error!("an error occured while parsing header name: {:?}", err);
             }
-
-            Error::InvalidMethod(ref err) => {
+            Error::InvalidMethod(err) => {
                 error!("an error occured while parsing method: {:?}", err);
             }
-
-            Error::Unexpected(ref err) => {
+            Error::Unexpected(err) => {
                 error!("an internal server error occured: {:?}", err);
             }
+            _ => {}
         }
+    }
+}
+
+/// What a response knows about the [`Error`] it was made from.
+///
+/// It is added to the extensions of the response so that
+/// [`crate::middlewares::error_pages::error_pages`] can render it the way the request asked for,
+/// e.g. as an error page, an htmx fragment or a problem+json body.
+#[derive(Clone, Debug)]
+pub struct ErrorReport {
+    pub status: StatusCode,
+    /// The message for the user, see [`Error::message`].
+    pub message: String,
+    /// The error itself, only to be shown in development.
+    pub details: Option<String>,
+    /// What is wrong with each field of the form the request sent, see [`crate::forms`].
+    pub fields: Option<FieldErrors>,
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        self.log();
+
+        let report = ErrorReport {
+            status: self.status_code(),
+            message: self.message(),
+            details: Some(format!("{self:?}")),
+            fields: match &self {
+                Error::Database(err) => FieldErrors::of(err),
+                _ => None,
+            },
+        };
+
+        // Without the error pages middleware the message is sent as is.
+        let mut response = (report.status, report.message.clone()).into_response();
+        response.extensions_mut().insert(report);
 
-        // TODO: Return a default error view here.
-        self.status_code().into_response()
+        response
     }
 }
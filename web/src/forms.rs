@@ -0,0 +1,203 @@
+//! Forms that are shown again with what the user typed when what they sent is invalid.
+//!
+//! Handlers take their form as [`ValidForm`], which fails with the errors of every field when
+//! the form doesn't pass its `validator` checks. Those errors, and unique constraint failures of
+//! the handler itself, make it into the [`ErrorReport`] of the response. The [`form_page`] route
+//! layer then shows the page of the form again, with the submitted values and the errors of
+//! each field, or only the form for htmx requests.
+//!
+//! # Example
+//!
+//! ```rust
+//! Router::new().route(
+//!     "/auth/register",
+//!     post(RegisterController::register)
+//!         .route_layer(from_fn_with_state(RegisterView::FORM, form_page)),
+//! )
+//! ```
+//!
+//! The form template gets the submitted values as `form` and the errors as `errors`, e.g.
+//! `{{ form.email }}` and `{% for error in errors.email %}`. Password fields are never sent
+//! back.
+
+use std::collections::BTreeMap;
+
+use axum::{
+    Form, Json,
+    body::{Body, to_bytes},
+    extract::{FromRequest, FromRequestParts, RawPathParams, Request, State},
+    http::{HeaderMap, HeaderName, StatusCode, header::CONTENT_TYPE},
+    middleware::Next,
+    response::Response,
+};
+use nohead_rs_db::{DeserializeOwned, Validate};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::{
+    error::{Error, ErrorReport},
+    format,
+    initializers::view_engine::engine::{View, ViewEngine, ViewRenderer},
+    negotiate::ResponseFormat,
+};
+
+/// Form bodies are read to show them again, so they are capped like axum's `Form` extractor.
+const FORM_LIMIT: usize = 2 * 1024 * 1024;
+
+const HX_RETARGET: HeaderName = HeaderName::from_static("hx-retarget");
+const HX_RESWAP: HeaderName = HeaderName::from_static("hx-reswap");
+const HX_PUSH_URL: HeaderName = HeaderName::from_static("hx-push-url");
+
+/// The messages of what is wrong with each field of a form.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FieldErrors(BTreeMap<String, Vec<String>>);
+
+impl FieldErrors {
+    /// The field errors of a database error, if it is about what the user sent.
+    pub fn of(err: &nohead_rs_db::Error) -> Option<Self> {
+        let mut errors = Self::default();
+        match err {
+            nohead_rs_db::Error::ValidationError(err) => {
+                for (field, field_errors) in err.field_errors() {
+                    for error in field_errors {
+                        let message = match &error.message {
+                            Some(message) => message.to_string(),
+                            None => format!("{field} is invalid"),
+                        };
+                        errors.add(&field, message);
+                    }
+                }
+            }
+            nohead_rs_db::Error::UniqueConstraint(fields) => {
+                for (field, _) in fields {
+                    errors.add(field, format!("{field} is already taken"));
+                }
+            }
+            _ => return None,
+        }
+
+        Some(errors)
+    }
+
+    fn add(&mut self, field: &str, message: String) {
+        self.0.entry(field.to_string()).or_default().push(message);
+    }
+}
+
+/// A form that passed its `validator` checks.
+///
+/// Works like axum's `Form`, or `Json` for requests with a JSON body, but fails with
+/// [`nohead_rs_db::Error::ValidationError`] when the form is invalid.
+pub struct ValidForm<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidForm<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let form = if has_content_type(request.headers(), &mime::APPLICATION_JSON) {
+            Json::<T>::from_request(request, state).await?.0
+        } else {
+            Form::<T>::from_request(request, state).await?.0
+        };
+        form.validate().map_err(nohead_rs_db::Error::from)?;
+
+        Ok(Self(form))
+    }
+}
+
+/// Where a form is shown, to show it again when what was sent with it is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormPage {
+    /// The page with the form, shown to plain form posts.
+    pub page: &'static str,
+    /// The template of only the form, swapped in for htmx requests.
+    pub form: &'static str,
+    /// The id of the form element, which htmx swaps the form into.
+    pub form_id: &'static str,
+}
+
+/// Route layer that shows the page of a form again when the handler fails on what was sent.
+///
+/// Besides `form` and `errors`, the templates get the path parameters of the route as `params`.
+pub async fn form_page(
+    State(form_page): State<FormPage>,
+    request: Request,
+    next: Next,
+) -> Result<Response, Error> {
+    let format = ResponseFormat::of(request.headers());
+    if format == ResponseFormat::Json {
+        return Ok(next.run(request).await);
+    }
+
+    let (mut parts, body) = request.into_parts();
+    let view = parts.extensions.get::<ViewEngine<View>>().cloned();
+    let params: BTreeMap<String, String> = RawPathParams::from_request_parts(&mut parts, &())
+        .await
+        .map(|params| {
+            params
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let bytes = to_bytes(body, FORM_LIMIT)
+        .await
+        .map_err(|e| Error::Unexpected(e.into()))?;
+    let values = submitted_values(&parts.headers, &bytes);
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+    let errors = response
+        .extensions()
+        .get::<ErrorReport>()
+        .and_then(|report| report.fields.clone());
+    let (Some(ViewEngine(v)), Some(errors)) = (view, errors) else {
+        return Ok(response);
+    };
+
+    let context = json!({
+        "form": values,
+        "errors": errors,
+        "params": params,
+        "flashes": [],
+    });
+
+    if format == ResponseFormat::Htmx {
+        format::render()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .header(HX_RETARGET, format!("#{}", form_page.form_id))
+            .header(HX_RESWAP, "outerHTML")
+            .header(HX_PUSH_URL, "false")
+            .html(&v.render(form_page.form, context)?)
+    } else {
+        format::render()
+            .status(StatusCode::UNPROCESSABLE_ENTITY)
+            .html(&v.render(form_page.page, context)?)
+    }
+}
+
+/// The values of a form body, without passwords, which shouldn't be sent back.
+fn submitted_values(headers: &HeaderMap, bytes: &[u8]) -> BTreeMap<String, String> {
+    if !has_content_type(headers, &mime::APPLICATION_WWW_FORM_URLENCODED) {
+        return BTreeMap::new();
+    }
+
+    serde_urlencoded::from_bytes::<Vec<(String, String)>>(bytes)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(name, _)| !name.contains("password") && name != "csrf_token")
+        .collect()
+}
+
+fn has_content_type(headers: &HeaderMap, mime: &mime::Mime) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with(mime.as_ref()))
+}
@@ -70,6 +70,8 @@ where
 pub struct View {
     pub reloader: Arc<AutoReloader>,
     pub component_engine: ComponentEngine,
+    /// The CSRF token of the current request, available to every template as `csrf_token`.
+    pub csrf_token: Option<String>,
 }
 
 impl View {
@@ -93,15 +95,27 @@ impl View {
         Ok(Self {
             reloader: Arc::new(reloader),
             component_engine,
+            csrf_token: None,
         })
     }
+
+    /// Returns a view that renders templates with the given CSRF token.
+    pub fn with_csrf_token(self, csrf_token: String) -> Self {
+        Self {
+            csrf_token: Some(csrf_token),
+            ..self
+        }
+    }
 }
 
 impl ViewRenderer for View {
     fn render<S: Serialize>(&self, key: &str, data: S) -> Result<String, ViewEngineError> {
         let env = self.reloader.acquire_env()?;
         let template = env.get_template(key)?;
-        let base_html = template.render(minijinja::Value::from_serialize(data))?;
+        let base_html = template.render(minijinja::context! {
+            csrf_token => self.csrf_token,
+            ..minijinja::Value::from_serialize(data)
+        })?;
         let rendered = self.clone().component_engine.inject(&base_html)?;
         Ok(rendered)
     }
@@ -1,9 +1,14 @@
+pub mod api;
 pub mod app;
 pub mod controllers;
 pub mod error;
 pub mod format;
+pub mod forms;
 pub mod initializers;
 pub mod middlewares;
+pub mod negotiate;
+pub mod openapi;
+pub mod redirect;
 pub mod router;
 pub mod state;
 pub mod tracing;
@@ -1,19 +1,34 @@
 use async_trait::async_trait;
-use axum_login::{AuthManagerLayer, AuthManagerLayerBuilder, AuthnBackend, UserId};
+use std::collections::HashSet;
+
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::{
+        HeaderValue, StatusCode,
+        header::{AUTHORIZATION, COOKIE, SET_COOKIE, WWW_AUTHENTICATE},
+        request::Parts,
+    },
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+use axum_login::{AuthManagerLayer, AuthManagerLayerBuilder, AuthnBackend, AuthzBackend, UserId};
+use nohead_rs_config::PendingUserPolicy;
 use nohead_rs_db::{
     DbPool,
-    entities::user::{User, UserCredentials},
+    entities::{
+        api_token::ApiToken,
+        permission::Permission,
+        user::{User, UserCredentials, UserStatus},
+    },
 };
 use password_auth::verify_password;
 use tokio::task::{self, JoinHandle};
 use tower_sessions::{
-    ExpiredDeletion, Expiry, SessionManagerLayer,
-    cookie::{Key, time::Duration},
-    session_store,
+    ExpiredDeletion, Expiry, SessionManagerLayer, cookie::time::Duration, session_store,
 };
 use tower_sessions_sqlx_store::SqliteStore;
 
-use crate::{error::Error, state::AppState};
+use crate::{error::Error, middlewares::flash::Flash, state::AppState};
 
 // We use a type alias for convenience.
 //
@@ -23,11 +38,21 @@ pub type AuthSession = axum_login::AuthSession<AuthBackend>;
 #[derive(Debug, Clone)]
 pub struct AuthBackend {
     db: DbPool,
+    pending_users: PendingUserPolicy,
 }
 
 impl AuthBackend {
-    pub fn new(db: DbPool) -> Self {
-        Self { db }
+    pub fn new(db: DbPool, pending_users: PendingUserPolicy) -> Self {
+        Self { db, pending_users }
+    }
+
+    /// Whether a user with the given status may hold an authenticated session.
+    fn is_allowed(&self, status: UserStatus) -> bool {
+        match status {
+            UserStatus::Confirmed => true,
+            UserStatus::Pending => self.pending_users == PendingUserPolicy::Limited,
+            UserStatus::Suspended | UserStatus::Deleted => false,
+        }
     }
 }
 
@@ -47,21 +72,51 @@ impl AuthnBackend for AuthBackend {
         let user: Option<Self::User> = User::try_get_by_email(&creds.email, &self.db).await?;
         // Verifying the password is blocking and potentially slow, so we'll do so via
         // `spawn_blocking`.
-        task::spawn_blocking(|| {
+        let user = task::spawn_blocking(|| {
             // We're using password-based authentication--this works by comparing our form
             // input with an argon2 password hash.
-            Ok(user.filter(|user| verify_password(creds.password, &user.password_hash).is_ok()))
+            user.filter(|user| verify_password(creds.password, &user.password_hash).is_ok())
         })
         .await
-        .map_err(|e| Error::Unexpected(e.into()))?
+        .map_err(|e| Error::Unexpected(e.into()))?;
+
+        // Only reveal the status of the account once the password has been verified.
+        match user {
+            Some(user) if self.is_allowed(user.status) => Ok(Some(user)),
+            Some(User {
+                status: UserStatus::Pending,
+                ..
+            }) => Err(Error::PendingUser),
+            Some(User {
+                status: UserStatus::Suspended,
+                ..
+            }) => Err(Error::SuspendedUser),
+            _ => Ok(None),
+        }
     }
 
     async fn get_user(&self, user_id: &UserId<Self>) -> Result<Option<Self::User>, Self::Error> {
-        let user = User::try_get_by_id(user_id, &self.db).await?;
+        // Users that are no longer allowed in are logged out of any existing session.
+        let user = User::try_get_by_id(user_id, &self.db)
+            .await?
+            .filter(|user| self.is_allowed(user.status));
         Ok(user)
     }
 }
 
+#[async_trait]
+impl AuthzBackend for AuthBackend {
+    type Permission = Permission;
+
+    async fn get_group_permissions(
+        &self,
+        user: &Self::User,
+    ) -> Result<HashSet<Self::Permission>, Self::Error> {
+        let permissions = Permission::load_for_user(user.id, &self.db).await?;
+        Ok(permissions)
+    }
+}
+
 /// ------------------------------------------------------------------------
 /// A canvenience struct to build and manage the authentication session.
 /// ------------------------------------------------------------------------
@@ -92,19 +147,19 @@ impl AuthSessionManager {
                 .continuously_delete_expired(tokio::time::Duration::from_secs(60)),
         );
 
-        // Generate a cryptographic key to sign the session cookie.
-        let key = Key::generate();
-
         let session_layer = SessionManagerLayer::new(session_store)
             .with_secure(true)
             .with_expiry(Expiry::OnInactivity(Duration::days(1)))
-            .with_signed(key);
+            .with_signed(app_state.cookie_keys.key.clone());
 
         // Auth service.
         //
         // This combines the session layer with our backend to establish the auth
         // service which will provide the auth session as a request extension.
-        let backend = AuthBackend::new(app_state.db_pool.clone());
+        let backend = AuthBackend::new(
+            app_state.db_read_pool.clone(),
+            app_state.config.auth.pending_users,
+        );
         let auth_layer = AuthManagerLayerBuilder::new(backend, session_layer).build();
 
         Self {
@@ -113,3 +168,162 @@ impl AuthSessionManager {
         }
     }
 }
+
+/// Extractor for the user of the current [`AuthSession`], or of the API token the request was
+/// sent with, see [`bearer_auth`].
+///
+/// Rejects the request with [`Error::Unauthenticated`] when nobody is logged in, so handlers
+/// behind `login_required!` can rely on always having a user to scope their queries to.
+#[derive(Clone, Debug)]
+pub struct CurrentUser(pub User);
+
+impl<S> FromRequestParts<S> for CurrentUser
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(current_user) = parts.extensions.get::<CurrentUser>() {
+            return Ok(current_user.clone());
+        }
+
+        let auth_session = AuthSession::from_request_parts(parts, state)
+            .await
+            .map_err(|_| Error::Unauthenticated)?;
+
+        auth_session.user.map(Self).ok_or(Error::Unauthenticated)
+    }
+}
+
+/// ------------------------------------------------------------------------
+/// Middleware to keep pending users out of routes that need a confirmed account.
+/// ------------------------------------------------------------------------
+///
+/// Pending users are only ever logged in with [`PendingUserPolicy::Limited`], in which
+/// case they are redirected to the confirm page instead.
+///
+/// # Example
+///
+/// ```rust
+/// Router::new()
+///     .merge(TodoController::router())
+///     .route_layer(from_fn_with_state(app_state.clone(), confirmed_required))
+/// ```
+/// ------------------------------------------------------------------------
+pub async fn confirmed_required(
+    auth_session: AuthSession,
+    flash: Flash,
+    request: Request,
+    next: Next,
+) -> Response {
+    match auth_session.user {
+        Some(User {
+            status: UserStatus::Pending,
+            ..
+        }) => (
+            flash.warning("please confirm your email to continue"),
+            Redirect::to("/auth/register/confirm"),
+        )
+            .into_response(),
+        _ => next.run(request).await,
+    }
+}
+
+/// ------------------------------------------------------------------------
+/// Middleware to keep users without a permission out of a route.
+/// ------------------------------------------------------------------------
+///
+/// Works like axum_login's `permission_required!` but takes the permission as state, so it
+/// can be decided at runtime, e.g. by [`Controller::authorize`](crate::controllers::Controller::authorize).
+///
+/// # Example
+///
+/// ```rust
+/// Router::new().route(
+///     "/todos/{id}",
+///     delete(TodoController::delete).route_layer(from_fn_with_state(
+///         Permission::from("todos.delete"),
+///         permission_required,
+///     )),
+/// )
+/// ```
+/// ------------------------------------------------------------------------
+pub async fn permission_required(
+    State(permission): State<Permission>,
+    auth_session: AuthSession,
+    CurrentUser(user): CurrentUser,
+    request: Request,
+    next: Next,
+) -> Result<Response, Error> {
+    if !auth_session.backend.has_perm(&user, permission).await? {
+        return Err(Error::Forbidden);
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// ------------------------------------------------------------------------
+/// Middleware to authenticate API requests with the token they were sent with.
+/// ------------------------------------------------------------------------
+///
+/// Requests with an `Authorization: Bearer <token>` header act as the user the
+/// [`ApiToken`] was issued to, see [`CurrentUser`]. Requests with an unknown token are
+/// rejected, requests without one go through as nobody.
+///
+/// API requests are authenticated by their token alone, so the cookies of a browser session
+/// are dropped on the way in and none are set on the way out.
+///
+/// # Example
+///
+/// ```rust
+/// Router::new()
+///     .merge(TodoController::router())
+///     .layer(from_fn_with_state(app_state.clone(), bearer_auth))
+/// ```
+/// ------------------------------------------------------------------------
+pub async fn bearer_auth(
+    State(app_state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, Error> {
+    request.headers_mut().remove(COOKIE);
+
+    if let Some(authorization) = request.headers().get(AUTHORIZATION) {
+        let token = authorization
+            .to_str()
+            .ok()
+            .and_then(|authorization| authorization.strip_prefix("Bearer "))
+            .ok_or(Error::Unauthenticated)?;
+        let user = authenticate_token(token.trim(), &app_state).await?;
+        request.extensions_mut().insert(CurrentUser(user));
+    }
+
+    let mut response = next.run(request).await;
+    response.headers_mut().remove(SET_COOKIE);
+    if response.status() == StatusCode::UNAUTHORIZED {
+        response
+            .headers_mut()
+            .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    }
+
+    Ok(response)
+}
+
+/// The user an API token was issued to, as long as they may still use the API.
+async fn authenticate_token(token: &str, app_state: &AppState) -> Result<User, Error> {
+    // Marking the token as used is a write.
+    let user_id = ApiToken::try_get_user_id_by_token(token, &app_state.db_pool)
+        .await?
+        .ok_or(Error::Unauthenticated)?;
+    let user = User::try_get_by_id(&user_id, &app_state.db_read_pool)
+        .await?
+        .ok_or(Error::Unauthenticated)?;
+
+    match user.status {
+        UserStatus::Confirmed => Ok(user),
+        UserStatus::Pending => Err(Error::PendingUser),
+        UserStatus::Suspended => Err(Error::SuspendedUser),
+        UserStatus::Deleted => Err(Error::Unauthenticated),
+    }
+}
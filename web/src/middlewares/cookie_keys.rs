@@ -0,0 +1,129 @@
+//! The keys that sign the session and flash cookies, see `[security]` in `config/app.toml`.
+//!
+//! The session and flash layers only know a single key, so cookies signed with one of the old
+//! keys are re-signed with the current key by [`resign_cookies`] before they reach them.
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, header::COOKIE},
+    middleware::Next,
+    response::Response,
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use color_eyre::eyre::eyre;
+use nohead_rs_config::{Environment, SecurityConfig};
+use tower_sessions::cookie::{Cookie, CookieJar, Key};
+
+use crate::error::Error;
+
+#[derive(Clone)]
+pub struct CookieKeys {
+    /// The key new cookies are signed with.
+    pub key: Key,
+    /// The keys cookies were signed with before `key`, which are still accepted.
+    pub old_keys: Vec<Key>,
+}
+
+impl CookieKeys {
+    /// Decodes the keys in the config.
+    ///
+    /// Without a `secret_key` a key is generated, so cookies won't survive a restart, which is
+    /// an error in [`Environment::Production`].
+    pub fn from_config(config: &SecurityConfig, env: &Environment) -> Result<Self, Error> {
+        let key = match (&config.secret_key, env) {
+            (Some(secret_key), _) => decode_key("secret_key", secret_key)?,
+            (None, Environment::Production) => {
+                return Err(eyre!(
+                    "[security] secret_key must be set in production, generate one with `nohead-rs config generate-key` and set APP_SECURITY__SECRET_KEY"
+                )
+                .into());
+            }
+            (None, _) => Key::generate(),
+        };
+
+        let old_keys = config
+            .old_secret_keys
+            .iter()
+            .map(|old_key| decode_key("old_secret_keys", old_key))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { key, old_keys })
+    }
+
+    /// Re-signs the cookies in `headers` that were signed with an old key.
+    fn resign(&self, headers: &mut HeaderMap) {
+        let mut resigned = false;
+        let cookies: Vec<String> = headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(Cookie::split_parse)
+            .filter_map(Result::ok)
+            .map(|cookie| match self.resign_cookie(&cookie) {
+                Some(value) => {
+                    resigned = true;
+                    format!("{}={value}", cookie.name())
+                }
+                None => format!("{}={}", cookie.name(), cookie.value()),
+            })
+            .collect();
+
+        if !resigned {
+            return;
+        }
+
+        if let Ok(value) = HeaderValue::from_str(&cookies.join("; ")) {
+            headers.remove(COOKIE);
+            headers.insert(COOKIE, value);
+        }
+    }
+
+    /// Returns the value of `cookie` signed with the current key, or `None` if it is already
+    /// signed with it or wasn't signed with any of the old keys.
+    fn resign_cookie(&self, cookie: &Cookie<'_>) -> Option<String> {
+        let mut jar = CookieJar::new();
+        jar.add_original(cookie.clone().into_owned());
+
+        if jar.signed(&self.key).get(cookie.name()).is_some() {
+            return None;
+        }
+
+        let verified = self
+            .old_keys
+            .iter()
+            .find_map(|old_key| jar.signed(old_key).get(cookie.name()))?;
+
+        let mut jar = CookieJar::new();
+        jar.signed_mut(&self.key).add(verified);
+
+        jar.get(cookie.name())
+            .map(|cookie| cookie.value().to_string())
+    }
+}
+
+/// Generates a new random key for `[security] secret_key`.
+pub fn generate_secret_key() -> String {
+    STANDARD.encode(Key::generate().master())
+}
+
+fn decode_key(name: &str, value: &str) -> Result<Key, Error> {
+    let bytes = STANDARD
+        .decode(value.trim())
+        .map_err(|_| eyre!("[security] {name}: is not valid base64"))?;
+
+    Key::try_from(bytes.as_slice())
+        .map_err(|_| eyre!("[security] {name}: must be at least 64 bytes long").into())
+}
+
+/// Middleware that lets cookies signed with an old key through to the session and flash layers.
+pub async fn resign_cookies(
+    State(keys): State<CookieKeys>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if !keys.old_keys.is_empty() {
+        keys.resign(request.headers_mut());
+    }
+
+    next.run(request).await
+}
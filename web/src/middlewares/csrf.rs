@@ -0,0 +1,161 @@
+//! Protection against cross-site request forgery.
+//!
+//! Every browser session gets a random token in a signed cookie that forms must send back when
+//! they post, put or delete, either in a `csrf_token` field or in the `X-CSRF-Token` header.
+//! Keeping it in a cookie rather than the session store means visitors that never log in don't
+//! leave sessions behind.
+//!
+//! Templates can read the token as `csrf_token` and `base.html` adds it to the headers of every
+//! htmx request, so most forms don't need to do anything. It is also sent in the
+//! `X-CSRF-Token` response header.
+
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, Method, header::CONTENT_TYPE},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::{
+    SignedCookieJar,
+    cookie::{Cookie, SameSite},
+};
+use rand::Rng as _;
+use serde::Deserialize;
+
+use crate::{
+    error::Error,
+    initializers::view_engine::engine::{View, ViewEngine},
+    state::AppState,
+};
+
+/// The header htmx requests send the token in, and that responses carry it in.
+pub const CSRF_HEADER: HeaderName = HeaderName::from_static("x-csrf-token");
+
+const COOKIE_NAME: &str = "csrf_token";
+
+/// Form bodies are read to find the token, so they are capped like axum's `Form` extractor.
+const FORM_LIMIT: usize = 2 * 1024 * 1024;
+
+/// The CSRF token of the browser session, for handlers that need it, e.g. to render JSON.
+#[derive(Clone, Debug)]
+pub struct CsrfToken(pub String);
+
+/// The field plain forms send the token in.
+#[derive(Deserialize)]
+struct CsrfForm {
+    csrf_token: Option<String>,
+}
+
+/// Middleware that issues the CSRF token of the browser session and checks it on unsafe
+/// requests.
+///
+/// The token is only checked when `[security] csrf` is on.
+pub async fn csrf(
+    State(app_state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, Error> {
+    let jar = SignedCookieJar::from_headers(request.headers(), app_state.cookie_keys.key.clone());
+    let (token, new_cookie) = match jar.get(COOKIE_NAME) {
+        Some(cookie) => (cookie.value().to_string(), None),
+        None => {
+            let token = generate_token();
+            (token.clone(), Some(create_cookie(token)))
+        }
+    };
+
+    let mut request = if app_state.config.security.csrf && !is_safe(request.method()) {
+        verify(request, &token).await?
+    } else {
+        request
+    };
+
+    let extensions = request.extensions_mut();
+    if let Some(ViewEngine(view)) = extensions.remove::<ViewEngine<View>>() {
+        extensions.insert(ViewEngine(view.with_csrf_token(token.clone())));
+    }
+    extensions.insert(CsrfToken(token.clone()));
+
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .insert(CSRF_HEADER, HeaderValue::from_str(&token)?);
+
+    match new_cookie {
+        Some(cookie) => Ok((jar.add(cookie), response).into_response()),
+        None => Ok(response),
+    }
+}
+
+/// A cookie that lasts until the browser is closed.
+fn create_cookie(token: String) -> Cookie<'static> {
+    Cookie::build((COOKIE_NAME, token))
+        .secure(true)
+        .http_only(true)
+        .same_site(SameSite::Strict)
+        .path("/")
+        .build()
+}
+
+/// Checks the token sent with `request`, returning the request with its body intact.
+async fn verify(request: Request, token: &str) -> Result<Request, Error> {
+    if let Some(sent) = request.headers().get(&CSRF_HEADER) {
+        return if tokens_match(sent.as_bytes(), token.as_bytes()) {
+            Ok(request)
+        } else {
+            Err(Error::InvalidCsrfToken)
+        };
+    }
+
+    let is_form = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| {
+            content_type.starts_with(mime::APPLICATION_WWW_FORM_URLENCODED.as_ref())
+        });
+    if !is_form {
+        return Err(Error::InvalidCsrfToken);
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, FORM_LIMIT)
+        .await
+        .map_err(|_| Error::InvalidCsrfToken)?;
+    let sent = serde_urlencoded::from_bytes::<CsrfForm>(&bytes)
+        .ok()
+        .and_then(|form| form.csrf_token);
+
+    match sent {
+        Some(sent) if tokens_match(sent.as_bytes(), token.as_bytes()) => {
+            Ok(Request::from_parts(parts, Body::from(bytes)))
+        }
+        _ => Err(Error::InvalidCsrfToken),
+    }
+}
+
+fn is_safe(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+/// Compares in constant time so that the token can't be guessed a byte at a time.
+fn tokens_match(sent: &[u8], token: &[u8]) -> bool {
+    sent.len() == token.len()
+        && sent
+            .iter()
+            .zip(token)
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+fn generate_token() -> String {
+    let mut rng = rand::rng();
+    std::iter::repeat_with(|| rng.sample(rand::distr::Alphanumeric))
+        .map(char::from)
+        .take(32)
+        .collect()
+}
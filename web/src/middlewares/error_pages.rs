@@ -0,0 +1,66 @@
+//! Renders error responses for whoever asked.
+//!
+//! [`crate::error::Error`] doesn't know the request it failed, so it only adds an
+//! [`ErrorReport`] to its response. This middleware swaps that response for an error page, an
+//! htmx fragment for the `#errors` element of the current page or, for requests that accept
+//! JSON, a problem+json body. The error itself is only shown in development.
+
+use axum::{
+    extract::{Request, State},
+    http::header::{CONTENT_LENGTH, CONTENT_TYPE},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use nohead_rs_config::Environment;
+
+use crate::{
+    error::ErrorReport,
+    initializers::view_engine::engine::{View, ViewEngine},
+    middlewares::csrf::CSRF_HEADER,
+    negotiate::ResponseFormat,
+    state::AppState,
+    views::errors::ErrorView,
+};
+
+/// Middleware that renders the responses of errors according to the request.
+pub async fn error_pages(
+    State(app_state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let format = ResponseFormat::of(request.headers());
+    let view = request.extensions().get::<ViewEngine<View>>().cloned();
+
+    let response = next.run(request).await;
+    let Some(mut report) = response.extensions().get::<ErrorReport>().cloned() else {
+        return response;
+    };
+    if app_state.env != Environment::Development {
+        report.details = None;
+    }
+
+    let (parts, _) = response.into_parts();
+    // The CSRF middleware runs inside this one, so take its token from the response for the
+    // forms on the page.
+    let view = view.map(|ViewEngine(view)| match parts.headers.get(CSRF_HEADER) {
+        Some(token) => ViewEngine(view.with_csrf_token(token.to_str().unwrap_or_default().into())),
+        None => ViewEngine(view),
+    });
+
+    let mut rendered = match (format, view) {
+        (ResponseFormat::Json, _) | (_, None) => ErrorView::Problem(report),
+        (ResponseFormat::Htmx, Some(view)) => ErrorView::Fragment(view, report),
+        (ResponseFormat::Html, Some(view)) => ErrorView::Page(view, report),
+    }
+    .into_response();
+
+    // Keep what was added to the response on the way out, e.g. the CSRF cookie of a new session.
+    let headers = rendered.headers_mut();
+    for (name, value) in &parts.headers {
+        if name != CONTENT_TYPE && name != CONTENT_LENGTH {
+            headers.append(name, value.clone());
+        }
+    }
+
+    rendered
+}
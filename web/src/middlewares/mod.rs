@@ -1,2 +1,5 @@
 pub mod auth;
+pub mod cookie_keys;
+pub mod csrf;
+pub mod error_pages;
 pub mod flash;
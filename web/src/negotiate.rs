@@ -0,0 +1,148 @@
+//! Answering browsers, htmx and JSON clients from the same handlers.
+//!
+//! Handlers take the [`ResponseFormat`] of the request and return a [`Negotiated`] response,
+//! which is their view, or redirect, for browsers and the serialized data for JSON clients.
+//!
+//! # Example
+//!
+//! ```rust
+//! async fn read_one(
+//!     v: ViewEngine<View>,
+//!     format: ResponseFormat,
+//!     Path(id): Path<i64>,
+//!     State(app_state): State<AppState>,
+//! ) -> Result<Negotiated<TodoView, Todo>, Error> {
+//!     let todo = Todo::load(id, &app_state.db_read_pool).await?;
+//!
+//!     Ok(Negotiated::new(format, TodoView::Show(v, todo.clone()), todo))
+//! }
+//! ```
+
+use std::convert::Infallible;
+
+use axum::{
+    Json,
+    extract::FromRequestParts,
+    http::{
+        HeaderMap, HeaderValue, StatusCode,
+        header::{ACCEPT, LOCATION},
+        request::Parts,
+    },
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+/// How the client that sent a request wants to be answered, read from its `HX-Request` and
+/// `Accept` headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    /// A page, for browsers.
+    Html,
+    /// Part of a page, for htmx.
+    Htmx,
+    /// The data itself.
+    Json,
+}
+
+impl ResponseFormat {
+    pub fn of(headers: &HeaderMap) -> Self {
+        let is_htmx = headers
+            .get("hx-request")
+            .is_some_and(|value| value.as_bytes() == b"true");
+        if is_htmx {
+            return ResponseFormat::Htmx;
+        }
+
+        // Whichever of HTML and JSON the client lists first, browsers list HTML.
+        let accept = headers
+            .get(ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .unwrap_or_default();
+        let preferred = accept
+            .split(',')
+            .map(|media_type| media_type.split(';').next().unwrap_or_default().trim())
+            .find(|media_type| {
+                *media_type == mime::TEXT_HTML.as_ref()
+                    || *media_type == mime::APPLICATION_JSON.as_ref()
+                    || media_type.ends_with("+json")
+            });
+
+        match preferred {
+            Some(media_type) if media_type != mime::TEXT_HTML.as_ref() => ResponseFormat::Json,
+            _ => ResponseFormat::Html,
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for ResponseFormat
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(ResponseFormat::of(&parts.headers))
+    }
+}
+
+/// A response that is `html` for browsers and htmx, and `json` serialized for JSON clients.
+pub struct Negotiated<V, T> {
+    format: ResponseFormat,
+    html: V,
+    json: T,
+    /// The status of the JSON response.
+    status: StatusCode,
+    location: Option<String>,
+}
+
+impl<V, T> Negotiated<V, T> {
+    pub fn new(format: ResponseFormat, html: V, json: T) -> Self {
+        Self {
+            format,
+            html,
+            json,
+            status: StatusCode::OK,
+            location: None,
+        }
+    }
+
+    /// Answers JSON clients with `201 Created` and the location of the new record.
+    pub fn created(self, location: &str) -> Self {
+        Self {
+            status: StatusCode::CREATED,
+            location: Some(location.to_string()),
+            ..self
+        }
+    }
+
+    /// Answers JSON clients with `204 No Content`, e.g. once a record is deleted.
+    pub fn no_content(self) -> Self {
+        Self {
+            status: StatusCode::NO_CONTENT,
+            ..self
+        }
+    }
+}
+
+impl<V, T> IntoResponse for Negotiated<V, T>
+where
+    V: IntoResponse,
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        if self.format != ResponseFormat::Json {
+            return self.html.into_response();
+        }
+
+        let mut response = if self.status == StatusCode::NO_CONTENT {
+            self.status.into_response()
+        } else {
+            (self.status, Json(self.json)).into_response()
+        };
+        if let Some(location) = self.location.and_then(|l| HeaderValue::from_str(&l).ok()) {
+            response.headers_mut().insert(LOCATION, location);
+        }
+
+        response
+    }
+}
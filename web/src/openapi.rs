@@ -0,0 +1,294 @@
+//! The OpenAPI document of the JSON API, served at `/api/openapi.json`.
+//!
+//! Controllers describe their routes with [`Operation`]s, e.g. the actions of every
+//! [`Controller`] with [`Controller::openapi`]. The schemas of what is sent and answered are
+//! derived from the entities and changesets with `#[derive(Schema)]`, and the error responses
+//! from the status codes of [`Error`], so the document keeps up with the code.
+//!
+//! # Example
+//!
+//! ```rust
+//! OpenApi::default().operation(
+//!     Method::GET,
+//!     "/api/v1/notes/{id}",
+//!     Operation::new("Load a note")
+//!         .path_param("id")
+//!         .response::<Note>(StatusCode::OK, "the note")
+//!         .errors(&[Error::Database(nohead_rs_db::Error::NoRecordFound)]),
+//! )
+//! ```
+
+use std::collections::BTreeMap;
+
+use axum::http::{Method, StatusCode};
+use nohead_rs_db::schema::{Schema, reference};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    api,
+    controllers::{
+        Controller,
+        api_tokens::ApiTokenController,
+        auth::{login::LoginController, register::RegisterController},
+        todos::TodoController,
+    },
+    error::Error,
+};
+
+/// The document of every route that is described, see [`OpenApi`].
+pub fn document() -> Value {
+    let doc = OpenApi::default();
+    let doc = TodoController::openapi(&format!("{}/todos", api::PREFIX), doc);
+    let doc = ApiTokenController::openapi(&format!("{}/tokens", api::PREFIX), doc);
+    let doc = LoginController::openapi(doc);
+    let doc = RegisterController::openapi(doc);
+
+    doc.into_json()
+}
+
+/// An OpenAPI 3.1 document, built up from the [`Operation`]s of each route.
+pub struct OpenApi {
+    paths: BTreeMap<String, Map<String, Value>>,
+    schemas: Map<String, Value>,
+}
+
+impl Default for OpenApi {
+    fn default() -> Self {
+        let mut schemas = Map::new();
+        schemas.insert("Problem".to_string(), problem_schema());
+
+        Self {
+            paths: BTreeMap::new(),
+            schemas,
+        }
+    }
+}
+
+impl OpenApi {
+    /// Adds the operation of a method on a path, along with the schemas it uses.
+    pub fn operation(mut self, method: Method, path: &str, operation: Operation) -> Self {
+        self.schemas.extend(operation.schemas);
+        self.paths
+            .entry(path.to_string())
+            .or_default()
+            .insert(method.as_str().to_lowercase(), operation.operation);
+
+        self
+    }
+
+    pub fn into_json(self) -> Value {
+        json!({
+            "openapi": "3.1.0",
+            "info": {
+                "title": "nohead-rs",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "paths": self.paths,
+            "components": {
+                "schemas": self.schemas,
+                "securitySchemes": {
+                    "bearerAuth": {
+                        "type": "http",
+                        "scheme": "bearer",
+                        "description": format!("An API token, issued by `POST {}/tokens`", api::PREFIX),
+                    },
+                },
+            },
+        })
+    }
+}
+
+/// What a route does with a method, see <https://spec.openapis.org/oas/v3.1.0#operation-object>.
+pub struct Operation {
+    operation: Value,
+    /// The schemas the operation refers to.
+    schemas: Map<String, Value>,
+}
+
+impl Operation {
+    pub fn new(summary: impl Into<String>) -> Self {
+        Self {
+            operation: json!({ "summary": summary.into(), "responses": {} }),
+            schemas: Map::new(),
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.operation["description"] = description.into().into();
+        self
+    }
+
+    /// Groups the operation with the others of the same tag, e.g. of the same entity.
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.operation["tags"] = json!([tag]);
+        self
+    }
+
+    /// Requires an API token, see [`crate::middlewares::auth::bearer_auth`].
+    pub fn bearer_auth(mut self) -> Self {
+        self.operation["security"] = json!([{ "bearerAuth": [] }]);
+        self.errors(&[Error::Unauthenticated])
+    }
+
+    /// Adds an id parameter to the path, e.g. the `{id}` of `/todos/{id}`.
+    pub fn path_param(self, name: &str) -> Self {
+        self.parameter(json!({
+            "name": name,
+            "in": "path",
+            "required": true,
+            "schema": { "type": "integer", "format": "int64" },
+        }))
+    }
+
+    /// Adds the query params of [`nohead_rs_db::pagination::ListParams`].
+    pub fn list_params(self) -> Self {
+        [
+            (
+                "page",
+                json!({ "type": "integer", "minimum": 1 }),
+                "the page to load",
+            ),
+            (
+                "per_page",
+                json!({ "type": "integer", "minimum": 1 }),
+                "the number of records per page",
+            ),
+            ("sort", json!({ "type": "string" }), "the column to sort by"),
+            (
+                "order",
+                json!({ "type": "string", "enum": ["asc", "desc"] }),
+                "the direction to sort in",
+            ),
+            (
+                "filter",
+                json!({ "type": "string" }),
+                "a search term to filter the records by",
+            ),
+        ]
+        .into_iter()
+        .fold(self, |operation, (name, schema, description)| {
+            operation.parameter(json!({
+                "name": name,
+                "in": "query",
+                "schema": schema,
+                "description": description,
+            }))
+        })
+    }
+
+    /// Takes a `T` as the body, in JSON or whichever `content_type` is given.
+    pub fn body<T: Schema>(mut self, content_type: &str) -> Self {
+        T::register(&mut self.schemas);
+        self.operation["requestBody"] = json!({
+            "required": true,
+            "content": { content_type: { "schema": reference::<T>() } },
+        });
+        self
+    }
+
+    /// Answers a `T` as JSON.
+    pub fn response<T: Schema>(mut self, status: StatusCode, description: &str) -> Self {
+        T::register(&mut self.schemas);
+        self.json_response(status, description, reference::<T>())
+    }
+
+    /// Answers a list of `T`s as JSON.
+    pub fn list_response<T: Schema>(mut self, status: StatusCode, description: &str) -> Self {
+        T::register(&mut self.schemas);
+        self.json_response(
+            status,
+            description,
+            json!({ "type": "array", "items": reference::<T>() }),
+        )
+    }
+
+    /// Answers a [`nohead_rs_db::pagination::Page`] of `T`s as JSON.
+    pub fn page_response<T: Schema>(mut self, status: StatusCode, description: &str) -> Self {
+        T::register(&mut self.schemas);
+        let page = json!({
+            "type": "object",
+            "properties": {
+                "items": { "type": "array", "items": reference::<T>() },
+                "page": { "type": "integer" },
+                "per_page": { "type": "integer" },
+                "total": { "type": "integer" },
+                "total_pages": { "type": "integer" },
+                "next_page": { "type": ["integer", "null"] },
+                "prev_page": { "type": ["integer", "null"] },
+                "params": { "type": "object" },
+            },
+            "required": ["items", "page", "per_page", "total", "total_pages"],
+        });
+        self.json_response(status, description, page)
+    }
+
+    /// Answers without a body, e.g. `204 No Content` or a redirect.
+    pub fn empty_response(mut self, status: StatusCode, description: &str) -> Self {
+        self.operation["responses"][status.as_str()] = json!({ "description": description });
+        self
+    }
+
+    /// Answers the problem details of the errors, grouped by their status code.
+    pub fn errors(mut self, errors: &[Error]) -> Self {
+        for err in errors {
+            let status = err.status_code();
+            let message = err.message();
+            let response = &mut self.operation["responses"][status.as_str()];
+            let description = match response["description"].as_str() {
+                Some(description) if !message.is_empty() && !description.contains(&message) => {
+                    format!("{description}, {message}")
+                }
+                Some(description) => description.to_string(),
+                None if message.is_empty() => status.canonical_reason().unwrap_or_default().into(),
+                None => message,
+            };
+
+            *response = json!({
+                "description": description,
+                "content": {
+                    "application/problem+json": {
+                        "schema": { "$ref": "#/components/schemas/Problem" },
+                    },
+                },
+            });
+        }
+
+        self
+    }
+
+    fn json_response(mut self, status: StatusCode, description: &str, schema: Value) -> Self {
+        self.operation["responses"][status.as_str()] = json!({
+            "description": description,
+            "content": { "application/json": { "schema": schema } },
+        });
+        self
+    }
+
+    fn parameter(mut self, parameter: Value) -> Self {
+        match self.operation["parameters"].as_array_mut() {
+            Some(parameters) => parameters.push(parameter),
+            None => self.operation["parameters"] = json!([parameter]),
+        }
+        self
+    }
+}
+
+/// The problem details errors are answered with, see [`crate::views::errors::ErrorView`].
+fn problem_schema() -> Value {
+    json!({
+        "description": "The problem details of an error, see RFC 9457.",
+        "type": "object",
+        "properties": {
+            "type": { "type": "string" },
+            "title": { "type": "string" },
+            "status": { "type": "integer" },
+            "detail": { "type": "string" },
+            "errors": {
+                "description": "What is wrong with each field that was sent.",
+                "type": "object",
+                "additionalProperties": { "type": "array", "items": { "type": "string" } },
+            },
+        },
+        "required": ["type", "title", "status", "detail"],
+    })
+}
@@ -0,0 +1,148 @@
+//! Redirects to URLs that come from the request, e.g. the `next` parameter of the login page.
+//!
+//! Redirecting to such a URL as is lets anyone craft a link to this site that sends the user
+//! on to a site of their choosing, see
+//! <https://cheatsheetseries.owasp.org/cheatsheets/Unvalidated_Redirects_and_Forwards_Cheat_Sheet.html>.
+
+use std::fmt::{Display, Formatter};
+
+use axum::response::Redirect;
+use percent_encoding::percent_decode_str;
+use serde::Serialize;
+
+/// A URL to redirect to that is known to stay on this site.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SafeRedirect(String);
+
+impl SafeRedirect {
+    /// Accepts `target` if it is a path on this site, e.g. `/todos?page=2`.
+    ///
+    /// An absolute URL is accepted when it starts with `host`, i.e. `ServerConfig::host`, and
+    /// is reduced to its path. Anything that a browser could read as another site is refused,
+    /// e.g. `//evil.example`, `/\evil.example` or `https://evil.example`, including when it is
+    /// percent encoded.
+    pub fn parse(target: &str, host: &str) -> Option<Self> {
+        let path = match strip_origin(target, host) {
+            Some(path) if !path.starts_with('/') => format!("/{path}"),
+            Some(path) => path.to_string(),
+            None => target.to_string(),
+        };
+
+        is_local_path(&path).then_some(Self(path))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for SafeRedirect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<SafeRedirect> for Redirect {
+    fn from(redirect: SafeRedirect) -> Self {
+        Redirect::to(&redirect.0)
+    }
+}
+
+/// Returns what follows the origin of `host` in `target`, if `target` is on `host`.
+fn strip_origin<'a>(target: &'a str, host: &str) -> Option<&'a str> {
+    let origin = host.trim_end_matches('/');
+    let prefix = target.get(..origin.len())?;
+    if !prefix.eq_ignore_ascii_case(origin) {
+        return None;
+    }
+
+    let rest = &target[origin.len()..];
+    // Otherwise `https://example.com.evil.example` would be on `https://example.com`.
+    (rest.is_empty() || rest.starts_with(['/', '?', '#'])).then_some(rest)
+}
+
+fn is_local_path(path: &str) -> bool {
+    // Browsers skip tabs and new lines in URLs and read `\` as `/`, so `/\t/evil.example` and
+    // `/\evil.example` both lead to `//evil.example`.
+    let is_plain = |path: &str| !path.chars().any(|c| c.is_control() || c == '\\');
+    // Only a single leading `/` keeps a URL on this site, `//` starts another host.
+    let is_relative = |path: &str| path.starts_with('/') && !path.starts_with("//");
+
+    // Check the decoded path too, in case it is decoded again before it reaches the browser.
+    let Ok(decoded) = percent_decode_str(path).decode_utf8() else {
+        return false;
+    };
+
+    is_plain(path) && is_relative(path) && is_plain(&decoded) && is_relative(&decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST: &str = "https://nohead-rs.fly.dev";
+
+    fn parse(target: &str) -> Option<String> {
+        SafeRedirect::parse(target, HOST).map(|redirect| redirect.to_string())
+    }
+
+    #[test]
+    fn local_paths_are_accepted() {
+        assert_eq!(parse("/"), Some("/".to_string()));
+        assert_eq!(parse("/todos"), Some("/todos".to_string()));
+        assert_eq!(
+            parse("/todos?page=2&q=a%20b#top"),
+            Some("/todos?page=2&q=a%20b#top".to_string())
+        );
+    }
+
+    #[test]
+    fn urls_on_the_host_are_reduced_to_their_path() {
+        assert_eq!(
+            parse("https://nohead-rs.fly.dev/todos?page=2"),
+            Some("/todos?page=2".to_string())
+        );
+        assert_eq!(parse("HTTPS://NOHEAD-RS.FLY.DEV"), Some("/".to_string()));
+        assert_eq!(
+            parse("https://nohead-rs.fly.dev?page=2"),
+            Some("/?page=2".to_string())
+        );
+    }
+
+    #[test]
+    fn other_sites_are_refused() {
+        for target in [
+            "https://evil.example",
+            "https://nohead-rs.fly.dev.evil.example/todos",
+            "https://nohead-rs.fly.dev@evil.example",
+            "http://nohead-rs.fly.dev/todos",
+            "//evil.example",
+            "///evil.example",
+            "/\\evil.example",
+            "\\\\evil.example",
+            "/\t/evil.example",
+            "/\n/evil.example",
+            "evil.example",
+            "todos",
+            "",
+        ] {
+            assert_eq!(parse(target), None, "{target:?} should be refused");
+        }
+    }
+
+    #[test]
+    fn encoded_tricks_are_refused() {
+        for target in [
+            "javascript:alert(1)",
+            "javascript%3Aalert(1)",
+            "%2F%2Fevil.example",
+            "/%2F/evil.example",
+            "/%2f%2fevil.example",
+            "/%5Cevil.example",
+            "/%09/evil.example",
+            "/%ff",
+        ] {
+            assert_eq!(parse(target), None, "{target:?} should be refused");
+        }
+    }
+}
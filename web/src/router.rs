@@ -1,7 +1,13 @@
 use std::{path::Path, time::Duration};
 
-use axum::{Extension, Router, http::header, routing::get};
+use axum::{
+    Extension, Router,
+    http::{Method, header},
+    middleware::from_fn_with_state,
+    routing::get,
+};
 use axum_login::{AuthManagerLayer, login_required};
+use nohead_rs_config::Environment;
 use nohead_rs_db::DeserializeOwned;
 use nohead_rs_worker::WorkerStorage;
 use serde::Serialize;
@@ -13,19 +19,28 @@ use tower_http::{
 use tower_sessions_sqlx_store::SqliteStore;
 
 use crate::{
+    api,
     controllers::{
         Controller,
         auth::{
-            login::LoginController, logout::LogoutController, register::RegisterController,
-            register_confirm::RegisterConfirmController,
+            login::LoginController, logout::LogoutController,
+            password_forgot::PasswordForgotController, password_reset::PasswordResetController,
+            register::RegisterController, register_confirm::RegisterConfirmController,
         },
         home::HomeController,
+        mailbox::MailboxController,
+        openapi::OpenApiController,
         ping::PingController,
         todos::TodoController,
     },
-    error::Result,
+    error::{Error, Result},
     initializers::view_engine::engine::ViewEngineInitializer,
-    middlewares::auth::AuthBackend,
+    middlewares::{
+        auth::{AuthBackend, confirmed_required},
+        cookie_keys::resign_cookies,
+        csrf::csrf,
+        error_pages::error_pages,
+    },
     state::AppState,
 };
 
@@ -34,7 +49,7 @@ pub fn init_router<T>(
     auth_layer: AuthManagerLayer<AuthBackend, SqliteStore, tower_sessions::service::SignedCookie>,
     worker_layer: WorkerStorage<T>,
     view_engine: ViewEngineInitializer,
-) -> Result<Router>
+) -> Result<(Router, RouteTable)>
 where
     T: 'static + Serialize + DeserializeOwned + Send + Sync + Unpin,
 {
@@ -49,32 +64,117 @@ where
         header::HeaderValue::from_static("no-store, must-revalidate"),
     );
 
-    let mut router = Router::new()
-        .route(
-            "/protected",
-            get(|| async { "you gotta be logged in to see me!" }),
-        )
-        .merge(TodoController::router())
+    let mut table = RouteTable::default();
+    let mut routes = Router::new()
+        .merge(table.add(
+            &[("/protected", &[Method::GET])],
+            Router::new().route(
+                "/protected",
+                get(|| async { "you gotta be logged in to see me!" }),
+            ),
+        ))
+        .merge(table.add(TodoController::ROUTES, TodoController::router()))
+        .route_layer(from_fn_with_state(app_state.clone(), confirmed_required))
         .route_layer(login_required!(AuthBackend, login_url = "/auth/login"))
-        .merge(HomeController::router())
-        .merge(LoginController::router())
-        .merge(LogoutController::router())
-        .merge(RegisterController::router())
-        .merge(RegisterConfirmController::router())
-        .merge(PingController::router())
+        .merge(table.add(HomeController::ROUTES, HomeController::router()))
+        .merge(table.add(LoginController::ROUTES, LoginController::router()))
+        .merge(table.add(LogoutController::ROUTES, LogoutController::router()))
+        .merge(table.add(RegisterController::ROUTES, RegisterController::router()))
+        .merge(table.add(
+            RegisterConfirmController::ROUTES,
+            RegisterConfirmController::router(),
+        ))
+        .merge(table.add(
+            PasswordForgotController::ROUTES,
+            PasswordForgotController::router(),
+        ))
+        .merge(table.add(
+            PasswordResetController::ROUTES,
+            PasswordResetController::router(),
+        ))
+        .merge(table.add(PingController::ROUTES, PingController::router()))
+        .merge(table.add(OpenApiController::ROUTES, OpenApiController::router()));
+
+    // The mailbox shows every email the app sent, which only developers should see.
+    if app_state.env == Environment::Development {
+        routes = routes.merge(table.add(MailboxController::ROUTES, MailboxController::router()));
+    }
+
+    let mut router = routes
+        .fallback(|| async { Error::NotFound })
         .with_state(app_state.clone())
         .layer(ServiceBuilder::new().layer((
             TraceLayer::new_for_http(),
             // Graceful shutdown will wait for outstanding requests to complete. Add a timeout so
             // requests don't hang forever.
             TimeoutLayer::new(Duration::from_secs(10)),
-            auth_layer,
+            from_fn_with_state(app_state.clone(), error_pages),
+            // Before the session layer, which only accepts cookies signed with the current key.
+            from_fn_with_state(app_state.cookie_keys.clone(), resign_cookies),
+            auth_layer.clone(),
+            from_fn_with_state(app_state.clone(), csrf),
             Extension(worker_layer),
         )))
-        .nest_service("/static", static_assets)
+        .nest(api::PREFIX, api::router(app_state, auth_layer, &mut table))
+        .nest_service(
+            "/static",
+            table.add(&[("/static/{*rest}", &[])], static_assets),
+        )
         .layer(no_cache_layer);
 
     router = view_engine.after_routes(router, app_state)?;
 
-    Ok(router)
+    Ok((router, table))
+}
+
+/// The paths a router serves along with the methods each accepts, no methods for services
+/// such as static assets that accept any.
+pub type Routes = &'static [(&'static str, &'static [Method])];
+
+/// A route of the app router along with the methods it accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteInfo {
+    pub path: String,
+    /// The methods the route accepts, `*` for services such as static assets that accept any.
+    pub methods: Vec<String>,
+}
+
+/// The routes of the app, as `nohead-rs routes` lists them.
+///
+/// axum can't list the routes of a router, so every router merged into the app registers
+/// its [`Routes`] as it is merged:
+///
+/// ```rust
+/// Router::new().merge(table.add(TodoController::ROUTES, TodoController::router()))
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RouteTable(Vec<RouteInfo>);
+
+impl RouteTable {
+    /// Registers the routes of a router, which is passed through to be merged.
+    pub fn add<R>(&mut self, routes: Routes, router: R) -> R {
+        self.nest("", routes, router)
+    }
+
+    /// Registers the routes of a router nested under `prefix`.
+    pub fn nest<R>(&mut self, prefix: &str, routes: Routes, router: R) -> R {
+        self.0
+            .extend(routes.iter().map(|(path, methods)| RouteInfo {
+                path: format!("{prefix}{path}"),
+                methods: match methods {
+                    [] => vec!["*".to_string()],
+                    methods => methods.iter().map(ToString::to_string).collect(),
+                },
+            }));
+
+        router
+    }
+
+    /// The registered routes, sorted by path.
+    pub fn routes(&self) -> Vec<RouteInfo> {
+        let mut routes = self.0.clone();
+        routes.sort_by(|a, b| a.path.cmp(&b.path));
+
+        routes
+    }
 }
@@ -1,37 +1,84 @@
+use std::path::Path;
+
 use axum::extract::FromRef;
-use axum_extra::extract::cookie::Key;
 use color_eyre::Result;
 use nohead_rs_config::{Config, Environment, load_config};
-use nohead_rs_db::{DbPool, connect_pool};
-use nohead_rs_mailer::EmailClient;
+use nohead_rs_db::{
+    DbPool, connect_pool, connect_read_pool,
+    migrate::{self, StartupMigration},
+};
+use nohead_rs_mailer::{EmailClient, EmailTemplates};
+use tracing::{info, warn};
 
-use crate::{error::Error, middlewares::flash};
+use crate::{
+    error::Error,
+    middlewares::{cookie_keys::CookieKeys, flash},
+};
 
 /// The application's state that is available in [`crate::controllers`] and [`crate::middlewares`].
 #[derive(Clone)]
 pub struct AppState {
     pub env: Environment,
     pub config: Config,
+    /// The pool to write to the database with, see [`nohead_rs_db::connect_pool`].
     pub db_pool: DbPool,
+    /// The pool to only read from the database with, which is `db_pool` unless a separate
+    /// read pool is configured.
+    pub db_read_pool: DbPool,
+    /// The keys that sign the session and flash cookies.
+    pub cookie_keys: CookieKeys,
     pub flash_config: flash::Config,
     pub email_client: EmailClient,
+    /// Renders the emails in `mailer/templates`.
+    pub email_templates: EmailTemplates,
 }
 
 impl AppState {
     pub async fn build(env: Environment) -> Result<Self, Error> {
-        let config: Config = load_config(&env)?;
+        let config = Self::load_config(&env)?;
+
+        if config.database.auto_migrate {
+            match migrate::run_on_startup(&config.database).await? {
+                StartupMigration::Applied(versions) => {
+                    info!("applied {} pending migrations", versions.len())
+                }
+                StartupMigration::SkippedOnReplica => {
+                    info!("skipped migrations on a LiteFS replica")
+                }
+            }
+        }
+
         let db_pool = connect_pool(&config.database).await?;
-        let flash_config = flash::Config::new(Key::generate());
-        let email_client = EmailClient::new(&config.mailer);
+        let db_read_pool = connect_read_pool(&config.database, &db_pool).await?;
+        let cookie_keys = CookieKeys::from_config(&config.security, &env)?;
+        if config.security.secret_key.is_none() {
+            warn!("no [security] secret_key is set, sessions won't survive a restart");
+        }
+        let flash_config = flash::Config::new(cookie_keys.key.clone());
+        let email_client = EmailClient::new(&config.mailer)?;
+        let email_templates = EmailTemplates::new(&config);
 
         Ok(Self {
             env,
             config,
             db_pool,
+            db_read_pool,
+            cookie_keys,
             flash_config,
             email_client,
+            email_templates,
         })
     }
+
+    /// Loads the configuration of an environment and checks its values, resolving the paths
+    /// in it against the web crate.
+    pub fn load_config(env: &Environment) -> Result<Config, Error> {
+        let config: Config = load_config(env)?;
+        config.validate(Path::new(env!("CARGO_MANIFEST_DIR")))?;
+        CookieKeys::from_config(&config.security, env)?;
+
+        Ok(config)
+    }
 }
 
 /// Allow direct extraction of flash messages in handlers.
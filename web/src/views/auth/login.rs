@@ -3,12 +3,22 @@ use serde_json::json;
 
 use crate::{
     format::{self},
+    forms::FormPage,
     initializers::view_engine::engine::{View, ViewEngine},
     middlewares::flash::IncomingFlashes,
+    redirect::SafeRedirect,
 };
 
 pub enum LoginView {
-    Index(ViewEngine<View>, IncomingFlashes, Option<String>),
+    Index(ViewEngine<View>, IncomingFlashes, Option<SafeRedirect>),
+}
+
+impl LoginView {
+    pub const FORM: FormPage = FormPage {
+        page: "auth/login/index.html",
+        form: "auth/login/form.html",
+        form_id: "login-form",
+    };
 }
 
 impl IntoResponse for LoginView {
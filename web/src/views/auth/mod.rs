@@ -1,3 +1,5 @@
 pub mod login;
+pub mod password_forgot;
+pub mod password_reset;
 pub mod register;
 pub mod register_confirm;
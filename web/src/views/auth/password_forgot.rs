@@ -0,0 +1,26 @@
+use axum::response::{IntoResponse, Response};
+use serde_json::json;
+
+use crate::format;
+use crate::initializers::view_engine::engine::{View, ViewEngine};
+use crate::middlewares::flash::IncomingFlashes;
+
+pub enum PasswordForgotView {
+    Index(ViewEngine<View>, IncomingFlashes),
+}
+
+impl IntoResponse for PasswordForgotView {
+    fn into_response(self) -> Response {
+        match self {
+            PasswordForgotView::Index(ViewEngine(v), IncomingFlashes { flashes, .. }) => {
+                format::render()
+                    .view(
+                        &v,
+                        "auth/password_forgot/index.html",
+                        json!({"flashes": flashes}),
+                    )
+                    .into_response()
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use axum::response::{IntoResponse, Response};
+use serde_json::json;
+
+use crate::format;
+use crate::initializers::view_engine::engine::{View, ViewEngine};
+use crate::middlewares::flash::IncomingFlashes;
+
+pub enum PasswordResetView {
+    Index(ViewEngine<View>, IncomingFlashes, Option<String>),
+}
+
+impl IntoResponse for PasswordResetView {
+    fn into_response(self) -> Response {
+        match self {
+            PasswordResetView::Index(ViewEngine(v), IncomingFlashes { flashes, .. }, token) => {
+                format::render()
+                    .view(
+                        &v,
+                        "auth/password_reset/index.html",
+                        json!({"flashes": flashes, "token": token}),
+                    )
+                    .into_response()
+            }
+        }
+    }
+}
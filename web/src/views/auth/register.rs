@@ -2,6 +2,7 @@ use axum::response::{IntoResponse, Response};
 use serde_json::json;
 
 use crate::format;
+use crate::forms::FormPage;
 use crate::initializers::view_engine::engine::{View, ViewEngine};
 use crate::middlewares::flash::IncomingFlashes;
 
@@ -9,6 +10,14 @@ pub enum RegisterView {
     Index(ViewEngine<View>, IncomingFlashes),
 }
 
+impl RegisterView {
+    pub const FORM: FormPage = FormPage {
+        page: "auth/register/index.html",
+        form: "auth/register/form.html",
+        form_id: "register-form",
+    };
+}
+
 impl IntoResponse for RegisterView {
     fn into_response(self) -> Response {
         match self {
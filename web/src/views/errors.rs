@@ -0,0 +1,113 @@
+use axum::{
+    Json,
+    http::{HeaderName, HeaderValue, StatusCode, header::CONTENT_TYPE},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::json;
+use tracing::error;
+
+use crate::{
+    error::{Error, ErrorReport},
+    format,
+    forms::FieldErrors,
+    initializers::view_engine::engine::{View, ViewEngine},
+};
+
+/// The element htmx swaps error fragments into, see `base.html`.
+const ERRORS_TARGET: &str = "#errors";
+
+const HX_RETARGET: HeaderName = HeaderName::from_static("hx-retarget");
+const HX_RESWAP: HeaderName = HeaderName::from_static("hx-reswap");
+
+pub enum ErrorView {
+    /// A full error page, for requests from the browser.
+    Page(ViewEngine<View>, ErrorReport),
+    /// A fragment that htmx swaps into the errors element of the current page.
+    Fragment(ViewEngine<View>, ErrorReport),
+    /// A problem details body as in RFC 9457, for requests that accept JSON.
+    Problem(ErrorReport),
+}
+
+/// The problem details of an error, see <https://www.rfc-editor.org/rfc/rfc9457>.
+#[derive(Serialize)]
+struct Problem {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    title: &'static str,
+    status: u16,
+    detail: String,
+    /// The error itself, only in development.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<String>,
+    /// What is wrong with each field of the form that was sent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errors: Option<FieldErrors>,
+}
+
+impl IntoResponse for ErrorView {
+    fn into_response(self) -> Response {
+        match self {
+            ErrorView::Page(ViewEngine(v), report) => {
+                let rendered = format::render().status(report.status).view(
+                    &v,
+                    template(report.status),
+                    context(&report),
+                );
+                rendered.unwrap_or_else(|err| fallback(err, report))
+            }
+            ErrorView::Fragment(ViewEngine(v), report) => {
+                let rendered = format::render()
+                    .status(report.status)
+                    .header(HX_RETARGET, ERRORS_TARGET)
+                    .header(HX_RESWAP, "innerHTML")
+                    .view(&v, "errors/fragment.html", context(&report));
+                rendered.unwrap_or_else(|err| fallback(err, report))
+            }
+            ErrorView::Problem(report) => {
+                let problem = Problem {
+                    kind: "about:blank",
+                    title: report.status.canonical_reason().unwrap_or_default(),
+                    status: report.status.as_u16(),
+                    detail: report.message,
+                    details: report.details,
+                    errors: report.fields,
+                };
+                (
+                    report.status,
+                    [(
+                        CONTENT_TYPE,
+                        HeaderValue::from_static("application/problem+json"),
+                    )],
+                    Json(problem),
+                )
+                    .into_response()
+            }
+        }
+    }
+}
+
+/// The page for an error status, client errors without a page of their own share the 422 one.
+fn template(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => "errors/401.html",
+        StatusCode::NOT_FOUND | StatusCode::GONE => "errors/404.html",
+        status if status.is_server_error() => "errors/500.html",
+        _ => "errors/422.html",
+    }
+}
+
+fn context(report: &ErrorReport) -> serde_json::Value {
+    json!({
+        "status": report.status.as_u16(),
+        "message": report.message,
+        "details": report.details,
+        "flashes": [],
+    })
+}
+
+/// Sends the message as text when the error template can't be rendered either.
+fn fallback(err: Error, report: ErrorReport) -> Response {
+    error!("an error occured while rendering an error page: {:?}", err);
+    (report.status, report.message).into_response()
+}
@@ -0,0 +1,90 @@
+use axum::response::{IntoResponse, Response};
+use nohead_rs_mailer::{EmailPayload, Preview, RenderedEmail, SentEmail};
+use nohead_rs_worker::QueuedEmail;
+use serde_json::json;
+
+use crate::{
+    format,
+    initializers::view_engine::engine::{View, ViewEngine},
+    middlewares::flash::IncomingFlashes,
+};
+
+pub enum MailboxView {
+    Index(
+        ViewEngine<View>,
+        Vec<SentEmail>,
+        Vec<QueuedEmail>,
+        Vec<&'static str>,
+        IncomingFlashes,
+    ),
+    /// An email that was sent or queued, along with its payload as JSON.
+    Email(
+        ViewEngine<View>,
+        &'static str,
+        EmailPayload,
+        IncomingFlashes,
+    ),
+    /// A template rendered with sample data, along with the data as JSON.
+    Preview(ViewEngine<View>, Preview, RenderedEmail, IncomingFlashes),
+}
+
+impl IntoResponse for MailboxView {
+    fn into_response(self) -> Response {
+        match self {
+            MailboxView::Index(
+                ViewEngine(v),
+                sent,
+                queued,
+                previews,
+                IncomingFlashes { flashes, .. },
+            ) => format::render()
+                .view(
+                    &v,
+                    "mailbox/index.html",
+                    json!({
+                        "sent": sent,
+                        "queued": queued,
+                        "previews": previews,
+                        "flashes": flashes,
+                    }),
+                )
+                .into_response(),
+            MailboxView::Email(ViewEngine(v), kind, email, IncomingFlashes { flashes, .. }) => {
+                format::render()
+                    .view(
+                        &v,
+                        "mailbox/show.html",
+                        json!({
+                            "kind": kind,
+                            "email": email,
+                            "raw": serde_json::to_string_pretty(&email).unwrap_or_default(),
+                            "flashes": flashes,
+                        }),
+                    )
+                    .into_response()
+            }
+            MailboxView::Preview(
+                ViewEngine(v),
+                preview,
+                rendered,
+                IncomingFlashes { flashes, .. },
+            ) => format::render()
+                .view(
+                    &v,
+                    "mailbox/show.html",
+                    json!({
+                        "kind": "Preview",
+                        "name": preview.name,
+                        "email": {
+                            "subject": rendered.subject,
+                            "html": rendered.html,
+                            "text": rendered.text,
+                        },
+                        "raw": serde_json::to_string_pretty(&preview.context).unwrap_or_default(),
+                        "flashes": flashes,
+                    }),
+                )
+                .into_response(),
+        }
+    }
+}
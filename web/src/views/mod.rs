@@ -1,3 +1,6 @@
 pub mod auth;
+pub mod errors;
 pub mod home;
+pub mod mailbox;
+pub mod openapi;
 pub mod todos;
@@ -0,0 +1,22 @@
+use axum::response::{IntoResponse, Response};
+use serde_json::json;
+
+use crate::{
+    format,
+    initializers::view_engine::engine::{View, ViewEngine},
+};
+
+pub enum OpenApiView {
+    /// The API reference of the document at the url.
+    Docs(ViewEngine<View>, &'static str),
+}
+
+impl IntoResponse for OpenApiView {
+    fn into_response(self) -> Response {
+        match self {
+            OpenApiView::Docs(ViewEngine(v), spec_url) => format::render()
+                .view(&v, "api/docs.html", json!({ "spec_url": spec_url }))
+                .into_response(),
+        }
+    }
+}
@@ -1,18 +1,33 @@
 use axum::response::{IntoResponse, Response};
-use nohead_rs_db::entities::todo::Todo;
+use nohead_rs_db::{entities::todo::Todo, pagination::Page};
 use serde_json::json;
 
 use crate::{
     format,
+    forms::FormPage,
     initializers::view_engine::engine::{View, ViewEngine},
     middlewares::flash::IncomingFlashes,
 };
 
 pub enum TodoView {
-    Index(ViewEngine<View>, Vec<Todo>, IncomingFlashes),
+    Index(ViewEngine<View>, Page<Todo>, IncomingFlashes),
     Show(ViewEngine<View>, Todo, IncomingFlashes),
 }
 
+impl TodoView {
+    pub const CREATE_FORM: FormPage = FormPage {
+        page: "todos/new.html",
+        form: "todos/form.html",
+        form_id: "todo-form",
+    };
+
+    pub const UPDATE_FORM: FormPage = FormPage {
+        page: "todos/show.html",
+        form: "todos/update.html",
+        form_id: "update-todo-form",
+    };
+}
+
 impl IntoResponse for TodoView {
     fn into_response(self) -> Response {
         match self {
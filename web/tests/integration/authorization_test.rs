@@ -0,0 +1,95 @@
+use super::{create_confirmed_user, test_request_with_db};
+use fake::{Fake as _, Faker};
+use nohead_rs_db::{
+    DbPool, MIGRATOR,
+    entities::{
+        permission::Permission,
+        role::Role,
+        user::{RegisterUser, User, UserCredentials},
+    },
+};
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn new_users_are_assigned_the_default_role(pool: DbPool) {
+    let user: RegisterUser = Faker.fake();
+
+    let user = User::create(user, &pool)
+        .await
+        .expect("failed to create user in test db");
+
+    let roles = Role::load_for_user(user.id, &pool).await.unwrap();
+
+    assert!(
+        roles.iter().any(|role| role.name == "user"),
+        "new users should have the user role"
+    );
+
+    let permissions = Permission::load_for_user(user.id, &pool).await.unwrap();
+
+    assert!(
+        permissions.contains(&Permission::from("todos.read_all")),
+        "the user role should be able to list todos"
+    );
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn user_without_roles_is_forbidden(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |mut request| async move {
+        request.save_cookies();
+
+        let user: RegisterUser = Faker.fake();
+
+        let saved_user = create_confirmed_user(user.clone(), &pool).await;
+        Role::revoke(saved_user.id, "user", &pool).await.unwrap();
+
+        request
+            .post("/auth/login")
+            .form(&UserCredentials {
+                email: user.email,
+                password: user.password,
+                next: None,
+            })
+            .await;
+
+        request.get("/todos").await.assert_status_forbidden();
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn permissions_are_checked_per_action(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |mut request| async move {
+        request.save_cookies();
+
+        // A role that can only list todos
+        sqlx::query!(
+            r#"INSERT INTO roles (name) VALUES ('reader');
+            INSERT INTO roles_permissions (role_id, permission_id)
+            SELECT roles.id, permissions.id FROM roles, permissions
+            WHERE roles.name = 'reader' AND permissions.name = 'todos.read_all'"#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let user: RegisterUser = Faker.fake();
+
+        let saved_user = create_confirmed_user(user.clone(), &pool).await;
+        Role::revoke(saved_user.id, "user", &pool).await.unwrap();
+        Role::assign(saved_user.id, "reader", &pool).await.unwrap();
+
+        request
+            .post("/auth/login")
+            .form(&UserCredentials {
+                email: user.email,
+                password: user.password,
+                next: None,
+            })
+            .await;
+
+        request.get("/todos").await.assert_status_ok();
+
+        request.delete("/todos/1").await.assert_status_forbidden();
+    })
+    .await
+}
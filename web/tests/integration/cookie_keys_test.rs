@@ -0,0 +1,100 @@
+use super::create_confirmed_user;
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use fake::{Fake as _, Faker};
+use nohead_rs_config::{Environment, SecurityConfig};
+use nohead_rs_db::{
+    DbPool, MIGRATOR,
+    entities::user::{RegisterUser, UserCredentials},
+};
+use nohead_rs_web::{
+    app::App,
+    middlewares::{
+        cookie_keys::{CookieKeys, generate_secret_key},
+        flash,
+    },
+    state::AppState,
+};
+
+/// Starts an app that signs its cookies with `secret_key`, still accepting `old_secret_keys`.
+async fn server_with_keys(pool: &DbPool, secret_key: &str, old_secret_keys: &[&str]) -> TestServer {
+    let mut app_state = AppState::build(Environment::Test)
+        .await
+        .expect("failed to build app state");
+    app_state.db_pool = pool.clone();
+    app_state.db_read_pool = pool.clone();
+
+    let config = SecurityConfig {
+        secret_key: Some(secret_key.to_string()),
+        old_secret_keys: old_secret_keys.iter().map(|key| key.to_string()).collect(),
+        ..Default::default()
+    };
+    app_state.cookie_keys = CookieKeys::from_config(&config, &Environment::Production).unwrap();
+    app_state.flash_config = flash::Config::new(app_state.cookie_keys.key.clone());
+
+    let app = App::build(app_state).expect("failed to boot test app");
+
+    TestServer::new(app.router).expect("unable to start test server")
+}
+
+#[test]
+fn production_requires_a_secret_key() {
+    let config = SecurityConfig::default();
+
+    assert!(CookieKeys::from_config(&config, &Environment::Production).is_err());
+    assert!(CookieKeys::from_config(&config, &Environment::Development).is_ok());
+}
+
+#[test]
+fn short_keys_are_rejected() {
+    let config = SecurityConfig {
+        secret_key: Some("c2hvcnQ=".to_string()),
+        ..Default::default()
+    };
+
+    assert!(CookieKeys::from_config(&config, &Environment::Development).is_err());
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn sessions_survive_a_key_rotation(pool: DbPool) {
+    let old_key = generate_secret_key();
+    let new_key = generate_secret_key();
+
+    let user: RegisterUser = Faker.fake();
+    create_confirmed_user(user.clone(), &pool).await;
+
+    let before = server_with_keys(&pool, &old_key, &[]).await;
+    let session = before
+        .post("/auth/login")
+        .form(&UserCredentials {
+            email: user.email,
+            password: user.password,
+            next: None,
+        })
+        .await
+        .cookie("id");
+
+    // The same key is configured after a restart.
+    let restarted = server_with_keys(&pool, &old_key, &[]).await;
+    restarted
+        .get("/todos")
+        .add_cookie(session.clone())
+        .await
+        .assert_status_ok();
+
+    // The old key is still accepted after it was rotated.
+    let rotated = server_with_keys(&pool, &new_key, &[&old_key]).await;
+    rotated
+        .get("/todos")
+        .add_cookie(session.clone())
+        .await
+        .assert_status_ok();
+
+    // Once the old key is dropped, its cookies are rejected.
+    server_with_keys(&pool, &new_key, &[])
+        .await
+        .get("/todos")
+        .add_cookie(session)
+        .await
+        .assert_status(StatusCode::TEMPORARY_REDIRECT);
+}
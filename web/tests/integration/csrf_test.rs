@@ -0,0 +1,87 @@
+use axum::http::{HeaderValue, StatusCode};
+use axum_test::{TestServer, TestServerBuilder};
+use nohead_rs_config::Environment;
+use nohead_rs_db::{DbPool, MIGRATOR};
+use nohead_rs_web::{app::App, middlewares::csrf::CSRF_HEADER, state::AppState};
+
+/// Starts an app that checks CSRF tokens, which the test environment turns off.
+async fn server_with_csrf(pool: &DbPool) -> TestServer {
+    let mut app_state = AppState::build(Environment::Test)
+        .await
+        .expect("failed to build app state");
+    app_state.db_pool = pool.clone();
+    app_state.db_read_pool = pool.clone();
+    app_state.config.security.csrf = true;
+
+    let app = App::build(app_state).expect("failed to boot test app");
+    let config = TestServerBuilder::new().save_cookies().into_config();
+
+    TestServer::new_with_config(app.router, config).expect("unable to start test server")
+}
+
+/// Loads a page to get the token of the session, checking that it was rendered into the page.
+async fn csrf_token(server: &TestServer) -> String {
+    let response = server.get("/auth/login").await;
+    let token = response.header(CSRF_HEADER).to_str().unwrap().to_string();
+
+    assert!(
+        response
+            .text()
+            .contains(&format!(r#"name="csrf_token" value="{token}""#)),
+        "the token should be rendered into forms"
+    );
+
+    token
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn forms_without_the_token_are_forbidden(pool: DbPool) {
+    let server = server_with_csrf(&pool).await;
+    csrf_token(&server).await;
+
+    let response = server
+        .post("/auth/logout")
+        .form(&[("csrf_token", "not-the-token")])
+        .await;
+    response.assert_status(StatusCode::FORBIDDEN);
+    response.assert_text_contains("invalid CSRF token");
+
+    server
+        .post("/auth/logout")
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn forms_with_the_token_are_accepted(pool: DbPool) {
+    let server = server_with_csrf(&pool).await;
+    let token = csrf_token(&server).await;
+
+    server
+        .post("/auth/logout")
+        .form(&[("csrf_token", &token)])
+        .await
+        .assert_status_see_other();
+
+    // As htmx sends it.
+    server
+        .post("/auth/logout")
+        .add_header(CSRF_HEADER, HeaderValue::from_str(&token).unwrap())
+        .await
+        .assert_status_see_other();
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn tokens_are_tied_to_the_browser(pool: DbPool) {
+    let server = server_with_csrf(&pool).await;
+    let token = csrf_token(&server).await;
+
+    let other_browser = server_with_csrf(&pool).await;
+    csrf_token(&other_browser).await;
+
+    other_browser
+        .post("/auth/logout")
+        .add_header(CSRF_HEADER, HeaderValue::from_str(&token).unwrap())
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,102 @@
+use axum::http::{HeaderName, HeaderValue, StatusCode, header};
+use axum_test::TestServer;
+use nohead_rs_config::Environment;
+use nohead_rs_db::{DbPool, MIGRATOR};
+use nohead_rs_web::{app::App, state::AppState};
+use serde_json::Value;
+
+use crate::{authenticated_request, test_request_with_db};
+
+async fn server_in(env: Environment, pool: &DbPool) -> TestServer {
+    let mut app_state = AppState::build(Environment::Test)
+        .await
+        .expect("failed to build app state");
+    app_state.env = env;
+    app_state.db_pool = pool.clone();
+    app_state.db_read_pool = pool.clone();
+
+    let app = App::build(app_state).expect("failed to boot test app");
+
+    TestServer::new(app.router).expect("unable to start test server")
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn unknown_pages_render_the_not_found_page(pool: DbPool) {
+    test_request_with_db::<_, _>(pool, |request| async move {
+        let response = request.get("/does-not-exist").await;
+
+        response.assert_status_not_found();
+        assert_eq!(
+            response.header(header::CONTENT_TYPE),
+            mime::TEXT_HTML_UTF_8.as_ref()
+        );
+        response.assert_text_contains("<h1>Not found</h1>");
+        response.assert_text_contains("page not found");
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn htmx_requests_get_a_fragment_for_the_errors_element(pool: DbPool) {
+    authenticated_request::<_, _>(pool, |request| async move {
+        let response = request
+            .get("/todos/999")
+            .add_header(
+                HeaderName::from_static("hx-request"),
+                HeaderValue::from_static("true"),
+            )
+            .await;
+
+        response.assert_status_not_found();
+        assert_eq!(response.header("hx-retarget"), "#errors");
+        assert_eq!(response.header("hx-reswap"), "innerHTML");
+        response.assert_text_contains("no record found");
+        assert!(
+            !response.text().contains("<html"),
+            "htmx should only get a fragment"
+        );
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn json_requests_get_problem_details(pool: DbPool) {
+    let server = server_in(Environment::Production, &pool).await;
+
+    let response = server
+        .get("/does-not-exist")
+        .add_header(header::ACCEPT, HeaderValue::from_static("application/json"))
+        .await;
+
+    response.assert_status(StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.header(header::CONTENT_TYPE),
+        "application/problem+json"
+    );
+    let problem: Value = response.json();
+    assert_eq!(problem["status"], 404);
+    assert_eq!(problem["title"], "Not Found");
+    assert_eq!(problem["detail"], "page not found");
+    assert!(
+        problem.get("details").is_none(),
+        "the error should be hidden outside of development"
+    );
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn errors_are_shown_in_development(pool: DbPool) {
+    let server = server_in(Environment::Development, &pool).await;
+
+    let response = server
+        .get("/does-not-exist")
+        .add_header(header::ACCEPT, HeaderValue::from_static("application/json"))
+        .await;
+
+    let problem: Value = response.json();
+    assert_eq!(problem["details"], "NotFound");
+
+    server
+        .get("/does-not-exist")
+        .await
+        .assert_text_contains("<pre>NotFound</pre>");
+}
@@ -1,2 +1 @@
-INSERT INTO todos (description) VALUES ('buy milk');
-
+INSERT INTO todos (description, user_id) VALUES ('buy milk', 1);
@@ -0,0 +1,98 @@
+use axum::http::{HeaderName, HeaderValue, header};
+use fake::{Fake, Faker};
+use nohead_rs_db::{DbPool, MIGRATOR, entities::user::RegisterUser};
+use serde_json::Value;
+
+use crate::{authenticated_request, create_confirmed_user, test_request_with_db};
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn invalid_forms_are_shown_again_with_errors_and_values(pool: DbPool) {
+    test_request_with_db::<_, _>(pool, |request| async move {
+        let response = request
+            .post("/auth/register")
+            .form(&[
+                ("email", "not-an-email"),
+                ("password", "short"),
+                ("confirm_password", "different"),
+            ])
+            .await;
+
+        response.assert_status_unprocessable_entity();
+        response.assert_text_contains("<h1>Register</h1>");
+        response.assert_text_contains(r#"value="not-an-email""#);
+        response.assert_text_contains("Must be a valid email address");
+        response.assert_text_contains("password must be at least 8 characters");
+        response.assert_text_contains("passwords do not match");
+        assert!(
+            !response.text().contains("short"),
+            "passwords should not be sent back"
+        );
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn taken_emails_are_shown_as_field_errors(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let user: RegisterUser = Faker.fake();
+        create_confirmed_user(user.clone(), &pool).await;
+
+        let response = request
+            .post("/auth/register")
+            .form(&[
+                ("email", user.email.as_str()),
+                ("password", user.password.as_str()),
+                ("confirm_password", user.password.as_str()),
+            ])
+            .await;
+
+        response.assert_status_unprocessable_entity();
+        response.assert_text_contains("email is already taken");
+        response.assert_text_contains(format!(r#"value="{}""#, user.email));
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn htmx_requests_get_only_the_form(pool: DbPool) {
+    authenticated_request::<_, _>(pool, |request| async move {
+        let response = request
+            .post("/todos")
+            .add_header(
+                HeaderName::from_static("hx-request"),
+                HeaderValue::from_static("true"),
+            )
+            .form(&[("description", "")])
+            .await;
+
+        response.assert_status_unprocessable_entity();
+        assert_eq!(response.header("hx-retarget"), "#todo-form");
+        assert_eq!(response.header("hx-reswap"), "outerHTML");
+        response.assert_text_contains(r#"<form id="todo-form""#);
+        response.assert_text_contains("Description must be at least 1 character long");
+        assert!(
+            !response.text().contains("<html"),
+            "htmx should only get the form"
+        );
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn json_requests_get_the_field_errors(pool: DbPool) {
+    authenticated_request::<_, _>(pool, |request| async move {
+        let response = request
+            .post("/todos")
+            .add_header(header::ACCEPT, HeaderValue::from_static("application/json"))
+            .form(&[("description", "")])
+            .await;
+
+        response.assert_status_unprocessable_entity();
+        let problem: Value = response.json();
+        assert_eq!(
+            problem["errors"]["description"][0],
+            "Description must be at least 1 character long"
+        );
+    })
+    .await
+}
@@ -1,10 +1,10 @@
-use super::test_request_with_db;
+use super::{create_confirmed_user, test_request_with_db};
 use fake::{Fake as _, Faker};
 use nohead_rs_db::{
     DbPool, MIGRATOR,
     entities::{
         session::Session,
-        user::{RegisterUser, User, UserCredentials},
+        user::{RegisterUser, User, UserCredentials, UserStatus},
     },
 };
 
@@ -13,7 +13,7 @@ async fn login_creates_session_on_success(pool: DbPool) {
     test_request_with_db::<_, _>(pool.clone(), |request| async move {
         let user: RegisterUser = Faker.fake();
 
-        User::create(user.clone(), &pool).await.unwrap();
+        create_confirmed_user(user.clone(), &pool).await;
 
         let response = request
             .post("/auth/login")
@@ -134,3 +134,180 @@ async fn login_redirects_to_login_page_for_invalid_user(pool: DbPool) {
     })
     .await
 }
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn login_redirects_pending_user_to_confirm_page(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let user: RegisterUser = Faker.fake();
+
+        // The user has registered but not confirmed their email yet
+        User::create(user.clone(), &pool)
+            .await
+            .expect("failed to create user in test db");
+
+        let response = request
+            .post("/auth/login")
+            .form(&UserCredentials {
+                email: user.email,
+                password: user.password,
+                next: None,
+            })
+            .await;
+
+        response.assert_status_see_other();
+
+        assert!(
+            response.maybe_cookie("id").is_none(),
+            "oops a session cookie was created for a pending user"
+        );
+
+        let location = response
+            .headers()
+            .get("location")
+            .expect("unable to get redirect location header from response")
+            .to_str()
+            .unwrap();
+
+        assert_eq!(
+            location, "/auth/register/confirm",
+            "redirected to the wrong page"
+        );
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn login_does_not_create_session_for_suspended_user(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let user: RegisterUser = Faker.fake();
+
+        let saved_user = create_confirmed_user(user.clone(), &pool).await;
+        User::update_status(saved_user.id, UserStatus::Suspended, &pool)
+            .await
+            .unwrap();
+
+        let response = request
+            .post("/auth/login")
+            .form(&UserCredentials {
+                email: user.email,
+                password: user.password,
+                next: None,
+            })
+            .await;
+
+        assert!(
+            response.maybe_cookie("id").is_none(),
+            "oops a session cookie was created for a suspended user"
+        );
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn suspending_a_user_ends_their_session(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |mut request| async move {
+        request.save_cookies();
+
+        let user: RegisterUser = Faker.fake();
+
+        let saved_user = create_confirmed_user(user.clone(), &pool).await;
+
+        request
+            .post("/auth/login")
+            .form(&UserCredentials {
+                email: user.email,
+                password: user.password,
+                next: None,
+            })
+            .await;
+
+        request.get("/todos").await.assert_status_ok();
+
+        User::update_status(saved_user.id, UserStatus::Suspended, &pool)
+            .await
+            .unwrap();
+
+        let response = request.get("/todos").await;
+
+        let location = response
+            .headers()
+            .get("location")
+            .expect("the suspended user should be redirected to the login page")
+            .to_str()
+            .unwrap();
+
+        assert!(
+            location.starts_with("/auth/login"),
+            "the suspended user should no longer be authenticated"
+        );
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn login_redirects_to_next_only_on_this_site(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let user: RegisterUser = Faker.fake();
+
+        create_confirmed_user(user.clone(), &pool).await;
+
+        for (next, expected) in [
+            ("/todos?page=2", "/todos?page=2"),
+            ("http://localhost/todos", "/todos"),
+            ("https://evil.example", "/"),
+            ("//evil.example", "/"),
+            ("/\\evil.example", "/"),
+        ] {
+            let response = request
+                .post("/auth/login")
+                .form(&UserCredentials {
+                    email: user.email.clone(),
+                    password: user.password.clone(),
+                    next: Some(next.to_string()),
+                })
+                .await;
+
+            let location = response
+                .headers()
+                .get("location")
+                .expect("unable to get redirect location header from response")
+                .to_str()
+                .unwrap();
+
+            assert_eq!(
+                location, expected,
+                "redirected to the wrong page for {next}"
+            );
+        }
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn login_keeps_next_encoded_on_failure(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let user: RegisterUser = Faker.fake();
+
+        let response = request
+            .post("/auth/login")
+            .form(&UserCredentials {
+                email: user.email,
+                password: user.password,
+                next: Some("/todos?page=2&next=https://evil.example".to_string()),
+            })
+            .await;
+
+        let location = response
+            .headers()
+            .get("location")
+            .expect("unable to get redirect location header from response")
+            .to_str()
+            .unwrap();
+
+        assert_eq!(
+            location, "/auth/login?next=%2Ftodos%3Fpage%3D2%26next%3Dhttps%3A%2F%2Fevil.example",
+            "next should not add parameters to the login page"
+        );
+    })
+    .await
+}
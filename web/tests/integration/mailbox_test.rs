@@ -0,0 +1,84 @@
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use nohead_rs_config::Environment;
+use nohead_rs_db::{DbPool, MIGRATOR};
+use nohead_rs_mailer::{EmailClient, EmailPayload};
+use nohead_rs_web::{app::App, state::AppState};
+
+use crate::test_request;
+
+async fn dev_server(pool: &DbPool) -> (TestServer, EmailClient) {
+    let mut app_state = AppState::build(Environment::Test)
+        .await
+        .expect("failed to build app state");
+    app_state.env = Environment::Development;
+    app_state.db_pool = pool.clone();
+    app_state.db_read_pool = pool.clone();
+    let email_client = app_state.email_client.clone();
+
+    let app = App::build(app_state).expect("failed to boot test app");
+    let server = TestServer::new(app.router).expect("unable to start test server");
+
+    (server, email_client)
+}
+
+#[tokio::test]
+async fn the_mailbox_is_only_mounted_in_development() {
+    test_request(|request| async move {
+        request
+            .get("/_dev/mailbox")
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    })
+    .await;
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn sent_emails_are_listed_and_shown(pool: DbPool) {
+    let (server, email_client) = dev_server(&pool).await;
+    let email = EmailPayload::builder()
+        .from("dev@notebar.io")
+        .to("jane@example.com")
+        .reply_to("support@notebar.io")
+        .subject("Your invoice")
+        .html("<p>Attached</p>")
+        .text("Attached")
+        .build()
+        .unwrap();
+    email_client.send_email(email).await.unwrap();
+
+    let response = server.get("/_dev/mailbox").await;
+    response.assert_status_ok();
+    response.assert_text_contains("<a href=\"/_dev/mailbox/sent/1\">Your invoice</a>");
+    // minijinja escapes the slashes of the name, which browsers read as slashes again.
+    response.assert_text_contains("/_dev/mailbox/previews/auth&#x2f;confirmation");
+
+    let response = server.get("/_dev/mailbox/sent/1").await;
+    response.assert_status_ok();
+    response.assert_text_contains("<h1>Your invoice</h1>");
+    response.assert_text_contains("srcdoc=\"&lt;p&gt;Attached&lt;&#x2f;p&gt;\"");
+    response.assert_text_contains("<pre>Attached</pre>");
+    response
+        .assert_text_contains("&quot;reply_to&quot;: [\n    &quot;support@notebar.io&quot;\n  ]");
+
+    server
+        .get("/_dev/mailbox/sent/2")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn templates_are_previewed_with_sample_data(pool: DbPool) {
+    let (server, _) = dev_server(&pool).await;
+
+    let response = server.get("/_dev/mailbox/previews/auth/confirmation").await;
+    response.assert_status_ok();
+    response.assert_text_contains("<h1>Please confirm your registration</h1>");
+    response.assert_text_contains("123456");
+    response.assert_text_contains("&quot;register_token&quot;: &quot;123456&quot;");
+
+    server
+        .get("/_dev/mailbox/previews/auth/unknown")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
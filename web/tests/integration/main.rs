@@ -1,14 +1,27 @@
+mod api_test;
+mod authorization_test;
+mod cookie_keys_test;
+mod csrf_test;
+mod errors_test;
+mod forms_test;
 mod login_test;
+mod mailbox_test;
+mod migrate_test;
+mod openapi_test;
+mod password_reset_test;
+mod pool_test;
+mod register_confirm_test;
+mod routes_test;
 mod todos_test;
 
-use std::sync::OnceLock;
+use std::{env, fs, sync::OnceLock};
 
 use axum_test::{TestServer, TestServerBuilder};
 use fake::{Fake, Faker};
-use nohead_rs_config::Environment;
+use nohead_rs_config::{DatabaseConfig, Environment};
 use nohead_rs_db::{
     DbPool,
-    entities::user::{RegisterUser, User, UserCredentials},
+    entities::user::{RegisterUser, User, UserCredentials, UserStatus},
 };
 use nohead_rs_web::{app::App, state::AppState, tracing::Tracing};
 
@@ -22,10 +35,33 @@ fn lazy_eyre() {
     EYRE.get_or_init(|| color_eyre::install().expect("failed to initialize Eyre"));
 }
 
+/// Creates a user that has already confirmed their email, so that they are allowed to log in.
+pub async fn create_confirmed_user(user: RegisterUser, pool: &DbPool) -> User {
+    let saved_user = User::create(user, pool).await.unwrap();
+
+    User::update_status(saved_user.id, UserStatus::Confirmed, pool)
+        .await
+        .unwrap()
+}
+
+/// Points a database config at a fresh file in the temp directory, for tests that need to
+/// create or connect to a database themselves rather than use the one `sqlx::test` sets up.
+pub fn temp_database(name: &str) -> DatabaseConfig {
+    let dir = env::temp_dir().join(format!("nohead-rs-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    DatabaseConfig {
+        url: format!("sqlite://{}", dir.join("app.db").display()),
+        auto_migrate: true,
+        ..Default::default()
+    }
+}
+
 pub async fn mock_logged_in_state(request: &TestServer, pool: &DbPool) -> User {
     let user: RegisterUser = Faker.fake();
 
-    let saved_user = User::create(user.clone(), pool).await.unwrap();
+    let saved_user = create_confirmed_user(user.clone(), pool).await;
 
     request
         .post("/auth/login")
@@ -52,6 +88,7 @@ where
     // [sqlx::test] sets up a test database when running the test and cleans up afterwards
     // https://docs.rs/sqlx/latest/sqlx/attr.test.html
     app_state.db_pool = test_db.clone();
+    app_state.db_read_pool = test_db.clone();
 
     if std::env::var("TEST_LOG").is_ok() {
         lazy_tracing(&app_state);
@@ -86,6 +123,7 @@ where
 
     // [sqlx::test] sets up a test database when running the test and cleans up afterwards
     // https://docs.rs/sqlx/latest/sqlx/attr.test.html
+    app_state.db_read_pool = test_db.clone();
     app_state.db_pool = test_db;
 
     if std::env::var("TEST_LOG").is_ok() {
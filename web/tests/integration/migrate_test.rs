@@ -0,0 +1,191 @@
+use std::{env, fs, path::Path};
+
+use super::temp_database;
+use nohead_rs_db::{
+    DbPool, Error, MIGRATOR, connect_pool,
+    migrate::{self, StartupMigration},
+};
+use sqlx::migrate::Migrator;
+
+#[tokio::test]
+async fn startup_creates_and_migrates_the_database() {
+    let config = temp_database("startup");
+
+    let StartupMigration::Applied(applied) = migrate::run_on_startup(&config).await.unwrap() else {
+        panic!("should migrate a primary database");
+    };
+    assert!(!applied.is_empty(), "should apply every migration");
+
+    assert_eq!(
+        migrate::run_on_startup(&config).await.unwrap(),
+        StartupMigration::Applied(vec![]),
+        "should have nothing left to apply"
+    );
+}
+
+#[tokio::test]
+async fn startup_refuses_unknown_migrations() {
+    let config = temp_database("unknown");
+    migrate::run_on_startup(&config).await.unwrap();
+
+    let db_pool = connect_pool(&config).await.unwrap();
+    sqlx::query(
+        "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+        VALUES (99990101000000, 'from a newer build', true, x'00', 0)",
+    )
+    .execute(&db_pool)
+    .await
+    .unwrap();
+
+    let result = migrate::run_on_startup(&config).await;
+
+    assert!(
+        matches!(result, Err(Error::UnknownMigrations(ref versions)) if versions == &[99990101000000]),
+        "should refuse to start against a newer schema"
+    );
+}
+
+#[tokio::test]
+async fn startup_does_not_migrate_litefs_replicas() {
+    let config = temp_database("replica");
+    migrate::create_database_if_missing(&config).await.unwrap();
+    let path = config.url.trim_start_matches("sqlite://");
+    fs::write(
+        std::path::Path::new(path).with_file_name(".primary"),
+        "primary.internal",
+    )
+    .unwrap();
+
+    assert!(migrate::is_litefs_replica(&config));
+    assert_eq!(
+        migrate::run_on_startup(&config).await.unwrap(),
+        StartupMigration::SkippedOnReplica
+    );
+
+    let db_pool = connect_pool(&config).await.unwrap();
+    assert!(
+        migrate::unknown_migrations(&db_pool)
+            .await
+            .unwrap()
+            .is_empty(),
+        "should not have created the migrations table"
+    );
+    let status = migrate::status(&db_pool).await.unwrap();
+    assert!(status.iter().all(|migration| !migration.applied));
+}
+
+#[tokio::test]
+async fn todos_without_owners_are_given_to_the_first_admin() {
+    let db_pool = migrate_before_todo_owners("todo-owners").await;
+
+    sqlx::raw_sql(
+        "INSERT INTO users (id, email, password_hash, status) VALUES
+            (1, 'jane@example.com', 'hash', 'confirmed'),
+            (2, 'joe@example.com', 'hash', 'confirmed'),
+            (3, 'ann@example.com', 'hash', 'confirmed');
+        INSERT INTO users_roles (user_id, role_id) SELECT id, (SELECT id FROM roles WHERE name = 'admin')
+            FROM users WHERE id IN (2, 3);
+        INSERT INTO todos (id, description, user_id) VALUES
+            (1, 'from before owners', NULL),
+            (2, 'owned', 1);",
+    )
+    .execute(&db_pool)
+    .await
+    .unwrap();
+
+    MIGRATOR.run(&db_pool).await.unwrap();
+
+    let owners: Vec<(i64, i64)> = sqlx::query_as("SELECT id, user_id FROM todos ORDER BY id")
+        .fetch_all(&db_pool)
+        .await
+        .unwrap();
+    assert_eq!(owners, [(1, 2), (2, 1)]);
+
+    let result = sqlx::query("INSERT INTO todos (description, user_id) VALUES ('ownerless', NULL)")
+        .execute(&db_pool)
+        .await;
+    assert!(result.is_err(), "todos should require an owner");
+}
+
+#[tokio::test]
+async fn todos_without_owners_are_given_to_the_first_user_without_an_admin() {
+    let db_pool = migrate_before_todo_owners("todo-owners-no-admin").await;
+
+    sqlx::raw_sql(
+        "INSERT INTO users (id, email, password_hash, status) VALUES
+            (2, 'joe@example.com', 'hash', 'confirmed'),
+            (3, 'ann@example.com', 'hash', 'confirmed');
+        INSERT INTO todos (id, description, user_id) VALUES
+            (1, 'from before owners', NULL),
+            (2, 'owned', 3);",
+    )
+    .execute(&db_pool)
+    .await
+    .unwrap();
+
+    MIGRATOR.run(&db_pool).await.unwrap();
+
+    let owners: Vec<(i64, i64)> = sqlx::query_as("SELECT id, user_id FROM todos ORDER BY id")
+        .fetch_all(&db_pool)
+        .await
+        .unwrap();
+    assert_eq!(owners, [(1, 2), (2, 3)]);
+}
+
+#[tokio::test]
+async fn todos_without_owners_fail_the_migration_without_users() {
+    let db_pool = migrate_before_todo_owners("todo-owners-no-users").await;
+
+    sqlx::query(
+        "INSERT INTO todos (id, description, user_id) VALUES (1, 'from before owners', NULL)",
+    )
+    .execute(&db_pool)
+    .await
+    .unwrap();
+
+    let result = MIGRATOR.run(&db_pool).await;
+
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("todos without owners need a user"),
+        "should say why the migration failed"
+    );
+    let todos: Vec<(i64,)> = sqlx::query_as("SELECT id FROM todos")
+        .fetch_all(&db_pool)
+        .await
+        .unwrap();
+    assert_eq!(todos, [(1,)], "should keep the todos");
+}
+
+/// A new database migrated up to before todos required owners.
+async fn migrate_before_todo_owners(name: &str) -> DbPool {
+    let config = temp_database(name);
+    migrate::create_database_if_missing(&config).await.unwrap();
+    let db_pool = connect_pool(&config).await.unwrap();
+
+    let migrations = Path::new(env!("CARGO_MANIFEST_DIR")).join("../db/migrations");
+    let before = env::temp_dir().join(format!(
+        "nohead-rs-migrations-before-owners-{name}-{}",
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&before);
+    fs::create_dir_all(&before).unwrap();
+    for entry in fs::read_dir(&migrations).unwrap() {
+        let path = entry.unwrap().path();
+        let name = path.file_name().unwrap().to_str().unwrap().to_string();
+        if name.as_str() < "20250326091204" {
+            fs::copy(&path, before.join(name)).unwrap();
+        }
+    }
+    Migrator::new(before.as_path())
+        .await
+        .unwrap()
+        .run(&db_pool)
+        .await
+        .unwrap();
+    fs::remove_dir_all(&before).unwrap();
+
+    db_pool
+}
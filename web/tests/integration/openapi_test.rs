@@ -0,0 +1,85 @@
+use crate::test_request;
+
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn the_api_is_described_by_an_openapi_document() {
+    test_request(|request| async move {
+        let response = request.get("/api/openapi.json").await;
+        response.assert_status_ok();
+        let doc: Value = response.json();
+
+        assert_eq!(doc["openapi"], "3.1.0");
+        let todos = &doc["paths"]["/api/v1/todos"];
+        assert_eq!(
+            todos["post"]["requestBody"]["content"]["application/json"]["schema"],
+            json!({ "$ref": "#/components/schemas/TodoChangeset" })
+        );
+        assert_eq!(
+            todos["get"]["security"],
+            json!([{ "bearerAuth": [] }]),
+            "the API should be authenticated with tokens"
+        );
+
+        let todo = &doc["paths"]["/api/v1/todos/{id}"]["get"];
+        for status in ["200", "401", "403", "404"] {
+            assert!(
+                todo["responses"][status].is_object(),
+                "loading a todo should answer {status}"
+            );
+        }
+        assert_eq!(
+            todo["responses"]["404"]["content"]["application/problem+json"]["schema"],
+            json!({ "$ref": "#/components/schemas/Problem" })
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn schemas_include_validation_constraints() {
+    test_request(|request| async move {
+        let doc: Value = request.get("/api/openapi.json").await.json();
+        let schemas = &doc["components"]["schemas"];
+
+        let description = &schemas["TodoChangeset"]["properties"]["description"];
+        assert_eq!(description["type"], "string");
+        assert_eq!(description["minLength"], 1);
+        assert_eq!(schemas["TodoChangeset"]["required"], json!(["description"]));
+
+        let register_user = &schemas["RegisterUser"]["properties"];
+        assert_eq!(register_user["email"]["format"], "email");
+        assert_eq!(register_user["password"]["minLength"], 8);
+
+        let credentials = &schemas["UserCredentials"];
+        assert_eq!(
+            credentials["properties"]["next"]["type"],
+            json!(["string", "null"])
+        );
+        assert!(
+            !credentials["required"]
+                .as_array()
+                .unwrap()
+                .contains(&json!("next")),
+            "optional fields should not be required"
+        );
+
+        assert!(
+            schemas["ApiToken"]["properties"].get("user_id").is_none(),
+            "skipped fields should not be described"
+        );
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn api_docs_are_only_browsable_in_development() {
+    test_request(|request| async move {
+        request
+            .get("/api/docs")
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    })
+    .await;
+}
@@ -0,0 +1,201 @@
+use super::{create_confirmed_user, test_request_with_db};
+use fake::{Fake as _, Faker};
+use nohead_rs_db::{
+    DbPool, MIGRATOR,
+    entities::{
+        password_reset_token::{ForgotPassword, PasswordResetToken, ResetPassword},
+        user::{RegisterUser, User, UserCredentials},
+    },
+};
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn forgot_password_creates_reset_token_for_existing_user(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let user: RegisterUser = Faker.fake();
+
+        let user = User::create(user, &pool)
+            .await
+            .expect("failed to create user in test db");
+
+        let response = request
+            .post("/auth/password/forgot")
+            .form(&ForgotPassword {
+                email: user.email.clone(),
+            })
+            .await;
+
+        response.assert_status_see_other();
+
+        let reset_token = sqlx::query!(
+            "SELECT user_id FROM password_reset_tokens WHERE user_id = ?",
+            user.id
+        )
+        .fetch_optional(&pool)
+        .await
+        .unwrap();
+
+        assert!(reset_token.is_some(), "no reset token was created");
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn forgot_password_responds_the_same_for_unknown_email(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let response = request
+            .post("/auth/password/forgot")
+            .form(&ForgotPassword {
+                email: "thisisnotauser@fake.com".into(),
+            })
+            .await;
+
+        response.assert_status_see_other();
+
+        let location = response
+            .headers()
+            .get("location")
+            .expect("unable to get redirect location header from response")
+            .to_str()
+            .unwrap();
+
+        assert_eq!(location, "/auth/login", "redirected to the wrong page");
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn reset_password_allows_login_with_new_password(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let user: RegisterUser = Faker.fake();
+
+        let saved_user = create_confirmed_user(user.clone(), &pool).await;
+        let reset_token = PasswordResetToken::create(saved_user.id, &pool)
+            .await
+            .expect("failed to create reset token in test db");
+
+        let response = request
+            .post("/auth/password/reset")
+            .form(&ResetPassword {
+                reset_token: reset_token.reset_token,
+                password: "n3wPa$$word".into(),
+                confirm_password: "n3wPa$$word".into(),
+            })
+            .await;
+
+        response.assert_status_see_other();
+
+        let response = request
+            .post("/auth/login")
+            .form(&UserCredentials {
+                email: user.email.clone(),
+                password: user.password,
+                next: None,
+            })
+            .await;
+
+        assert!(
+            response.maybe_cookie("id").is_none(),
+            "the old password should no longer log the user in"
+        );
+
+        let response = request
+            .post("/auth/login")
+            .form(&UserCredentials {
+                email: user.email,
+                password: "n3wPa$$word".into(),
+                next: None,
+            })
+            .await;
+
+        assert!(
+            response.maybe_cookie("id").is_some(),
+            "the new password should log the user in"
+        );
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn reset_password_token_can_only_be_used_once(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let user: RegisterUser = Faker.fake();
+
+        let saved_user = User::create(user, &pool)
+            .await
+            .expect("failed to create user in test db");
+        let reset_token = PasswordResetToken::create(saved_user.id, &pool)
+            .await
+            .expect("failed to create reset token in test db");
+
+        let form = ResetPassword {
+            reset_token: reset_token.reset_token,
+            password: "n3wPa$$word".into(),
+            confirm_password: "n3wPa$$word".into(),
+        };
+
+        request
+            .post("/auth/password/reset")
+            .form(&form)
+            .await
+            .assert_status_see_other();
+
+        request
+            .post("/auth/password/reset")
+            .form(&form)
+            .await
+            .assert_status_unauthorized();
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn reset_password_invalidates_existing_sessions(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |mut request| async move {
+        request.save_cookies();
+
+        let user: RegisterUser = Faker.fake();
+
+        let saved_user = create_confirmed_user(user.clone(), &pool).await;
+
+        request
+            .post("/auth/login")
+            .form(&UserCredentials {
+                email: user.email,
+                password: user.password,
+                next: None,
+            })
+            .await;
+
+        // The logged in session has access to protected routes
+        request.get("/todos").await.assert_status_ok();
+
+        let reset_token = PasswordResetToken::create(saved_user.id, &pool)
+            .await
+            .expect("failed to create reset token in test db");
+
+        request
+            .post("/auth/password/reset")
+            .form(&ResetPassword {
+                reset_token: reset_token.reset_token,
+                password: "n3wPa$$word".into(),
+                confirm_password: "n3wPa$$word".into(),
+            })
+            .await
+            .assert_status_see_other();
+
+        let response = request.get("/todos").await;
+
+        let location = response
+            .headers()
+            .get("location")
+            .expect("the old session should be redirected to the login page")
+            .to_str()
+            .unwrap();
+
+        assert!(
+            location.starts_with("/auth/login"),
+            "the old session should no longer be authenticated"
+        );
+    })
+    .await
+}
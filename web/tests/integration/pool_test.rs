@@ -0,0 +1,63 @@
+use super::temp_database;
+use nohead_rs_config::ReadPoolConfig;
+use nohead_rs_db::{connect_pool, connect_read_pool, migrate};
+
+#[tokio::test]
+async fn pool_enforces_foreign_keys_in_wal_mode() {
+    let config = temp_database("pool");
+    migrate::run_on_startup(&config).await.unwrap();
+
+    let db_pool = connect_pool(&config).await.unwrap();
+
+    let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+        .fetch_one(&db_pool)
+        .await
+        .unwrap();
+    assert_eq!(journal_mode, "wal");
+
+    let result =
+        sqlx::query("INSERT INTO registration_tokens (register_token, user_id) VALUES ('1', 999)")
+            .execute(&db_pool)
+            .await;
+    assert!(
+        result.is_err(),
+        "tokens should not be created for users that don't exist"
+    );
+}
+
+#[tokio::test]
+async fn read_pool_splits_reads_from_a_single_writer() {
+    let mut config = temp_database("read-pool");
+    config.read_pool = Some(ReadPoolConfig {
+        max_connections: 4,
+        min_connections: 0,
+    });
+    migrate::run_on_startup(&config).await.unwrap();
+
+    let db_pool = connect_pool(&config).await.unwrap();
+    let db_read_pool = connect_read_pool(&config, &db_pool).await.unwrap();
+
+    assert_eq!(db_pool.options().get_max_connections(), 1);
+    assert_eq!(db_read_pool.options().get_max_connections(), 4);
+
+    sqlx::query(
+        "INSERT INTO users (id, email, password_hash, status) VALUES (1, 'jane@example.com', 'hash', 'confirmed')",
+    )
+    .execute(&db_pool)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO todos (description, user_id) VALUES ('written', 1)")
+        .execute(&db_pool)
+        .await
+        .unwrap();
+    let count: i64 = sqlx::query_scalar("SELECT count(*) FROM todos")
+        .fetch_one(&db_read_pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 1, "reads should see committed writes");
+
+    let result = sqlx::query("INSERT INTO todos (description, user_id) VALUES ('read only', 1)")
+        .execute(&db_read_pool)
+        .await;
+    assert!(result.is_err(), "the read pool should not be able to write");
+}
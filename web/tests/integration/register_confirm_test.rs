@@ -0,0 +1,222 @@
+use super::test_request_with_db;
+use fake::{Fake as _, Faker};
+use nohead_rs_db::{
+    DbPool, MIGRATOR,
+    entities::{
+        register_token::{
+            MAX_REGISTER_TOKEN_ATTEMPTS, MAX_REGISTER_TOKEN_RESENDS, RegisterToken,
+            RegisterTokenResend, RegisterTokenValidate,
+        },
+        user::{RegisterUser, User, UserStatus},
+    },
+};
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn verify_confirms_user_and_consumes_token(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let user: RegisterUser = Faker.fake();
+
+        let user = User::create(user, &pool)
+            .await
+            .expect("failed to create user in test db");
+        let register_token = RegisterToken::create(user.id, &pool)
+            .await
+            .expect("failed to create register token in test db");
+
+        let form = RegisterTokenValidate {
+            email: user.email.clone(),
+            register_token: register_token.register_token,
+        };
+
+        request
+            .post("/auth/register/confirm")
+            .form(&form)
+            .await
+            .assert_status_see_other();
+
+        let user = User::try_get_by_id(&user.id, &pool)
+            .await
+            .unwrap()
+            .expect("user should still exist");
+
+        assert!(
+            matches!(user.status, UserStatus::Confirmed),
+            "the user should be confirmed"
+        );
+
+        request
+            .post("/auth/register/confirm")
+            .form(&form)
+            .await
+            .assert_status_unauthorized();
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn verify_rejects_expired_token(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let user: RegisterUser = Faker.fake();
+
+        let user = User::create(user, &pool)
+            .await
+            .expect("failed to create user in test db");
+        let register_token = RegisterToken::create(user.id, &pool)
+            .await
+            .expect("failed to create register token in test db");
+
+        sqlx::query!(
+            "UPDATE registration_tokens SET expires_at = DATETIME(CURRENT_TIMESTAMP, '-1 minutes') WHERE user_id = ?",
+            user.id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        request
+            .post("/auth/register/confirm")
+            .form(&RegisterTokenValidate {
+                email: user.email,
+                register_token: register_token.register_token,
+            })
+            .await
+            .assert_status(axum::http::StatusCode::GONE);
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn verify_invalidates_token_after_too_many_attempts(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let user: RegisterUser = Faker.fake();
+
+        let user = User::create(user, &pool)
+            .await
+            .expect("failed to create user in test db");
+        let register_token = RegisterToken::create(user.id, &pool)
+            .await
+            .expect("failed to create register token in test db");
+
+        let wrong_guess = RegisterTokenValidate {
+            email: user.email.clone(),
+            register_token: "wrong1".into(),
+        };
+
+        for _ in 1..MAX_REGISTER_TOKEN_ATTEMPTS {
+            request
+                .post("/auth/register/confirm")
+                .form(&wrong_guess)
+                .await
+                .assert_status_unauthorized();
+        }
+
+        request
+            .post("/auth/register/confirm")
+            .form(&wrong_guess)
+            .await
+            .assert_status(axum::http::StatusCode::TOO_MANY_REQUESTS);
+
+        // Even the right token no longer works once it has been invalidated
+        request
+            .post("/auth/register/confirm")
+            .form(&RegisterTokenValidate {
+                email: user.email,
+                register_token: register_token.register_token,
+            })
+            .await
+            .assert_status_unauthorized();
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn resend_invalidates_previous_token(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let user: RegisterUser = Faker.fake();
+
+        let user = User::create(user, &pool)
+            .await
+            .expect("failed to create user in test db");
+        let previous_token = RegisterToken::create(user.id, &pool)
+            .await
+            .expect("failed to create register token in test db");
+
+        request
+            .post("/auth/register/resend")
+            .form(&RegisterTokenResend {
+                email: user.email.clone(),
+            })
+            .await
+            .assert_status_see_other();
+
+        let new_token = RegisterToken::try_get_by_email(&user.email, &pool)
+            .await
+            .unwrap()
+            .expect("a new register token should have been created");
+
+        assert_ne!(
+            previous_token.register_token, new_token.register_token,
+            "the previous register token should have been replaced"
+        );
+    })
+    .await
+}
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn resends_are_throttled_until_the_token_expires(pool: DbPool) {
+    test_request_with_db::<_, _>(pool.clone(), |request| async move {
+        let user: RegisterUser = Faker.fake();
+
+        let user = User::create(user, &pool)
+            .await
+            .expect("failed to create user in test db");
+        RegisterToken::create(user.id, &pool)
+            .await
+            .expect("failed to create register token in test db");
+
+        let resend = || async {
+            request
+                .post("/auth/register/resend")
+                .form(&RegisterTokenResend {
+                    email: user.email.clone(),
+                })
+                .await
+                .assert_status_see_other();
+
+            RegisterToken::try_get_by_email(&user.email, &pool)
+                .await
+                .unwrap()
+                .expect("the user should still have a register token")
+        };
+
+        for _ in 0..MAX_REGISTER_TOKEN_RESENDS {
+            resend().await;
+        }
+        let last_token = RegisterToken::try_get_by_email(&user.email, &pool)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            resend().await.register_token,
+            last_token.register_token,
+            "no new register token should be sent once the resends are used up"
+        );
+
+        sqlx::query!(
+            "UPDATE registration_tokens SET expires_at = DATETIME(CURRENT_TIMESTAMP, '-1 minutes') WHERE user_id = ?",
+            user.id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let new_token = resend().await;
+        assert_ne!(
+            new_token.register_token, last_token.register_token,
+            "a new register token should be sent once the previous one expired"
+        );
+        assert_eq!(new_token.resends, 0);
+    })
+    .await
+}
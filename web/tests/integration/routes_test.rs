@@ -0,0 +1,71 @@
+use crate::mock_logged_in_state;
+
+use axum::http::{Method, StatusCode};
+use axum_test::{TestServer, TestServerBuilder};
+use nohead_rs_config::Environment;
+use nohead_rs_db::{DbPool, MIGRATOR};
+use nohead_rs_web::{app::App, router::RouteInfo, state::AppState};
+
+#[sqlx::test(migrator = "MIGRATOR")]
+async fn route_table_lists_the_paths_and_methods_of_the_router(pool: DbPool) {
+    let mut app_state = AppState::build(Environment::Test)
+        .await
+        .expect("failed to build app state");
+    app_state.db_read_pool = pool.clone();
+    app_state.db_pool = pool.clone();
+
+    let app = App::build(app_state).expect("failed to boot test app");
+
+    let routes = app.routes.routes();
+
+    for (path, methods) in [
+        ("/auth/login", vec!["GET", "POST"]),
+        ("/todos/{id}", vec!["GET", "PUT", "DELETE"]),
+        ("/api/v1/tokens/{id}", vec!["DELETE"]),
+        ("/static/{*rest}", vec!["*"]),
+    ] {
+        assert!(
+            routes.contains(&RouteInfo {
+                path: path.to_string(),
+                methods: methods.into_iter().map(str::to_string).collect(),
+            }),
+            "{path} should be listed in {routes:?}"
+        );
+    }
+    assert!(
+        !routes.iter().any(|route| route.path.starts_with("/_dev")),
+        "the mailbox is only mounted in development"
+    );
+
+    // Every listed route is served by the router with exactly the listed methods, paths that
+    // aren't routed at all are not found rather than not allowed. Logged in, as the login
+    // redirect answers every method of the protected routes, until the logout at the end.
+    let config = TestServerBuilder::new()
+        .transport(axum_test::Transport::HttpRandomPort)
+        .save_cookies()
+        .into_config();
+    let server =
+        TestServer::new_with_config(app.router, config).expect("failed to start test server");
+    mock_logged_in_state(&server, &pool).await;
+    let mut routes = routes;
+    routes.sort_by_key(|route| route.path == "/auth/logout");
+    for route in routes.iter().filter(|route| route.methods != ["*"]) {
+        let path = route.path.replace("{id}", "1");
+        for method in [
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::DELETE,
+            Method::PATCH,
+        ] {
+            let status = server.method(method.clone(), &path).await.status_code();
+
+            assert_eq!(
+                status == StatusCode::METHOD_NOT_ALLOWED,
+                !route.methods.contains(&method.to_string()),
+                "{method} {path} answered {status}, the route accepts {:?}",
+                route.methods
+            );
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use apalis::prelude::*;
 use nohead_rs_db::DbPool;
 use nohead_rs_mailer::{EmailClient, EmailPayload};
+use serde::Serialize;
 use tokio::task::JoinHandle;
 
 mod jobs;
@@ -40,6 +41,39 @@ impl Worker {
     }
 }
 
+/// An email job that hasn't been sent yet, or whose sending failed.
+#[derive(Serialize, Clone, Debug)]
+pub struct QueuedEmail {
+    pub id: String,
+    /// `Pending`, `Running`, `Failed` or `Killed`.
+    pub status: String,
+    pub attempts: usize,
+    pub last_error: Option<String>,
+    pub email: EmailPayload,
+}
+
+/// Lists the email jobs that are waiting to be sent, running or failed, the latest 10 of each.
+pub async fn queued_emails(
+    storage: &WorkerStorage<EmailPayload>,
+) -> Result<Vec<QueuedEmail>, Error> {
+    let mut queued = vec![];
+    for status in [State::Pending, State::Running, State::Failed, State::Killed] {
+        for job in storage.list_jobs(&status, 1).await? {
+            // The parts of the stored job are the context of the listed request.
+            let parts = job.parts.context;
+            queued.push(QueuedEmail {
+                id: parts.task_id.to_string(),
+                status: status.to_string(),
+                attempts: parts.attempt.current(),
+                last_error: parts.context.last_error().clone(),
+                email: job.args,
+            });
+        }
+    }
+
+    Ok(queued)
+}
+
 /// Errors that can occur as a result of a data layer operation.
 #[derive(thiserror::Error, Debug)]
 pub enum Error {